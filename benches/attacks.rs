@@ -0,0 +1,93 @@
+//! Compares the attack tables with the ray walking they replaced.
+//!
+//! Run with `cargo bench --bench attacks`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use analyzer::attacks;
+use analyzer::bitboard::Bitboard;
+
+const ROUNDS: usize = 200;
+
+type Lookup = fn(u64, u8) -> u64;
+
+/// The former `utils::compute_attack_squares`: steps along each delta until
+/// it leaves the board, wraps around a file edge or hits a blocker.
+fn ray_walk(occupancy: u64, init_pos: i8, deltas: &[i8], step_only: bool) -> u64 {
+    let mut attack_bitboard: u64 = 0;
+    for delta in deltas {
+        let mut last_pos = init_pos;
+        loop {
+            let pos = last_pos + delta;
+            let file_diff = (pos & 0x7) - (last_pos & 0x7);
+            if !(0..64).contains(&pos) || !(-2..=2).contains(&file_diff) {
+                break;
+            }
+            attack_bitboard |= 1 << pos;
+            if step_only || occupancy & (1 << pos) != 0 {
+                break;
+            }
+            last_pos = pos;
+        }
+    }
+    attack_bitboard
+}
+
+const KNIGHT: [i8; 8] = [17, 15, 10, 6, -17, -15, -10, -6];
+const KING: [i8; 8] = [9, 8, 7, 1, -9, -8, -7, -1];
+const BISHOP: [i8; 4] = [9, 7, -9, -7];
+const ROOK: [i8; 4] = [1, 8, -1, -8];
+
+/// Occupancies of a middlegame-like density.
+fn occupancies() -> Vec<u64> {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    (0..64)
+        .map(|_| {
+            let mut next = || {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            };
+            next() & next()
+        })
+        .collect()
+}
+
+fn time(name: &str, occupancies: &[u64], attacks: impl Fn(u64, u8) -> u64) -> Duration {
+    let start = Instant::now();
+    let mut sum = 0u64;
+    for _ in 0..ROUNDS {
+        for &occupied in occupancies {
+            for square in 0..64 {
+                sum = sum.wrapping_add(attacks(black_box(occupied), black_box(square)));
+            }
+        }
+    }
+    black_box(sum);
+    let elapsed = start.elapsed();
+    let lookups = (ROUNDS * occupancies.len() * 64) as f64;
+    println!("{:<28} {:>8.2} ns per lookup", name, elapsed.as_nanos() as f64 / lookups);
+    elapsed
+}
+
+fn main() {
+    let occupancies = occupancies();
+    // fill the magic tables before timing
+    black_box(attacks::rook(0, Bitboard(0)));
+
+    let pieces: [(&str, &[i8], bool, Lookup); 4] = [
+        ("knight", &KNIGHT, true, |_, square| attacks::knight(square).get()),
+        ("king", &KING, true, |_, square| attacks::king(square).get()),
+        ("bishop", &BISHOP, false, |occupied, square| attacks::bishop(square, Bitboard(occupied)).get()),
+        ("rook", &ROOK, false, |occupied, square| attacks::rook(square, Bitboard(occupied)).get()),
+    ];
+    for (name, deltas, step_only, table) in pieces {
+        let walked = time(&format!("{} ray walk", name), &occupancies, |occupied, square| {
+            ray_walk(occupied, square as i8, deltas, step_only)
+        });
+        let looked_up = time(&format!("{} attacks::{}", name, name), &occupancies, table);
+        println!("{:<28} {:>8.1}x\n", "speedup", walked.as_secs_f64() / looked_up.as_secs_f64());
+    }
+}
@@ -0,0 +1,168 @@
+//! Annotated PGN output.
+//!
+//! Writes an analysed game back as PGN with the engine's findings attached:
+//! an `[%eval]` comment after every move, a NAG on every inaccuracy, mistake
+//! and blunder, and the engine's best line as a variation at those moves.
+//! The result opens in any PGN viewer.
+
+use crate::{
+    board::Board,
+    classification::{ClassificationThresholds, MoveClass, MoveReview},
+    engine::analysis::Score,
+    game_analysis::PlyAnalysis,
+    pgn_header::PgnHeaders,
+    r#move::Move,
+};
+
+/// Longest engine line inserted as a variation, in plies
+const MAX_VARIATION_PLIES: usize = 8;
+
+/// PGN export lines are kept below 80 characters
+const MAX_LINE_LENGTH: usize = 79;
+
+/// Writes an analysed game as an annotated PGN.
+///
+/// Inaccuracies, mistakes and blunders, as judged by `thresholds`, get the
+/// engine's best line from the position before them as a variation.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::annotate::annotate_game;
+/// use analyzer::classification::ClassificationThresholds;
+/// use analyzer::engine::analysis::Analysis;
+/// use analyzer::engine::backend::SearchLimits;
+/// use analyzer::engine::mock::MockBackend;
+/// use analyzer::game_analysis::analyse_game;
+/// use analyzer::pgn::Pgn;
+///
+/// let mut backend = MockBackend::new(vec![Analysis::from_uci_lines([
+///     "info depth 10 score cp 20 pv e2e4",
+///     "bestmove e2e4",
+/// ])]);
+/// let mut pgn = Pgn::new("[White \"Alice\"]\n\n1. e4 *".to_string());
+/// let plies = analyse_game(&mut backend, &mut pgn, &SearchLimits::depth(10)).unwrap();
+///
+/// let annotated = annotate_game(&pgn.headers, &plies, &ClassificationThresholds::default());
+/// assert!(annotated.starts_with("[White \"Alice\"]"));
+/// assert!(annotated.contains("1. e4 { [%eval -0.20] }"));
+/// ```
+pub fn annotate_game(headers: &PgnHeaders, plies: &[PlyAnalysis], thresholds: &ClassificationThresholds) -> String {
+    let mut pgn = String::new();
+    let result = headers.result().unwrap_or("*");
+    for (name, value) in headers.tags() {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, escape_tag_value(value)));
+    }
+    if headers.result().is_none() {
+        pgn.push_str("[Result \"*\"]\n");
+    }
+    pgn.push('\n');
+
+    let mut tokens = Vec::new();
+    // a game whose start position cannot be read has no analysed plies
+    let mut board = headers.start_setup().map_or_else(|_| Board::init(), |setup| setup.board().clone());
+    for ply in plies {
+        let review = MoveReview::from_ply(ply, thresholds);
+        let class = review.as_ref().map(|review| review.class);
+
+        // the move number stays on the same line as its move
+        let mut san = format!("{} {}", move_number(ply.ply, true), ply.san.trim_end_matches(['!', '?']));
+        if let Some(nag) = class.and_then(|class| class.nag()) {
+            san.push_str(&format!(" ${}", nag));
+        }
+        tokens.push(san);
+
+        if let Some(score) = ply.analysis.score() {
+            if let Some(eval) = format_eval(score, ply.ply + 1) {
+                tokens.push(format!("{{ [%eval {}] }}", eval));
+            }
+        }
+
+        if class.is_some_and(|class| class >= MoveClass::Inaccuracy) {
+            if let Some(line) = ply.before.best_line() {
+                let variation = variation_san(&board, &line.pv, ply.ply);
+                if !variation.is_empty() {
+                    tokens.push(format!("( {} )", variation.join(" ")));
+                }
+            }
+        }
+
+        match Move::from_uci(&board, &ply.uci, ply.ply as u16) {
+            Ok(mov) => {
+                board.apply_move(&mov);
+            }
+            Err(_) => break,
+        }
+    }
+    tokens.push(result.to_string());
+
+    pgn.push_str(&wrap(&tokens));
+    pgn.push('\n');
+    pgn
+}
+
+/// Move number prefix of a ply: `12.` for White, `12...` for Black.
+/// Every Black move gets one, since every move is followed by a comment.
+fn move_number(ply: usize, with_black: bool) -> String {
+    if ply.is_multiple_of(2) {
+        format!("{}.", ply / 2 + 1)
+    } else if with_black {
+        format!("{}...", ply / 2 + 1)
+    } else {
+        String::new()
+    }
+}
+
+/// Formats an engine score for `[%eval]`: pawns or mate distance from White's
+/// point of view. `side_to_move_ply` is the ply index of the side to move.
+/// Returns `None` for a checkmated side to move, which has no evaluation.
+fn format_eval(score: Score, side_to_move_ply: usize) -> Option<String> {
+    let sign = if side_to_move_ply.is_multiple_of(2) { 1 } else { -1 };
+    match score {
+        Score::Mate(0) => None,
+        Score::Mate(moves) => Some(format!("#{}", moves * sign)),
+        Score::Centipawns(cp) => Some(format!("{:.2}", (cp * sign) as f64 / 100.0)),
+    }
+}
+
+/// Converts the start of an engine line into SAN, stopping at the first
+/// illegal move.
+fn variation_san(board: &Board, pv: &[String], first_ply: usize) -> Vec<String> {
+    let mut board = board.clone();
+    let mut tokens = Vec::new();
+    for (offset, uci) in pv.iter().take(MAX_VARIATION_PLIES).enumerate() {
+        let ply = first_ply + offset;
+        let Ok(mov) = Move::from_uci(&board, uci, ply as u16) else {
+            break;
+        };
+        let number = move_number(ply, offset == 0);
+        if !number.is_empty() {
+            tokens.push(number);
+        }
+        tokens.push(mov.san.clone());
+        board.apply_move(&mov);
+    }
+    tokens
+}
+
+fn escape_tag_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Joins movetext tokens into lines of at most [`MAX_LINE_LENGTH`] characters.
+fn wrap(tokens: &[String]) -> String {
+    let mut text = String::new();
+    let mut line_length = 0;
+    for token in tokens.iter().filter(|token| !token.is_empty()) {
+        if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+            text.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            text.push(' ');
+            line_length += 1;
+        }
+        text.push_str(token);
+        line_length += token.len();
+    }
+    text
+}
@@ -0,0 +1,246 @@
+//! Attack tables.
+//!
+//! The squares a piece attacks from a square, as a bitboard (bit `n` is
+//! square `n`, a1 = 0, h8 = 63). Knight, king and pawn attacks depend only on
+//! the square and are looked up in tables built at compile time. Bishop and
+//! rook attacks also depend on the pieces in the way; they are looked up with
+//! magic bitboards: the occupied squares on the piece's lines are multiplied
+//! by a per-square magic number whose top bits index a table holding the
+//! attacks for every arrangement of blockers. The tables are filled on first
+//! use. Attacked squares include the first blocker in each direction,
+//! whatever its color.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use analyzer::attacks;
+//! use analyzer::bitboard::Bitboard;
+//!
+//! // a rook on a1 with a blocker on a4 sees a2, a3, a4 and the first rank
+//! let attacks = attacks::rook(0, Bitboard(1 << 24));
+//! assert_eq!(attacks, Bitboard(0x0101_01fe));
+//! assert_eq!(attacks::knight(0), Bitboard::from_square(10) | Bitboard::from_square(17));
+//! ```
+
+use std::sync::OnceLock;
+
+use crate::{bitboard::Bitboard, color::Color, components::Piece};
+
+/// Squares a knight on `square` attacks.
+pub fn knight(square: u8) -> Bitboard {
+    Bitboard(KNIGHT_ATTACKS[square as usize])
+}
+
+/// Squares a king on `square` attacks.
+pub fn king(square: u8) -> Bitboard {
+    Bitboard(KING_ATTACKS[square as usize])
+}
+
+/// Squares a pawn of `color` on `square` attacks (its captures, not its
+/// pushes).
+pub fn pawn(color: Color, square: u8) -> Bitboard {
+    match color {
+        Color::White => Bitboard(PAWN_ATTACKS[0][square as usize]),
+        Color::Black => Bitboard(PAWN_ATTACKS[1][square as usize]),
+    }
+}
+
+/// Squares a bishop on `square` attacks when `occupied` are occupied.
+pub fn bishop(square: u8, occupied: Bitboard) -> Bitboard {
+    let tables = tables();
+    Bitboard(tables.attacks[tables.bishops[square as usize].index(occupied.get())])
+}
+
+/// Squares a rook on `square` attacks when `occupied` are occupied.
+pub fn rook(square: u8, occupied: Bitboard) -> Bitboard {
+    let tables = tables();
+    Bitboard(tables.attacks[tables.rooks[square as usize].index(occupied.get())])
+}
+
+/// Squares a queen on `square` attacks when `occupied` are occupied.
+pub fn queen(square: u8, occupied: Bitboard) -> Bitboard {
+    bishop(square, occupied) | rook(square, occupied)
+}
+
+/// Squares a `piece` of `color` on `square` attacks when `occupied` are
+/// occupied. The color only matters for pawns.
+pub fn piece(piece: Piece, color: Color, square: u8, occupied: Bitboard) -> Bitboard {
+    match piece {
+        Piece::Pawn => pawn(color, square),
+        Piece::Knight => knight(square),
+        Piece::Bishop => bishop(square, occupied),
+        Piece::Rook => rook(square, occupied),
+        Piece::Queen => queen(square, occupied),
+        Piece::King => king(square),
+    }
+}
+
+const KNIGHT_ATTACKS: [u64; 64] = leaper_table(&[(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)]);
+const KING_ATTACKS: [u64; 64] = leaper_table(&[(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)]);
+const PAWN_ATTACKS: [[u64; 64]; 2] = [leaper_table(&[(-1, 1), (1, 1)]), leaper_table(&[(-1, -1), (1, -1)])];
+
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (0, -1), (-1, 0), (0, 1)];
+
+/// Attacks of a piece stepping once by each (file, rank) offset, per square.
+const fn leaper_table(steps: &[(i8, i8)]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < steps.len() {
+            let file = (square % 8) as i8 + steps[i].0;
+            let rank = (square / 8) as i8 + steps[i].1;
+            if file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                table[square] |= 1 << (rank * 8 + file);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+/// Attacks of a slider on `square`, found by walking each direction until
+/// the edge or the first occupied square. Used to fill the magic tables.
+fn slider_attacks(square: u8, occupied: u64, directions: &[(i8, i8); 4]) -> u64 {
+    let mut attacks = 0;
+    for &(file_step, rank_step) in directions {
+        let (mut file, mut rank) = ((square % 8) as i8, (square / 8) as i8);
+        loop {
+            file += file_step;
+            rank += rank_step;
+            if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+                break;
+            }
+            let bit = 1 << (rank * 8 + file);
+            attacks |= bit;
+            if occupied & bit != 0 {
+                break;
+            }
+        }
+    }
+    attacks
+}
+
+/// Squares whose occupancy changes what a slider on `square` attacks: its
+/// lines without the edge squares they run into, which it attacks whether or
+/// not they are occupied.
+fn relevant_occupancy(square: u8, directions: &[(i8, i8); 4]) -> u64 {
+    let (file, rank) = (square % 8, square / 8);
+    let mut edges = 0;
+    if file != 0 {
+        edges |= FILE_A;
+    }
+    if file != 7 {
+        edges |= FILE_A << 7;
+    }
+    if rank != 0 {
+        edges |= RANK_1;
+    }
+    if rank != 7 {
+        edges |= RANK_1 << 56;
+    }
+    slider_attacks(square, 0, directions) & !edges
+}
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const RANK_1: u64 = 0xff;
+
+/// Where the attacks of a slider on one square live in the shared table.
+#[derive(Debug, Clone, Copy, Default)]
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupied: u64) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+struct Tables {
+    bishops: [Magic; 64],
+    rooks: [Magic; 64],
+    /// Attacks of all squares of both sliders, 5248 bishop entries followed
+    /// by 102400 rook entries
+    attacks: Vec<u64>,
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut attacks = Vec::new();
+        let bishops = fill(&mut attacks, &BISHOP_DIRECTIONS, &BISHOP_MAGICS);
+        let rooks = fill(&mut attacks, &ROOK_DIRECTIONS, &ROOK_MAGICS);
+        Tables { bishops, rooks, attacks }
+    })
+}
+
+/// Appends the attacks of a slider for every square and every arrangement
+/// of blockers to `attacks`.
+fn fill(attacks: &mut Vec<u64>, directions: &[(i8, i8); 4], magics: &[u64; 64]) -> [Magic; 64] {
+    let mut entries = [Magic::default(); 64];
+    for square in 0..64u8 {
+        let mask = relevant_occupancy(square, directions);
+        let bits = mask.count_ones();
+        let magic = Magic { mask, magic: magics[square as usize], shift: 64 - bits, offset: attacks.len() };
+        attacks.resize(attacks.len() + (1 << bits), 0);
+
+        // every subset of the mask (Carry-Rippler enumeration)
+        let mut occupied = 0u64;
+        loop {
+            attacks[magic.index(occupied)] = slider_attacks(square, occupied, directions);
+            occupied = occupied.wrapping_sub(mask) & mask;
+            if occupied == 0 {
+                break;
+            }
+        }
+        entries[square as usize] = magic;
+    }
+    entries
+}
+
+/// Magic numbers mapping every arrangement of blockers on a slider's lines
+/// to a distinct slot (or one with the same attacks), found by trying random
+/// sparse numbers.
+const BISHOP_MAGICS: [u64; 64] = [
+    0xa010041108003100, 0x006082020a002900, 0x6810010619200000, 0x08281a0520000408,
+    0x0001104001000400, 0x0018901008048400, 0x00040a0210245280, 0x000200210808a402,
+    0x9140048410821200, 0x0800091010820041, 0x20504804832202c0, 0x0100091401081000,
+    0x8021011140000012, 0x0810020804450400, 0x208b0542109008a2, 0x0080084a08040204,
+    0x0040e2a80811244c, 0x2505022008008108, 0x0430220100420040, 0x010a040420220040,
+    0x1105000290400000, 0x0093001200822120, 0x4000a62048043004, 0x280120048a015004,
+    0x006090002a020814, 0x44042000240800d0, 0x01102800040a4400, 0x1004080080220040,
+    0x0001001011004024, 0x0010044000805040, 0x0914041200820100, 0x0004821012821480,
+    0x0024040500c05021, 0x0088611002080200, 0x0116080a00040020, 0x4000020080080080,
+    0x2450450140840040, 0x0000880201484100, 0x0222020404020092, 0x8081110600002e00,
+    0x2842101105000801, 0x1100809008001025, 0x00020202221c0400, 0x0422014022009020,
+    0x0210046102100c00, 0xc004008082029102, 0x00aa461801101200, 0x0404080080201108,
+    0x020542108c205002, 0x0410544804100100, 0x0040910841100000, 0x0400200042021100,
+    0x00004204850400c0, 0x0200100410a42102, 0x1040020801210102, 0x0805040410420000,
+    0x2884804130100200, 0x800c262201242000, 0x1058000194108800, 0x0014221054420204,
+    0x0104000012a02200, 0x0200881003300100, 0x0140400202840100, 0x0402020801010201,
+];
+
+const ROOK_MAGICS: [u64; 64] = [
+    0x1080004008801020, 0x0840092002c03000, 0x1900200010400900, 0x0880100008000480,
+    0x4200100420080200, 0x8100020100080400, 0x0200040110886200, 0x0200008040220411,
+    0x0404800084400220, 0x0000401000402000, 0x0086001081220440, 0x0408800800100280,
+    0x000a001201040820, 0x8848800200840080, 0x4001000100040200, 0x0442000102105084,
+    0x9080010020804100, 0x0040404000201009, 0x0000808010002009, 0x2200090021d00100,
+    0x0008008008040080, 0x0004004002010040, 0x0011040008015042, 0x00000a0001768104,
+    0x0000800080204009, 0x2010004140002001, 0x9800200280100080, 0x1000100080080080,
+    0x0442000a00049020, 0x2100040080020080, 0x0800120400900148, 0x0010040a00128541,
+    0x2800804000800030, 0x1010002000400041, 0x4000200011004100, 0x0610008410800800,
+    0x0400802402800800, 0xc100020080800400, 0x0002000802000401, 0x0182085882000401,
+    0x0220204000808000, 0x2860100040024022, 0x0001002004110040, 0x99101042000a0020,
+    0x0004080004008080, 0x0010040002008080, 0x2012004881020004, 0x8300842444820011,
+    0x0088403882010200, 0x0820400080210100, 0x0110910040a00300, 0x0801100280080480,
+    0x0242009008200600, 0x1002000489500200, 0x0040800200010080, 0x0091800041000080,
+    0x0000209300488001, 0x04c1002414824001, 0x020020000b001041, 0x7000100004200901,
+    0x8002002004100802, 0x30010002084c0007, 0x0888221800813004, 0x4000002840840112,
+];
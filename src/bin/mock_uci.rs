@@ -0,0 +1,133 @@
+//! Scriptable fake UCI engine for testing engine clients without Stockfish.
+//!
+//! Usage: `mock_uci <script> [log]`
+//!
+//! The script is a text file. Lines before the first `[go]` header are sent in
+//! reply to `uci`, before `uciok`. Every `[go]` block is the reply to one `go`
+//! command, in order; once the blocks run out the last one is repeated.
+//! `isready` is always answered with `readyok` and `quit` exits.
+//!
+//! Besides plain output lines a block may contain directives:
+//!
+//! - `@sleep <ms>` pauses before the next line
+//! - `@hang` stops answering, to exercise client timeouts
+//! - `@exit <code>` exits immediately, to exercise crash handling
+//!
+//! Empty lines and lines starting with `#` are ignored. When a log path is
+//! given, every command received is appended to it, one per line.
+//!
+//! ```text
+//! id name MockEngine
+//! option name MultiPV type spin default 1 min 1 max 500
+//! [go]
+//! info depth 12 multipv 1 score cp 35 pv e2e4 e7e5
+//! bestmove e2e4 ponder e7e5
+//! ```
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+use std::{env, process, thread};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let Some(script_path) = args.get(1) else {
+        eprintln!("usage: mock_uci <script> [log]");
+        process::exit(2);
+    };
+    let script = fs::read_to_string(script_path).unwrap_or_else(|e| {
+        eprintln!("mock_uci: could not read script '{}': {}", script_path, e);
+        process::exit(2);
+    });
+    let mut log = args.get(2).map(|path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap_or_else(|e| {
+                eprintln!("mock_uci: could not open log '{}': {}", path, e);
+                process::exit(2);
+            })
+    });
+
+    let (handshake, searches) = parse_script(&script);
+    let mut search_index = 0;
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if let Some(log) = log.as_mut() {
+            record(log, &line);
+        }
+
+        match line.split_whitespace().next().unwrap_or("") {
+            "uci" => {
+                play(&handshake);
+                reply("uciok");
+            }
+            "isready" => reply("readyok"),
+            "go" => {
+                if let Some(block) = searches.get(search_index).or(searches.last()) {
+                    play(block);
+                }
+                search_index += 1;
+            }
+            "quit" => break,
+            _ => {}
+        }
+    }
+}
+
+/// Splits the script into the handshake lines and one block per `[go]` header.
+fn parse_script(script: &str) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut handshake = Vec::new();
+    let mut searches: Vec<Vec<String>> = Vec::new();
+
+    for line in script.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line == "[go]" {
+            searches.push(Vec::new());
+            continue;
+        }
+        match searches.last_mut() {
+            Some(block) => block.push(line.to_string()),
+            None => handshake.push(line.to_string()),
+        }
+    }
+
+    (handshake, searches)
+}
+
+/// Sends the lines of a block, executing directives along the way.
+fn play(lines: &[String]) {
+    for line in lines {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("@sleep") => {
+                let ms = tokens.next().and_then(|ms| ms.parse().ok()).unwrap_or(0);
+                thread::sleep(Duration::from_millis(ms));
+            }
+            Some("@hang") => loop {
+                thread::sleep(Duration::from_secs(60));
+            },
+            Some("@exit") => {
+                process::exit(tokens.next().and_then(|code| code.parse().ok()).unwrap_or(1));
+            }
+            _ => reply(line),
+        }
+    }
+}
+
+fn reply(line: &str) {
+    let mut stdout = io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+fn record(log: &mut File, command: &str) {
+    let _ = writeln!(log, "{}", command);
+    let _ = log.flush();
+}
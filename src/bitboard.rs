@@ -1,13 +1,204 @@
+//! Sets of squares.
+//!
+//! A [`Bitboard`] holds one bit per square, bit `n` for square `n` (a1 = 0,
+//! b1 = 1, ..., h8 = 63). Sets combine with the bit operators, iterate over
+//! their squares from a1 upwards, and shift a step in a [`Direction`] without
+//! wrapping around the board edge. Masks of ranks, files, diagonals and of
+//! the squares between two squares are built here too, so code working with
+//! positions does not need raw bit arithmetic.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use analyzer::bitboard::{Bitboard, Direction};
+//! use analyzer::square::{File, Rank};
+//!
+//! let pawns = Bitboard::rank(Rank::Second);
+//! let attacks = pawns.shift(Direction::NorthEast) | pawns.shift(Direction::NorthWest);
+//! assert_eq!(attacks, Bitboard::rank(Rank::Third));
+//!
+//! let h_file = Bitboard::file(File::H);
+//! assert_eq!(h_file.shift(Direction::East), Bitboard::EMPTY);
+//! assert_eq!(h_file.popcount(), 8);
+//! assert_eq!(h_file.squares().collect::<Vec<_>>(), [7, 15, 23, 31, 39, 47, 55, 63]);
+//!
+//! // a1 to h8, without either end
+//! assert_eq!(Bitboard::between(0, 63).popcount(), 6);
+//! ```
 
-#[derive(Copy,Clone,Debug)]
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr};
+
+use crate::square::{File, Rank};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
 pub struct Bitboard(pub u64);
 
+/// The eight directions of a step on the board, White's side at the south.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::South,
+        Direction::East,
+        Direction::West,
+        Direction::NorthEast,
+        Direction::NorthWest,
+        Direction::SouthEast,
+        Direction::SouthWest,
+    ];
+}
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = FILE_A << 7;
+const RANK_1: u64 = 0xff;
+/// The a1-h8 diagonal
+const MAIN_DIAGONAL: u64 = 0x8040_2010_0804_0201;
+/// The h1-a8 diagonal
+const MAIN_ANTI_DIAGONAL: u64 = 0x0102_0408_1020_4080;
+
 impl Bitboard {
-    
-    pub fn get(&self)->u64 {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const FULL: Bitboard = Bitboard(u64::MAX);
+
+    /// The set holding only `square`.
+    pub const fn from_square(square: u8) -> Bitboard {
+        Bitboard(1 << square)
+    }
+
+    /// All squares of `file`.
+    pub const fn file(file: File) -> Bitboard {
+        Bitboard(FILE_A << file as u8)
+    }
+
+    /// All squares of `rank`.
+    pub const fn rank(rank: Rank) -> Bitboard {
+        Bitboard(RANK_1 << (8 * rank as u8))
+    }
+
+    /// The file of `square`.
+    pub const fn file_of(square: u8) -> Bitboard {
+        Bitboard(FILE_A << (square % 8))
+    }
+
+    /// The rank of `square`.
+    pub const fn rank_of(square: u8) -> Bitboard {
+        Bitboard(RANK_1 << (8 * (square / 8)))
+    }
+
+    /// The diagonal through `square` running from south-west to north-east.
+    pub const fn diagonal(square: u8) -> Bitboard {
+        let shift = 8 * ((square / 8) as i8 - (square % 8) as i8);
+        if shift >= 0 {
+            Bitboard(MAIN_DIAGONAL << shift)
+        } else {
+            Bitboard(MAIN_DIAGONAL >> -shift)
+        }
+    }
+
+    /// The diagonal through `square` running from south-east to north-west.
+    pub const fn anti_diagonal(square: u8) -> Bitboard {
+        let shift = 8 * ((square / 8) as i8 + (square % 8) as i8 - 7);
+        if shift >= 0 {
+            Bitboard(MAIN_ANTI_DIAGONAL << shift)
+        } else {
+            Bitboard(MAIN_ANTI_DIAGONAL >> -shift)
+        }
+    }
+
+    /// The whole rank, file or diagonal through `a` and `b`, or the empty set
+    /// when they are not on one line (or are the same square).
+    pub fn line(a: u8, b: u8) -> Bitboard {
+        let candidates = [
+            Bitboard::rank_of(a),
+            Bitboard::file_of(a),
+            Bitboard::diagonal(a),
+            Bitboard::anti_diagonal(a),
+        ];
+        match candidates.into_iter().find(|line| line.contains(b)) {
+            Some(line) if a != b => line,
+            _ => Bitboard::EMPTY,
+        }
+    }
+
+    /// The squares strictly between `a` and `b` on their rank, file or
+    /// diagonal, or the empty set when they are not on one line.
+    pub fn between(a: u8, b: u8) -> Bitboard {
+        let line = Bitboard::line(a, b);
+        if line.is_empty() {
+            return line;
+        }
+        let (low, high) = (a.min(b), a.max(b));
+        // squares above `low` and below `high`
+        let span = (u64::MAX << low << 1) & !(u64::MAX << high);
+        line & Bitboard(span)
+    }
+
+    pub fn get(&self) -> u64 {
         self.0
     }
 
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, square: u8) -> bool {
+        self.0 & (1 << square) != 0
+    }
+
+    /// Number of squares in the set.
+    pub fn popcount(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// The lowest square in the set.
+    pub fn lsb(self) -> Option<u8> {
+        (self.0 != 0).then(|| self.0.trailing_zeros() as u8)
+    }
+
+    /// The highest square in the set.
+    pub fn msb(self) -> Option<u8> {
+        (self.0 != 0).then(|| 63 - self.0.leading_zeros() as u8)
+    }
+
+    /// Removes the lowest square from the set and returns it.
+    pub fn pop_lsb(&mut self) -> Option<u8> {
+        let square = self.lsb()?;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+
+    /// The squares in the set, from a1 upwards.
+    pub fn squares(self) -> Squares {
+        Squares(self)
+    }
+
+    /// Every square moved one step in `direction`; squares leaving the board
+    /// drop out.
+    pub const fn shift(self, direction: Direction) -> Bitboard {
+        let bits = self.0;
+        Bitboard(match direction {
+            Direction::North => bits << 8,
+            Direction::South => bits >> 8,
+            Direction::East => (bits & !FILE_H) << 1,
+            Direction::West => (bits & !FILE_A) >> 1,
+            Direction::NorthEast => (bits & !FILE_H) << 9,
+            Direction::NorthWest => (bits & !FILE_A) << 7,
+            Direction::SouthEast => (bits & !FILE_H) >> 7,
+            Direction::SouthWest => (bits & !FILE_A) >> 9,
+        })
+    }
+
     pub fn set_bit(&mut self, index: u8) {
         self.0 |= 1 << index;
     }
@@ -23,7 +214,7 @@ impl Bitboard {
     pub fn toggle(&mut self, source: u8, target:u8) {
         self.set_bit(target);
         self.clear_bit(source);
-            
+
     }
     pub fn printable(&self) ->String{
         let mut fmt_str= String::from("");
@@ -39,6 +230,102 @@ impl Bitboard {
         return fmt_str;
     }
 
-    
 
+
+}
+
+/// Iterator over the squares of a [`Bitboard`], see [`Bitboard::squares`].
+#[derive(Clone, Debug)]
+pub struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        self.0.pop_lsb()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.popcount() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Squares {}
+
+impl IntoIterator for Bitboard {
+    type Item = u8;
+    type IntoIter = Squares;
+
+    fn into_iter(self) -> Squares {
+        self.squares()
+    }
+}
+
+impl FromIterator<u8> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = u8>>(squares: I) -> Bitboard {
+        squares.into_iter().fold(Bitboard::EMPTY, |set, square| set | Bitboard::from_square(square))
+    }
+}
+
+impl From<u64> for Bitboard {
+    fn from(bits: u64) -> Bitboard {
+        Bitboard(bits)
+    }
+}
+
+impl From<Bitboard> for u64 {
+    fn from(set: Bitboard) -> u64 {
+        set.0
+    }
+}
+
+macro_rules! bit_operator {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:tt) => {
+        impl $trait for Bitboard {
+            type Output = Bitboard;
+
+            fn $method(self, other: Bitboard) -> Bitboard {
+                Bitboard(self.0 $op other.0)
+            }
+        }
+
+        impl $assign_trait for Bitboard {
+            fn $assign_method(&mut self, other: Bitboard) {
+                self.0 = self.0 $op other.0;
+            }
+        }
+    };
+}
+
+bit_operator!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+bit_operator!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+bit_operator!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+/// Shifts the bits, squares moving up (towards h8) by `count`; unlike
+/// [`Bitboard::shift`] this wraps around file edges.
+impl Shl<u8> for Bitboard {
+    type Output = Bitboard;
+
+    fn shl(self, count: u8) -> Bitboard {
+        Bitboard(self.0 << count)
+    }
+}
+
+/// Shifts the bits, squares moving down (towards a1) by `count`; unlike
+/// [`Bitboard::shift`] this wraps around file edges.
+impl Shr<u8> for Bitboard {
+    type Output = Bitboard;
+
+    fn shr(self, count: u8) -> Bitboard {
+        Bitboard(self.0 >> count)
+    }
 }
@@ -1,14 +1,17 @@
 use core::fmt;
 
 use crate::{
-    bitboard::Bitboard,
+    attacks,
+    bitboard::{Bitboard, Direction},
     color::{ByColor, Color},
-    components::Piece,
-    constants::{board, game_state, squares},
-    error::{ChessError, Square},
+    components::{ColoredPiece, Piece},
+    constants::game_state,
+    error::ChessError,
     fen::generate,
-    r#move::Move,
+    chess960::CastlingFiles,
+    r#move::{Castling, Move},
     role::ByPiece,
+    square::{File, Rank, Square},
 };
 
 /// Represents a chess board position using bitboards for efficient operations.
@@ -47,6 +50,12 @@ pub struct Board {
     pub half_move_count: u8,
     /// Full move counter (incremented after Black's move)
     pub full_move_count: u16,
+    /// Square a pawn skipped with a double step on the last move, capturable en passant
+    pub en_passant: Option<Square>,
+    /// Files the kings and castling rooks start on, not the e-, a- and h-files in Chess960
+    pub castling_files: CastlingFiles,
+    /// Whether the game is Chess960, where UCI writes castling as the king taking its rook
+    pub chess960: bool,
     /// Fast lookup table for piece at each square (None if empty)
     piece_lookup: [Option<Piece>; 64],
 }
@@ -85,6 +94,9 @@ impl Board {
             castling_rights: game_state::ALL_CASTLING_RIGHTS,
             half_move_count: game_state::STARTING_HALF_MOVES,
             full_move_count: game_state::STARTING_FULL_MOVES,
+            en_passant: None,
+            castling_files: CastlingFiles::STANDARD,
+            chess960: false,
             piece_lookup: [None; 64],
         };
         
@@ -92,12 +104,29 @@ impl Board {
         board
     }
 
+    /// Creates a board without any pieces or castling rights, to set up a
+    /// position with [`Board::put_piece`] or a [`Setup`](crate::setup::Setup).
+    pub fn empty() -> Board {
+        Board {
+            by_piece: ByPiece::empty(),
+            by_color: ByColor::empty(),
+            occupied: Bitboard::EMPTY,
+            castling_rights: 0,
+            half_move_count: game_state::STARTING_HALF_MOVES,
+            full_move_count: game_state::STARTING_FULL_MOVES,
+            en_passant: None,
+            castling_files: CastlingFiles::STANDARD,
+            chess960: false,
+            piece_lookup: [None; 64],
+        }
+    }
+
     /// Rebuilds the piece lookup table from current bitboard state.
     ///
     /// This method scans all piece bitboards and populates the lookup table
     /// for O(1) piece queries. Called after board initialization and whenever
     /// the bitboards are modified.
-    fn rebuild_piece_lookup(&mut self) {
+    pub(crate) fn rebuild_piece_lookup(&mut self) {
         // Clear the lookup table
         self.piece_lookup = [None; 64];
         
@@ -132,7 +161,7 @@ impl Board {
     ///
     /// # Returns
     ///
-    /// Returns the source square index for normal moves, or `None` for castling moves.
+    /// Returns the source square for normal moves, or `None` for castling moves.
     /// This is used by PGN processing to determine the source square for ambiguous notation.
     ///
     /// # Examples
@@ -140,14 +169,15 @@ impl Board {
     /// ```rust
     /// use analyzer::board::Board;
     /// use analyzer::r#move::Move;
+    /// use analyzer::square::Square;
     ///
     /// let mut board = Board::init();
     /// let move_e4 = Move::new("e4".to_string(), 0);
     /// let source = board.apply_move(&move_e4);
-    /// // source will be Some(12) indicating the pawn moved from e2
+    /// assert_eq!(source, Some(Square::E2));
     /// ```
-    pub fn apply_move(&mut self, mov: &Move) -> Option<u8> {
-        let mut source: Option<u8> = None;
+    pub fn apply_move(&mut self, mov: &Move) -> Option<Square> {
+        let mut source: Option<Square> = None;
 
         if mov.is_capture || mov.piece == { Piece::Pawn } {
             self.half_move_count = game_state::STARTING_HALF_MOVES
@@ -158,6 +188,7 @@ impl Board {
         if mov.color() == Color::Black {
             self.full_move_count += 1;
         }
+        self.en_passant = None;
 
         if mov.castling.is_some() {
             self.apply_castling(mov)
@@ -180,92 +211,100 @@ impl Board {
     fn apply_castling(&mut self, mov: &Move) {
         let color = mov.color();
         let castling = mov.castling;
-        let ((ks, kt), (rs, rt)) = castling.unwrap().compute_squares(color);
-        self.move_piece(ks, kt, color, Piece::King);
-        self.move_piece(rs, rt, color, Piece::Rook);
+        let ((ks, kt), (rs, rt)) = self.castling_squares(color, castling.unwrap());
+        // lift both first: in Chess960 one may land where the other stood
+        self.remove_piece(ks);
+        self.remove_piece(rs);
+        self.put_piece(kt, ColoredPiece::new(color, Piece::King));
+        self.put_piece(rt, ColoredPiece::new(color, Piece::Rook));
 
         // remove all castling rights for the side
         self.remove_all_castling_rights(color);
     }
 
-    fn remove_castling_rights(&mut self, color: Color, is_king_side: bool) {
-        let mask = match (color, is_king_side) {
-            (Color::White, true) => 0b_0111,
-            (Color::White, false) => 0b_1011,
-            (Color::Black, true) => 0b_1101,
-            (Color::Black, false) => 0b_1110,
-        };
-
-        self.castling_rights = self.castling_rights & mask;
-    }
-
     /// Removes all castling rights (both kingside and queenside) for the specified color.
     /// This is commonly needed when a king moves or during castling.
     fn remove_all_castling_rights(&mut self, color: Color) {
-        self.remove_castling_rights(color, true);  // Kingside
-        self.remove_castling_rights(color, false); // Queenside
+        self.castling_rights &= !(Castling::King.right(color) | Castling::Queen.right(color));
     }
 
-    fn apply_normal_move(&mut self, mov: &Move) -> Result<u8, String> {
-        let target = mov.get_target_index();
+    /// King and rook squares of castling on `side` for `color` in this game,
+    /// `((king_from, king_to), (rook_from, rook_to))`.
+    pub fn castling_squares(&self, color: Color, side: Castling) -> ((Square, Square), (Square, Square)) {
+        self.castling_files.squares(color, side)
+    }
+
+    /// Castling on `side` for `color` in UCI notation: the king's move
+    /// (`e1g1`), or in Chess960 the king taking its own rook (`e1h1`).
+    pub fn castling_uci(&self, color: Color, side: Castling) -> String {
+        let ((king_source, king_target), (rook_source, _)) = self.castling_squares(color, side);
+        let target = if self.chess960 { rook_source } else { king_target };
+        format!("{}{}", king_source, target)
+    }
+
+    fn apply_normal_move(&mut self, mov: &Move) -> Result<Square, String> {
+        let target = mov.get_target_square().ok_or_else(|| format!("Move {} has no target square", mov.san))?;
         let color = mov.color();
         let piece = mov.piece;
         let is_capture = mov.is_capture;
         let promotion = mov.promotion;
-        let source = self.get_source_index(mov)?;
+        let source = self.get_source_square(mov)?;
 
         if is_capture {
-            let mut opp_piece = self.get_piece_at_index(target.unwrap());
-            let mut opponent_target = target.unwrap();
+            let mut opp_piece = self.get_piece_at(target);
+            let mut opponent_target = target;
 
             if opp_piece.is_err() && piece == Piece::Pawn {
                 // potentially enpassant
-                opponent_target = if color == Color::White {
-                    target.unwrap() - 8
-                } else {
-                    target.unwrap() + 8
-                };
-                opp_piece = self.get_piece_at_index(opponent_target);
-                self.occupied.clear_bit(opponent_target);
+                opponent_target = Square::from_file_rank(target.file(), source.rank());
+                opp_piece = self.get_piece_at(opponent_target);
+                self.occupied.clear_bit(opponent_target.index());
             }
 
             let opp_color_board = &mut self.by_color.get_mut(color.get_opposite());
-            opp_color_board.clear_bit(opponent_target);
-
-            let opp_piece_board = &mut self.by_piece.get_mut(opp_piece.unwrap());
-            opp_piece_board.clear_bit(opponent_target);
-        }
+            opp_color_board.clear_bit(opponent_target.index());
 
-        if piece == Piece::Rook && squares::ALL_ROOK_SQUARES.contains(&source) {
-            self.remove_castling_rights(
-                color,
-                squares::WHITE_ROOKS[1] == source || squares::BLACK_ROOKS[1] == source,
-            );
+            let opp_piece_board = &mut self.by_piece.get_mut(opp_piece.map_err(|error| error.to_string())?);
+            opp_piece_board.clear_bit(opponent_target.index());
         }
 
-        if piece == Piece::King
-            && (source == squares::WHITE_KING_START || source == squares::BLACK_KING_START)
-        {
+        if piece == Piece::King {
             // remove all castling rights for the side
             self.remove_all_castling_rights(color);
         }
+        for side in [Castling::King, Castling::Queen] {
+            // a rook leaving its castling square, or captured on it
+            let (_, (own_rook, _)) = self.castling_squares(color, side);
+            if source == own_rook {
+                self.castling_rights &= !side.right(color);
+            }
+            let (_, (opponent_rook, _)) = self.castling_squares(color.get_opposite(), side);
+            if is_capture && target == opponent_rook {
+                self.castling_rights &= !side.right(color.get_opposite());
+            }
+        }
+
+        self.move_piece(source, target, color, piece);
 
-        self.move_piece(source, target.unwrap(), color, piece);
+        if piece == Piece::Pawn && source.rank() - target.rank() == 2 {
+            self.en_passant = Some(Square::from_file_rank(source.file(), if color == Color::White { Rank::Third } else { Rank::Sixth }));
+        }
 
         // once we have dealt with capture and moving piece. we deal with promotion
 
-        if promotion.is_some() {
+        if let Some(promotion) = promotion {
             let current_piece_board = &mut self.by_piece.get_mut(piece);
-            current_piece_board.clear_bit(target.unwrap());
+            current_piece_board.clear_bit(target.index());
 
-            let promotion_piece_board = &mut self.by_piece.get_mut(promotion.unwrap());
-            promotion_piece_board.set_bit(target.unwrap());
+            let promotion_piece_board = &mut self.by_piece.get_mut(promotion);
+            promotion_piece_board.set_bit(target.index());
         }
 
         Ok(source)
     }
 
-    fn move_piece(&mut self, source: u8, target: u8, color: Color, piece: Piece) {
+    fn move_piece(&mut self, source: Square, target: Square, color: Color, piece: Piece) {
+        let (source, target) = (source.index(), target.index());
         self.occupied.toggle(source, target);
 
         let color_board = &mut self.by_color.get_mut(color);
@@ -305,53 +344,214 @@ impl Board {
         generate(self, last_move)
     }
 
-    pub fn get_source_index(&self, mov: &Move) -> Result<u8, String> {
+    /// Square the piece of `mov` comes from, found from its target and the
+    /// disambiguation in its SAN.
+    pub fn get_source_square(&self, mov: &Move) -> Result<Square, String> {
         let piece = mov.piece;
         piece.compute_source(self, mov)
     }
 
-    pub fn get_piece_at_index(&self, index: u8) -> Result<Piece, ChessError> {
-        // Validate square index
-        let square = Square::new(index)?;
-        
+    /// Piece on `square`.
+    ///
+    /// # Errors
+    ///
+    /// [`ChessError::PieceNotFound`] if the square is empty.
+    pub fn get_piece_at(&self, square: Square) -> Result<Piece, ChessError> {
         // Use O(1) lookup table instead of O(6) bitboard search
         match self.piece_lookup[square.index() as usize] {
             Some(piece) => Ok(piece),
-            None => Err(ChessError::PieceNotFound { square: index })
+            None => Err(ChessError::PieceNotFound { square })
         }
     }
 }
 
-impl fmt::Display for Board {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for rank in (0..board::RANKS).rev() {
-            write!(f, "{} ", rank + 1)?; // Rank labels
-            for file in 0..board::FILES {
-                let index = rank * board::FILES + file;
-                if self.occupied.get_bit(index) {
-                    let color_code = if self.by_color.white.get_bit(index) {
-                        "33" // yellow
-                    } else {
-                        "34" // blue
+impl Board {
+    /// Piece and its color on `square`, if the square is occupied.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use analyzer::board::Board;
+    /// use analyzer::color::Color;
+    /// use analyzer::components::{ColoredPiece, Piece};
+    /// use analyzer::square::Square;
+    ///
+    /// let board = Board::init();
+    /// assert_eq!(board.piece_at(Square::D8), Some(ColoredPiece::new(Color::Black, Piece::Queen)));
+    /// assert_eq!(board.piece_at(Square::E4), None);
+    /// ```
+    pub fn piece_at(&self, square: Square) -> Option<ColoredPiece> {
+        let role = self.piece_lookup[square.index() as usize]?;
+        let color = self.color_at(square)?;
+        Some(ColoredPiece { color, role })
+    }
+
+    /// Puts `piece` on `square`, returning the piece it replaces.
+    ///
+    /// Only the placement changes: castling rights, en passant and the move
+    /// counters are left for the caller setting up the position.
+    pub fn put_piece(&mut self, square: Square, piece: ColoredPiece) -> Option<ColoredPiece> {
+        let replaced = self.remove_piece(square);
+        let index = square.index();
+        self.occupied.set_bit(index);
+        self.by_color.get_mut(piece.color).set_bit(index);
+        self.by_piece.get_mut(piece.role).set_bit(index);
+        self.piece_lookup[index as usize] = Some(piece.role);
+        replaced
+    }
+
+    /// Removes and returns the piece on `square`, if any.
+    pub fn remove_piece(&mut self, square: Square) -> Option<ColoredPiece> {
+        let piece = self.piece_at(square)?;
+        let index = square.index();
+        self.occupied.clear_bit(index);
+        self.by_color.get_mut(piece.color).clear_bit(index);
+        self.by_piece.get_mut(piece.role).clear_bit(index);
+        self.piece_lookup[index as usize] = None;
+        Some(piece)
+    }
+
+    /// Color of the piece on `square`, if any.
+    pub fn color_at(&self, square: Square) -> Option<Color> {
+        if self.by_color.white.contains(square.index()) {
+            Some(Color::White)
+        } else if self.by_color.black.contains(square.index()) {
+            Some(Color::Black)
+        } else {
+            None
+        }
+    }
+
+    /// Square of the king of `color`, if it is on the board.
+    pub fn king_square(&self, color: Color) -> Option<Square> {
+        (self.by_piece.king & self.by_color.get(color)).lsb().map(Square::new_unchecked)
+    }
+
+    /// Whether any piece of color `by` attacks `square`.
+    pub fn is_attacked(&self, square: Square, by: Color) -> bool {
+        !self.attackers(square, by).is_empty()
+    }
+
+    /// Pieces of color `by` attacking `square`.
+    pub fn attackers(&self, square: Square, by: Color) -> Bitboard {
+        let square = square.index();
+        let queens = self.by_piece.queen;
+
+        // pawns attack diagonally forward, so look from the square the other way
+        let attacked_by = attacks::pawn(by.get_opposite(), square) & self.by_piece.pawn
+            | attacks::knight(square) & self.by_piece.knight
+            | attacks::king(square) & self.by_piece.king
+            | attacks::bishop(square, self.occupied) & (self.by_piece.bishop | queens)
+            | attacks::rook(square, self.occupied) & (self.by_piece.rook | queens);
+        attacked_by & self.by_color.get(by)
+    }
+
+    /// Legal moves of the side to move at ply `index` (0 for White's first
+    /// move), with their SAN.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use analyzer::board::Board;
+    ///
+    /// let moves = Board::init().legal_moves(0);
+    /// assert_eq!(moves.len(), 20);
+    /// assert!(moves.iter().any(|mov| mov.san == "Nf3"));
+    /// ```
+    pub fn legal_moves(&self, index: u16) -> Vec<Move> {
+        let mut moves = self.legal_moves_unnamed(index);
+        for mov in &mut moves {
+            mov.san = mov.to_san(self);
+        }
+        moves
+    }
+
+    /// Whether the side to move at ply `index` has any legal move.
+    pub fn has_legal_moves(&self, index: u16) -> bool {
+        !self.legal_moves_unnamed(index).is_empty()
+    }
+
+    /// Legal moves with their SAN left empty, see [`Move::validate_uci`].
+    pub(crate) fn legal_moves_unnamed(&self, index: u16) -> Vec<Move> {
+        let color = if index.is_multiple_of(2) { Color::White } else { Color::Black };
+        let own = self.by_color.get(color);
+        let last_rank = if color == Color::White { Rank::Eighth } else { Rank::First };
+
+        let mut moves = Vec::new();
+        for source in own.squares().map(Square::new_unchecked) {
+            let Some(piece) = self.piece_lookup[source.index() as usize] else {
+                continue;
+            };
+            let targets = match piece {
+                // pushes and captures; the validation sorts out which apply
+                Piece::Pawn => {
+                    let (forward, start_rank) = match color {
+                        Color::White => (Direction::North, Rank::Second),
+                        Color::Black => (Direction::South, Rank::Seventh),
                     };
-                    match self.get_piece_at_index(index) {
-                        Ok(piece) => {
-                            write!(f, "\x1b[{}m{}\x1b[0m ", color_code, piece.to_unicode())?;
-                        },
-                        Err(_) => {
-                            write!(f, "\x1b[37m◻\x1b[0m ")?; // gray fallback
+                    let from = source.bitboard();
+                    let double = (from & Bitboard::rank(start_rank)).shift(forward).shift(forward);
+                    attacks::pawn(color, source.index()) | from.shift(forward) | double
+                }
+                _ => attacks::piece(piece, color, source.index(), self.occupied),
+            } & !own;
+
+            for target in targets.squares().map(Square::new_unchecked) {
+                let uci = format!("{}{}", source, target);
+                if piece == Piece::Pawn && target.rank() == last_rank {
+                    for promotion in ["q", "r", "b", "n"] {
+                        if let Ok(mov) = Move::validate_uci(self, &format!("{}{}", uci, promotion), index) {
+                            moves.push(mov);
                         }
                     }
-                } else {
-                    write!(f, "\x1b[37m◻\x1b[0m ")?; // gray empty square
+                } else if let Ok(mov) = Move::validate_uci(self, &uci, index) {
+                    moves.push(mov);
+                }
+            }
+        }
+
+        for side in [Castling::King, Castling::Queen] {
+            if self.castling_rights & side.right(color) == 0 {
+                continue;
+            }
+            if let Ok(mov) = Move::validate_uci(self, &self.castling_uci(color, side), index) {
+                moves.push(mov);
+            }
+        }
+        moves
+    }
+
+    /// Whether the king of `color` is attacked.
+    pub fn is_in_check(&self, color: Color) -> bool {
+        self.king_square(color)
+            .is_some_and(|square| self.is_attacked(square, color.get_opposite()))
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for rank in Rank::ALL.into_iter().rev() {
+            write!(f, "{} ", rank.to_char())?; // Rank labels
+            for file in File::ALL {
+                let square = Square::from_file_rank(file, rank);
+                match self.piece_at(square) {
+                    Some(piece) => {
+                        let color_code = if piece.color == Color::White {
+                            "33" // yellow
+                        } else {
+                            "34" // blue
+                        };
+                        write!(f, "\x1b[{}m{}\x1b[0m ", color_code, piece.to_unicode())?;
+                    }
+                    None => write!(f, "\x1b[37m◻\x1b[0m ")?, // gray empty square
                 }
             }
             writeln!(f)?;
         }
         // Add file labels
         write!(f, "  ")?;
-        for file in 0..board::FILES {
-            write!(f, "{} ", (b'a' + file) as char)?;
+        for file in File::ALL {
+            write!(f, "{} ", file.to_char())?;
         }
         writeln!(f)?;
         Ok(())
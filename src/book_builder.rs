@@ -0,0 +1,225 @@
+//! Building Polyglot opening books from games.
+//!
+//! [`BookBuilder`] replays the opening of every game it is given and counts
+//! how often each move was played in each position and how it scored. The
+//! result is a [`PolyglotBook`] whose weights are the points the move scored,
+//! in half points (two per win, one per draw), as Polyglot's own book maker
+//! weighs them.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    color::Color,
+    pgn::Pgn,
+    polyglot::{zobrist_key, BookEntry, PolyglotBook},
+    r#move::Move,
+};
+
+/// Default number of plies of every game that go into a book
+pub const DEFAULT_BOOK_PLIES: usize = 20;
+
+/// Which games and moves go into a book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookFilter {
+    /// Plies of each game that are replayed, from the starting position
+    pub max_plies: usize,
+    /// Only games of this player, and only the moves they made
+    pub player: Option<String>,
+    /// Only moves of this side; combined with `player`, only games in which
+    /// the player had this color
+    pub color: Option<Color>,
+    /// Moves played in fewer games from their position are left out
+    pub min_games: u32,
+}
+
+impl Default for BookFilter {
+    fn default() -> Self {
+        BookFilter {
+            max_plies: DEFAULT_BOOK_PLIES,
+            player: None,
+            color: None,
+            min_games: 1,
+        }
+    }
+}
+
+/// How a move fared, counted from the point of view of the side that made it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MoveStats {
+    pub games: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MoveStats {
+    /// Points scored with the move in half points: two per win, one per draw.
+    pub fn half_points(&self) -> u32 {
+        2 * self.wins + self.draws
+    }
+}
+
+/// Collects move statistics from games and turns them into a book.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::board::Board;
+/// use analyzer::book_builder::{BookBuilder, BookFilter};
+/// use analyzer::pgn::Pgn;
+///
+/// let mut builder = BookBuilder::new(BookFilter::default());
+/// builder.add_game(&Pgn::new("[Result \"1-0\"]\n\n1. e4 e5 2. Nf3 1-0".to_string()));
+/// builder.add_game(&Pgn::new("[Result \"1/2-1/2\"]\n\n1. d4 d5 1/2-1/2".to_string()));
+///
+/// let book = builder.build();
+/// let moves = book.moves(&Board::init(), 0);
+/// assert_eq!((moves[0].mov.san.as_str(), moves[0].weight), ("e4", 2));
+/// assert_eq!((moves[1].mov.san.as_str(), moves[1].weight), ("d4", 1));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BookBuilder {
+    filter: BookFilter,
+    /// Position key and packed move to their statistics
+    moves: HashMap<(u64, u16), MoveStats>,
+    games: usize,
+}
+
+impl BookBuilder {
+    pub fn new(filter: BookFilter) -> Self {
+        BookBuilder {
+            filter,
+            ..Default::default()
+        }
+    }
+
+    /// Counts the moves of a game that pass the filter. Returns whether the
+    /// game was used; games of other players are not, and neither are games
+    /// whose `FEN` tag cannot be read. Replay starts from the game's start
+    /// position and stops at the first move that cannot be played.
+    pub fn add_game(&mut self, pgn: &Pgn) -> bool {
+        let Some(recorded) = self.recorded_color(pgn) else {
+            return false;
+        };
+        let Ok(start) = pgn.start_setup() else {
+            return false;
+        };
+        let winner = match pgn.headers.result() {
+            Some("1-0") => Some(Some(Color::White)),
+            Some("0-1") => Some(Some(Color::Black)),
+            Some("1/2-1/2") => Some(None),
+            _ => None,
+        };
+
+        // a position repeated within a game counts once
+        let mut seen = HashSet::new();
+        let mut board = start.board().clone();
+        for (index, mov) in (start.ply_index()..).zip(pgn.moves.iter().take(self.filter.max_plies)) {
+            let Ok(played) = mov
+                .to_uci_on(&board)
+                .and_then(|uci| Move::validate_uci(&board, &uci, index))
+            else {
+                break;
+            };
+            let color = played.color();
+            if recorded.is_none_or(|recorded| recorded == color) {
+                let raw_move = played
+                    .to_uci()
+                    .and_then(|uci| BookEntry::encode_move(&polyglot_uci(&played, &uci)).ok());
+                if let Some(raw_move) = raw_move {
+                    let key = (zobrist_key(&board, color), raw_move);
+                    if seen.insert(key) {
+                        let stats = self.moves.entry(key).or_default();
+                        stats.games += 1;
+                        match winner {
+                            Some(Some(winner)) if winner == color => stats.wins += 1,
+                            Some(Some(_)) => stats.losses += 1,
+                            Some(None) => stats.draws += 1,
+                            None => {}
+                        }
+                    }
+                }
+            }
+            board.apply_move(&played);
+        }
+        self.games += 1;
+        true
+    }
+
+    /// The side whose moves are recorded in a game: `Some(None)` for both,
+    /// `None` if the game is filtered out.
+    fn recorded_color(&self, pgn: &Pgn) -> Option<Option<Color>> {
+        let Some(player) = &self.filter.player else {
+            return Some(self.filter.color);
+        };
+        let plays = |name: Option<&str>| name.is_some_and(|name| name.trim() == player.trim());
+        let color = if plays(pgn.headers.white()) {
+            Color::White
+        } else if plays(pgn.headers.black()) {
+            Color::Black
+        } else {
+            return None;
+        };
+        match self.filter.color {
+            Some(wanted) if wanted != color => None,
+            _ => Some(Some(color)),
+        }
+    }
+
+    /// Number of games used so far.
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    /// Statistics of a move in the position with Polyglot key `key`.
+    pub fn stats(&self, key: u64, raw_move: u16) -> Option<MoveStats> {
+        self.moves.get(&(key, raw_move)).copied()
+    }
+
+    /// Builds the book from the moves played in at least `min_games` games.
+    ///
+    /// Weights are the half points the move scored, scaled down per
+    /// position when they exceed what an entry can hold. Moves that never
+    /// scored keep a weight of 0: they are listed but never picked.
+    pub fn build(&self) -> PolyglotBook {
+        let mut by_position: HashMap<u64, Vec<(u16, MoveStats)>> = HashMap::new();
+        for (&(key, raw_move), &stats) in &self.moves {
+            if stats.games >= self.filter.min_games {
+                by_position.entry(key).or_default().push((raw_move, stats));
+            }
+        }
+
+        let mut entries = Vec::new();
+        for (key, moves) in by_position {
+            let heaviest = moves.iter().map(|(_, stats)| stats.half_points()).max().unwrap_or(0);
+            let scale = |points: u32| {
+                if heaviest <= u32::from(u16::MAX) {
+                    points as u16
+                } else {
+                    (u64::from(points) * u64::from(u16::MAX) / u64::from(heaviest)) as u16
+                }
+            };
+            entries.extend(moves.into_iter().map(|(raw_move, stats)| BookEntry {
+                key,
+                raw_move,
+                weight: scale(stats.half_points()),
+                learn: 0,
+            }));
+        }
+        // ties in key and weight would otherwise come out in hash order
+        entries.sort_by_key(|entry| entry.raw_move);
+        PolyglotBook::from_entries(entries)
+    }
+}
+
+/// The move in Polyglot's notation, which writes castling as the king taking
+/// its own rook.
+fn polyglot_uci(played: &Move, uci: &str) -> String {
+    match uci {
+        "e1g1" | "e1c1" | "e8g8" | "e8c8" if played.castling.is_some() => {
+            let rook_file = if uci.as_bytes()[2] == b'g' { 'h' } else { 'a' };
+            format!("{}{}{}", &uci[..2], rook_file, &uci[3..])
+        }
+        _ => uci.to_string(),
+    }
+}
@@ -0,0 +1,184 @@
+//! Chess960 (Fischer Random) start positions and castling.
+//!
+//! In Chess960 the pieces of the back rank are shuffled, the king somewhere
+//! between the rooks and the bishops on squares of both colors, Black
+//! mirroring White. The 960 arrangements are numbered as in Scharnagl's
+//! scheme, 518 being the standard position. Castling ends with king and rook
+//! on the squares they reach in standard chess, from wherever they start;
+//! [`CastlingFiles`] records where that is.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use analyzer::chess960::{position_number, start_position};
+//! use analyzer::color::Color;
+//! use analyzer::setup::Setup;
+//! use analyzer::square::Square;
+//!
+//! let board = start_position(0).unwrap();
+//! assert_eq!(board.piece_at(Square::A1).unwrap().to_fen_char(), 'B');
+//! let setup = Setup::from_board(board.clone(), Color::White);
+//! assert_eq!(setup.fen(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+//! assert_eq!(position_number(&board), Some(0));
+//! assert_eq!(position_number(&start_position(518).unwrap()), Some(518));
+//! ```
+
+use crate::{
+    board::Board,
+    color::Color,
+    components::{ColoredPiece, Piece},
+    constants::game_state,
+    error::ChessError,
+    r#move::Castling,
+    square::{File, Rank, Square},
+};
+
+/// Number of Chess960 start positions
+pub const POSITION_COUNT: u16 = 960;
+
+/// Number of the standard start position
+pub const STANDARD_POSITION: u16 = 518;
+
+/// Files the king and the two castling rooks start on, the same for both sides.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CastlingFiles {
+    pub king: File,
+    /// Rook castling towards the h-file, the right written `K`
+    pub king_rook: File,
+    /// Rook castling towards the a-file, the right written `Q`
+    pub queen_rook: File,
+}
+
+impl CastlingFiles {
+    /// The files of standard chess, king on e and rooks on a and h.
+    pub const STANDARD: CastlingFiles = CastlingFiles {
+        king: File::E,
+        king_rook: File::H,
+        queen_rook: File::A,
+    };
+
+    /// File of the rook castling on `side`.
+    pub fn rook(&self, side: Castling) -> File {
+        match side {
+            Castling::King => self.king_rook,
+            Castling::Queen => self.queen_rook,
+        }
+    }
+
+    /// King and rook squares of castling on `side` for `color`:
+    /// `((king_from, king_to), (rook_from, rook_to))`, the same shape as
+    /// [`Castling::compute_squares`].
+    pub fn squares(&self, color: Color, side: Castling) -> ((Square, Square), (Square, Square)) {
+        let rank = back_rank(color);
+        let (king_target, rook_target) = match side {
+            Castling::King => (File::G, File::F),
+            Castling::Queen => (File::C, File::D),
+        };
+        (
+            (Square::from_file_rank(self.king, rank), Square::from_file_rank(king_target, rank)),
+            (Square::from_file_rank(self.rook(side), rank), Square::from_file_rank(rook_target, rank)),
+        )
+    }
+}
+
+impl Default for CastlingFiles {
+    fn default() -> Self {
+        CastlingFiles::STANDARD
+    }
+}
+
+/// The rank the pieces of `color` start on.
+pub fn back_rank(color: Color) -> Rank {
+    match color {
+        Color::White => Rank::First,
+        Color::Black => Rank::Eighth,
+    }
+}
+
+/// Order of the back rank pieces of start position `number`, a-file first.
+///
+/// # Errors
+///
+/// [`ChessError::InvalidPosition`] if `number` is not below 960.
+pub fn back_rank_pieces(number: u16) -> Result<[Piece; 8], ChessError> {
+    if number >= POSITION_COUNT {
+        return Err(ChessError::InvalidPosition {
+            problems: vec![format!("Chess960 position number {} is not below {}", number, POSITION_COUNT)],
+        });
+    }
+    let mut pieces: [Option<Piece>; 8] = [None; 8];
+    let mut rest = usize::from(number);
+
+    // one bishop on a light square (b, d, f, h) and one on a dark square (a, c, e, g)
+    pieces[2 * (rest % 4) + 1] = Some(Piece::Bishop);
+    rest /= 4;
+    pieces[2 * (rest % 4)] = Some(Piece::Bishop);
+    rest /= 4;
+
+    // the others fill the remaining files in order: queen, knights, then rook, king, rook
+    let mut place = |piece: Piece, nth_free: usize| {
+        let file = (0..8).filter(|&file| pieces[file].is_none()).nth(nth_free).expect("a free file remains");
+        pieces[file] = Some(piece);
+    };
+    place(Piece::Queen, rest % 6);
+    rest /= 6;
+    const KNIGHTS: [(usize, usize); 10] = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)];
+    let (first, second) = KNIGHTS[rest];
+    // placing the first knight shifts the free files the second counts
+    place(Piece::Knight, first);
+    place(Piece::Knight, second - 1);
+    for piece in [Piece::Rook, Piece::King, Piece::Rook] {
+        place(piece, 0);
+    }
+
+    Ok(pieces.map(|piece| piece.expect("every file has a piece")))
+}
+
+/// The board of Chess960 start position `number`, White to move with all
+/// castling rights.
+///
+/// # Errors
+///
+/// [`ChessError::InvalidPosition`] if `number` is not below 960.
+pub fn start_position(number: u16) -> Result<Board, ChessError> {
+    let pieces = back_rank_pieces(number)?;
+    let mut board = Board::empty();
+    for (file, role) in File::ALL.into_iter().zip(pieces) {
+        for color in [Color::White, Color::Black] {
+            board.put_piece(Square::from_file_rank(file, back_rank(color)), ColoredPiece::new(color, role));
+        }
+        board.put_piece(Square::from_file_rank(file, Rank::Second), ColoredPiece::new(Color::White, Piece::Pawn));
+        board.put_piece(Square::from_file_rank(file, Rank::Seventh), ColoredPiece::new(Color::Black, Piece::Pawn));
+    }
+
+    let file_of = |wanted: Piece, nth: usize| {
+        File::ALL.into_iter().filter(|&file| pieces[file.index() as usize] == wanted).nth(nth).expect("piece is placed")
+    };
+    board.castling_files = CastlingFiles {
+        king: file_of(Piece::King, 0),
+        king_rook: file_of(Piece::Rook, 1),
+        queen_rook: file_of(Piece::Rook, 0),
+    };
+    board.castling_rights = game_state::ALL_CASTLING_RIGHTS;
+    board.chess960 = true;
+    Ok(board)
+}
+
+/// Number of the Chess960 start position whose back ranks `board` has, if
+/// any; pawns and other pieces are not looked at.
+pub fn position_number(board: &Board) -> Option<u16> {
+    let rank = |color: Color| -> Option<Vec<Piece>> {
+        File::ALL
+            .into_iter()
+            .map(|file| {
+                let piece = board.piece_at(Square::from_file_rank(file, back_rank(color)))?;
+                (piece.color == color).then_some(piece.role)
+            })
+            .collect()
+    };
+    let white = rank(Color::White)?;
+    if rank(Color::Black)? != white {
+        return None;
+    }
+    (0..POSITION_COUNT).find(|&number| back_rank_pieces(number).is_ok_and(|pieces| pieces[..] == white[..]))
+}
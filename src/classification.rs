@@ -0,0 +1,281 @@
+//! Classification of the moves of an analysed game.
+//!
+//! Every move is judged by how much of the mover's evaluation it gives away
+//! compared to the engine's best move: the centipawn loss. Both evaluations
+//! come from the engine analysis of the positions before and after the move,
+//! seen from the side that played it.
+//!
+//! A best move that gives up material the opponent can take, in a position
+//! that was not already won, is brilliant.
+
+use std::fmt;
+
+use crate::components::Piece;
+use crate::constants::game_state;
+use crate::engine::analysis::{Analysis, Score};
+use crate::engine::position::UciPosition;
+use crate::game_analysis::PlyAnalysis;
+use crate::r#move::Move;
+use crate::setup::Setup;
+
+/// Centipawn value standing in for a forced mate. Mates are worth more than
+/// any clamped centipawn evaluation, and shorter mates more than longer ones.
+const MATE_VALUE: i32 = 2000;
+
+/// Centipawn evaluations are clamped to this value before comparing them, so
+/// dropping from +15 to +9 in a won position does not count as a blunder.
+const MAX_EVALUATION: i32 = 1000;
+
+/// Evaluation before a move above which a sacrifice is no longer brilliant:
+/// with that much to spare, giving material back is just cashing in.
+const BRILLIANT_MAX_EVAL_BEFORE: i32 = 300;
+
+/// How good a move was, from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MoveClass {
+    /// A best move sacrificing material when the game was not already won
+    Brilliant,
+    /// The engine's choice, or a move losing nothing against it
+    Best,
+    Excellent,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveClass {
+    /// All classes, from best to worst.
+    pub const ALL: [MoveClass; 7] = [
+        MoveClass::Brilliant,
+        MoveClass::Best,
+        MoveClass::Excellent,
+        MoveClass::Good,
+        MoveClass::Inaccuracy,
+        MoveClass::Mistake,
+        MoveClass::Blunder,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MoveClass::Brilliant => "brilliant",
+            MoveClass::Best => "best",
+            MoveClass::Excellent => "excellent",
+            MoveClass::Good => "good",
+            MoveClass::Inaccuracy => "inaccuracy",
+            MoveClass::Mistake => "mistake",
+            MoveClass::Blunder => "blunder",
+        }
+    }
+
+    /// Numeric annotation glyph marking the move in a PGN: `$3` (!!) for
+    /// brilliant moves, `$6` (?!) for inaccuracies, `$2` (?) for mistakes and
+    /// `$4` (??) for blunders.
+    pub fn nag(&self) -> Option<u8> {
+        match self {
+            MoveClass::Brilliant => Some(3),
+            MoveClass::Inaccuracy => Some(6),
+            MoveClass::Mistake => Some(2),
+            MoveClass::Blunder => Some(4),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MoveClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Smallest centipawn loss for each class; smaller losses are excellent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassificationThresholds {
+    pub good: i32,
+    pub inaccuracy: i32,
+    pub mistake: i32,
+    pub blunder: i32,
+}
+
+impl Default for ClassificationThresholds {
+    fn default() -> Self {
+        Self {
+            good: 20,
+            inaccuracy: 50,
+            mistake: 100,
+            blunder: 300,
+        }
+    }
+}
+
+impl ClassificationThresholds {
+    /// Classifies a move by its centipawn loss.
+    pub fn classify(&self, cp_loss: i32) -> MoveClass {
+        match cp_loss {
+            loss if loss >= self.blunder => MoveClass::Blunder,
+            loss if loss >= self.mistake => MoveClass::Mistake,
+            loss if loss >= self.inaccuracy => MoveClass::Inaccuracy,
+            loss if loss >= self.good => MoveClass::Good,
+            loss if loss > 0 => MoveClass::Excellent,
+            _ => MoveClass::Best,
+        }
+    }
+}
+
+/// The verdict on one move of a game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveReview {
+    /// Zero-based ply index of the move in the game
+    pub ply: usize,
+    /// The move played, as written in the PGN
+    pub san: String,
+    /// The move played, in UCI notation
+    pub uci: String,
+    /// The engine's best move in the position the move was played in
+    pub best_move: String,
+    /// Evaluation before the move, from the mover's point of view
+    pub eval_before: Score,
+    /// Evaluation after the move, from the mover's point of view.
+    /// `Mate(0)` means the move delivered checkmate.
+    pub eval_after: Score,
+    /// Evaluation given away by the move, never negative
+    pub cp_loss: i32,
+    pub class: MoveClass,
+}
+
+impl MoveReview {
+    /// Judges the move of an analysed ply. Returns `None` for book moves and
+    /// when the engine reported no evaluation for the position before or
+    /// after the move.
+    ///
+    /// Telling a brilliant move from a best one needs the moves leading to
+    /// the ply in its `position`; without them the move is at best `Best`.
+    pub fn from_ply(ply: &PlyAnalysis, thresholds: &ClassificationThresholds) -> Option<MoveReview> {
+        if ply.book {
+            return None;
+        }
+        let eval_before = ply.before.score()?;
+        let eval_after = mover_score_after(&ply.analysis)?;
+
+        let played_best = ply.uci == ply.before.best_move;
+        let cp_loss = if played_best {
+            0
+        } else {
+            (centipawn_value(eval_before) - centipawn_value(eval_after)).max(0)
+        };
+
+        Some(MoveReview {
+            ply: ply.ply,
+            san: ply.san.clone(),
+            uci: ply.uci.clone(),
+            best_move: ply.before.best_move.clone(),
+            eval_before,
+            eval_after,
+            cp_loss,
+            class: match thresholds.classify(cp_loss) {
+                MoveClass::Best
+                    if centipawn_value(eval_before) <= BRILLIANT_MAX_EVAL_BEFORE
+                        && centipawn_value(eval_after) >= 0
+                        && is_sacrifice(&ply.position) =>
+                {
+                    MoveClass::Brilliant
+                }
+                class => class,
+            },
+        })
+    }
+}
+
+/// Reviews every move of an analysed game, skipping moves without evaluations.
+pub fn review_game(plies: &[PlyAnalysis], thresholds: &ClassificationThresholds) -> Vec<MoveReview> {
+    plies
+        .iter()
+        .filter_map(|ply| MoveReview::from_ply(ply, thresholds))
+        .collect()
+}
+
+/// Evaluation of the position after a move from the point of view of the
+/// player who made it, i.e. the opponent of the side to move.
+fn mover_score_after(analysis: &Analysis) -> Option<Score> {
+    match analysis.score()? {
+        // the side to move is checkmated
+        Score::Mate(0) => Some(Score::Mate(0)),
+        Score::Mate(moves) => Some(Score::Mate(-moves)),
+        Score::Centipawns(cp) => Some(Score::Centipawns(-cp)),
+    }
+}
+
+/// Whether the last move of `position` leaves the piece it moved where the
+/// opponent can take it for less than it is worth: undefended, or attacked
+/// by a cheaper piece. Pawn moves are never sacrifices, and neither is taking
+/// a piece worth as much as the one moved.
+fn is_sacrifice(position: &UciPosition) -> bool {
+    let Some((last, earlier)) = position.moves.split_last() else {
+        return false;
+    };
+    let start = position.start_fen.as_deref().unwrap_or(game_state::STARTING_FEN);
+    let Ok(setup) = Setup::from_fen(start) else {
+        return false;
+    };
+    let mut board = setup.board().clone();
+    let mut index = setup.ply_index();
+    for uci in earlier {
+        match Move::from_uci(&board, uci, index) {
+            Ok(mov) => {
+                board.apply_move(&mov);
+            }
+            Err(_) => return false,
+        }
+        index += 1;
+    }
+
+    let Ok(mov) = Move::from_uci(&board, last, index) else {
+        return false;
+    };
+    let Some(target) = mov.get_target_square().filter(|_| mov.castling.is_none()) else {
+        return false;
+    };
+    let captured = board.piece_at(target).map_or(0, |piece| material(piece.role));
+    board.apply_move(&mov);
+    let Some(moved) = board.piece_at(target).map(|piece| piece.role) else {
+        return false;
+    };
+    if matches!(moved, Piece::Pawn | Piece::King) || material(moved) <= captured {
+        return false;
+    }
+
+    let mover = mov.color();
+    let attackers = board.attackers(target, mover.get_opposite());
+    if attackers.is_empty() {
+        return false;
+    }
+    if !board.is_attacked(target, mover) {
+        // the king only takes undefended pieces
+        return true;
+    }
+    [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+        .into_iter()
+        .any(|attacker| material(attacker) < material(moved) && !(attackers & board.by_piece.get(attacker)).is_empty())
+}
+
+/// Material value of a piece in pawns, for telling sacrifices from trades.
+fn material(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight | Piece::Bishop => 3,
+        Piece::Rook => 5,
+        Piece::Queen => 9,
+        Piece::King => 0,
+    }
+}
+
+/// Maps an evaluation from the mover's point of view onto one centipawn scale:
+/// centipawns are clamped to ±10 pawns and mates count as ±20 pawns, a little
+/// less the further away they are.
+pub fn centipawn_value(score: Score) -> i32 {
+    match score {
+        Score::Centipawns(cp) => cp.clamp(-MAX_EVALUATION, MAX_EVALUATION),
+        Score::Mate(moves) if moves >= 0 => MATE_VALUE - moves,
+        Score::Mate(moves) => -MATE_VALUE - moves,
+    }
+}
@@ -2,7 +2,7 @@ use std::fmt;
 
 use crate::bitboard::Bitboard;
 
-#[derive(Clone, Copy,PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum  Color{
     White,
     Black
@@ -42,6 +42,13 @@ impl ByColor {
         }
     }
 
+    pub fn empty()->ByColor{
+        ByColor {
+            black: Bitboard::EMPTY,
+            white: Bitboard::EMPTY,
+        }
+    }
+
     pub fn get(&self,color:Color)->Bitboard{
         match color {
             Color::White => self.white,
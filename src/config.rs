@@ -36,6 +36,12 @@ pub struct EngineConfig {
     pub timeout_ms: Option<u32>,
     /// Engine executable path (defaults to "stockfish" in PATH)
     pub engine_path: Option<String>,
+    /// Command line arguments passed to the engine executable
+    pub engine_args: Vec<String>,
+    /// Search threads per engine (engine default when unset)
+    pub threads: Option<u32>,
+    /// Hash table size per engine in megabytes (engine default when unset)
+    pub hash_mb: Option<u32>,
 }
 
 impl Default for EngineConfig {
@@ -44,6 +50,9 @@ impl Default for EngineConfig {
             depth: defaults::DEFAULT_ENGINE_DEPTH,
             timeout_ms: None,
             engine_path: None,
+            engine_args: Vec::new(),
+            threads: None,
+            hash_mb: None,
         }
     }
 }
@@ -89,6 +98,7 @@ impl Default for FileConfig {
 ///         depth: 18,
 ///         timeout_ms: Some(5000),
 ///         engine_path: Some("/usr/local/bin/stockfish".to_string()),
+///         ..Default::default()
 ///     },
 ///     ..Default::default()
 /// };
@@ -127,7 +137,7 @@ impl Config {
             engine: EngineConfig {
                 depth: 8,
                 timeout_ms: Some(2000),
-                engine_path: None,
+                ..Default::default()
             },
             ..Default::default()
         }
@@ -141,7 +151,7 @@ impl Config {
             engine: EngineConfig {
                 depth: 22,
                 timeout_ms: Some(30000),
-                engine_path: None,
+                ..Default::default()
             },
             ..Default::default()
         }
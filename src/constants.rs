@@ -4,37 +4,41 @@
 /// piece positions, and game rules. Using constants improves maintainability
 /// and makes the code more readable.
 
-/// Square indices for important board positions.
+/// Squares of important board positions.
 pub mod squares {
+    use crate::square::Square;
+
     /// White king starting position (e1)
-    pub const WHITE_KING_START: u8 = 4;
+    pub const WHITE_KING_START: Square = Square::E1;
     
     /// Black king starting position (e8)
-    pub const BLACK_KING_START: u8 = 60;
+    pub const BLACK_KING_START: Square = Square::E8;
     
     /// White rook starting positions [a1, h1]
-    pub const WHITE_ROOKS: [u8; 2] = [0, 7];
+    pub const WHITE_ROOKS: [Square; 2] = [Square::A1, Square::H1];
     
     /// Black rook starting positions [a8, h8]
-    pub const BLACK_ROOKS: [u8; 2] = [56, 63];
+    pub const BLACK_ROOKS: [Square; 2] = [Square::A8, Square::H8];
     
     /// All initial rook positions for castling rights tracking
-    pub const ALL_ROOK_SQUARES: [u8; 4] = [0, 7, 56, 63];
+    pub const ALL_ROOK_SQUARES: [Square; 4] = [Square::A1, Square::H1, Square::A8, Square::H8];
 }
 
 /// Castling-related constants.
 pub mod castling {
+    use crate::square::Square;
+
     /// White kingside castling: (king_from, king_to), (rook_from, rook_to)
-    pub const WHITE_KINGSIDE: ((u8, u8), (u8, u8)) = ((4, 6), (7, 5));
+    pub const WHITE_KINGSIDE: ((Square, Square), (Square, Square)) = ((Square::E1, Square::G1), (Square::H1, Square::F1));
     
     /// White queenside castling: (king_from, king_to), (rook_from, rook_to)
-    pub const WHITE_QUEENSIDE: ((u8, u8), (u8, u8)) = ((4, 2), (0, 3));
+    pub const WHITE_QUEENSIDE: ((Square, Square), (Square, Square)) = ((Square::E1, Square::C1), (Square::A1, Square::D1));
     
     /// Black kingside castling: (king_from, king_to), (rook_from, rook_to)
-    pub const BLACK_KINGSIDE: ((u8, u8), (u8, u8)) = ((60, 62), (63, 61));
+    pub const BLACK_KINGSIDE: ((Square, Square), (Square, Square)) = ((Square::E8, Square::G8), (Square::H8, Square::F8));
     
     /// Black queenside castling: (king_from, king_to), (rook_from, rook_to)
-    pub const BLACK_QUEENSIDE: ((u8, u8), (u8, u8)) = ((60, 58), (56, 59));
+    pub const BLACK_QUEENSIDE: ((Square, Square), (Square, Square)) = ((Square::E8, Square::C8), (Square::A8, Square::D8));
 }
 
 /// Board geometry constants.
@@ -68,6 +72,9 @@ pub mod game_state {
     
     /// Starting full-move count
     pub const STARTING_FULL_MOVES: u16 = 1;
+
+    /// FEN of the standard starting position
+    pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 }
 
 /// Default file paths and names.
@@ -77,4 +84,7 @@ pub mod defaults {
     
     /// Default engine search depth
     pub const DEFAULT_ENGINE_DEPTH: u8 = 16;
+
+    /// Default location of the persistent analysis cache
+    pub const DEFAULT_CACHE_PATH: &str = "./.analyzer_cache.tsv";
 }
\ No newline at end of file
@@ -0,0 +1,195 @@
+//! Opening classification.
+//!
+//! Games are matched against a table of ECO (Encyclopaedia of Chess Openings)
+//! codes by position rather than by move order, so a game reaching a known
+//! position through a transposition gets the same name. The crate ships a
+//! table of common openings (`data/eco.tsv`); [`EcoTable::parse`] reads others
+//! in the same format.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{board::Board, error::ChessError, pgn::Pgn, pgn_header::PgnHeaders, r#move::Move};
+
+/// The table shipped with the crate
+const BUILTIN_TABLE: &str = include_str!("../data/eco.tsv");
+
+/// A named opening line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening {
+    /// ECO code, like `C50`
+    pub eco: String,
+    /// Full name, like `Italian Game: Giuoco Piano`
+    pub name: String,
+    /// Moves reaching the opening's position, in SAN movetext
+    pub moves: String,
+}
+
+impl Opening {
+    /// The opening without its variation, the part of the name before `:`.
+    pub fn family(&self) -> &str {
+        self.name.split_once(": ").map_or(self.name.as_str(), |(family, _)| family)
+    }
+
+    /// The variation, the part of the name after `:`, if any.
+    pub fn variation(&self) -> Option<&str> {
+        self.name.split_once(": ").map(|(_, variation)| variation)
+    }
+}
+
+/// Result of classifying a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpeningMatch {
+    /// Deepest named position the game reached
+    pub opening: Opening,
+    /// Number of plies played when that position was reached
+    pub plies: usize,
+    /// Index of the first move after which the game never returns to a
+    /// position from the table, `None` while it is still in book
+    pub out_of_book: Option<usize>,
+}
+
+impl OpeningMatch {
+    /// The move that left the book in move-number notation, like `7.` for a
+    /// White move or `7...` for a Black one.
+    pub fn out_of_book_move(&self) -> Option<String> {
+        self.out_of_book.map(|ply| {
+            if ply.is_multiple_of(2) {
+                format!("{}.", ply / 2 + 1)
+            } else {
+                format!("{}...", ply / 2 + 1)
+            }
+        })
+    }
+
+    /// Sets the `ECO`, `Opening` and, for named variations, `Variation` tags.
+    pub fn set_tags(&self, headers: &mut PgnHeaders) {
+        headers.set_tag("ECO".to_string(), self.opening.eco.clone());
+        headers.set_tag("Opening".to_string(), self.opening.family().to_string());
+        if let Some(variation) = self.opening.variation() {
+            headers.set_tag("Variation".to_string(), variation.to_string());
+        }
+    }
+}
+
+/// Openings indexed by the positions their lines reach.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::eco::EcoTable;
+/// use analyzer::pgn::Pgn;
+///
+/// let table = EcoTable::builtin();
+/// let game = Pgn::new("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. d3 Nf6 *".to_string());
+/// let opening = table.classify(&game).unwrap();
+/// assert_eq!(opening.opening.eco, "C50");
+/// assert_eq!(opening.opening.name, "Italian Game: Giuoco Piano");
+/// assert_eq!(opening.out_of_book_move().as_deref(), Some("4."));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EcoTable {
+    openings: Vec<Opening>,
+    /// Position key to the opening whose line ends there
+    named: HashMap<String, usize>,
+    /// Keys of every position on any line
+    known: HashSet<String>,
+}
+
+impl EcoTable {
+    /// The table of common openings shipped with the crate.
+    pub fn builtin() -> EcoTable {
+        Self::parse(BUILTIN_TABLE).expect("the built-in ECO table is valid")
+    }
+
+    /// Reads a table with one opening per line: ECO code, name and movetext
+    /// separated by tabs. Empty lines and lines starting with `#` are skipped.
+    ///
+    /// # Errors
+    ///
+    /// [`ChessError::InvalidMove`] naming the line of a malformed entry or a
+    /// move that cannot be played.
+    pub fn parse(text: &str) -> Result<EcoTable, ChessError> {
+        let mut table = EcoTable::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |detail: &str| ChessError::InvalidMove {
+                move_str: format!("{} (ECO table line {})", detail, number + 1),
+            };
+            let mut fields = line.split('\t');
+            let (Some(eco), Some(name), Some(moves), None) = (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid(line));
+            };
+
+            let pgn = Pgn::new(moves.to_string());
+            let keys = position_keys(&pgn.moves);
+            if keys.len() != pgn.moves.len() || keys.is_empty() {
+                return Err(invalid(moves));
+            }
+
+            let index = table.openings.len();
+            table.openings.push(Opening {
+                eco: eco.to_string(),
+                name: name.to_string(),
+                moves: moves.to_string(),
+            });
+            table.named.entry(keys[keys.len() - 1].clone()).or_insert(index);
+            table.known.extend(keys);
+        }
+        Ok(table)
+    }
+
+    /// Finds the deepest named position of the table the game reaches.
+    /// Returns `None` if it reaches none.
+    pub fn classify(&self, pgn: &Pgn) -> Option<OpeningMatch> {
+        let keys = position_keys(&pgn.moves);
+        let (ply, index) = keys
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(ply, key)| self.named.get(key).map(|&index| (ply, index)))?;
+        let last_known = keys.iter().rposition(|key| self.known.contains(key)).unwrap_or(ply);
+
+        Some(OpeningMatch {
+            opening: self.openings[index].clone(),
+            plies: ply + 1,
+            out_of_book: (last_known + 1 < pgn.moves.len()).then_some(last_known + 1),
+        })
+    }
+
+    pub fn openings(&self) -> &[Opening] {
+        &self.openings
+    }
+
+    pub fn len(&self) -> usize {
+        self.openings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.openings.is_empty()
+    }
+}
+
+/// Replays SAN moves from the starting position and returns a key for the
+/// position after each: piece placement, side to move and castling rights.
+/// Stops at the first move that cannot be played.
+fn position_keys(moves: &[Move]) -> Vec<String> {
+    let mut board = Board::init();
+    let mut keys = Vec::with_capacity(moves.len());
+    for (index, mov) in moves.iter().enumerate() {
+        let index = index as u16;
+        let Ok(played) = mov
+            .to_uci_on(&board)
+            .and_then(|uci| Move::validate_uci(&board, &uci, index))
+        else {
+            break;
+        };
+        board.apply_move(&played);
+        let fen = board.generate_fen(&played);
+        keys.push(fen.split_whitespace().take(3).collect::<Vec<_>>().join(" "));
+    }
+    keys
+}
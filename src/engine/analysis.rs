@@ -0,0 +1,173 @@
+use std::fmt;
+
+/// An engine evaluation from the point of view of the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    /// Advantage in centipawns (`score cp 35`)
+    Centipawns(i32),
+    /// Forced mate in the given number of moves; negative when the side to move gets mated (`score mate -3`)
+    Mate(i32),
+}
+
+impl Score {
+    /// Parses the value following `score` in an `info` line (`cp 35`, `mate -3`).
+    fn parse(kind: &str, value: &str) -> Option<Score> {
+        let value = value.parse().ok()?;
+        match kind {
+            "cp" => Some(Score::Centipawns(value)),
+            "mate" => Some(Score::Mate(value)),
+            _ => None,
+        }
+    }
+
+    /// Formats the score as in an `info` line (`cp 35`, `mate -3`).
+    pub fn to_uci(&self) -> String {
+        match self {
+            Score::Centipawns(cp) => format!("cp {}", cp),
+            Score::Mate(moves) => format!("mate {}", moves),
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Score::Centipawns(cp) => write!(f, "{:+.2}", *cp as f64 / 100.0),
+            Score::Mate(moves) => write!(f, "#{}", moves),
+        }
+    }
+}
+
+/// One principal variation reported by the engine (one per `MultiPV` slot).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PvLine {
+    /// MultiPV rank, 1 being the best line
+    pub multipv: usize,
+    /// Search depth the line was reported at
+    pub depth: u32,
+    /// Evaluation from the point of view of the side to move
+    pub score: Score,
+    /// Moves of the line in UCI notation
+    pub pv: Vec<String>,
+}
+
+impl PvLine {
+    /// Parses an `info` line. Returns `None` for lines that carry no score,
+    /// such as `info string` or `currmove` updates.
+    ///
+    /// A score without a `pv` is kept with an empty line: that is how engines
+    /// report a position without legal moves (`info depth 0 score mate 0`).
+    pub fn parse(line: &str) -> Option<PvLine> {
+        let mut tokens = line.split_whitespace();
+        if tokens.next() != Some("info") {
+            return None;
+        }
+
+        let mut multipv = 1;
+        let mut depth = 0;
+        let mut score = None;
+        let mut pv = Vec::new();
+
+        while let Some(token) = tokens.next() {
+            match token {
+                "depth" => depth = tokens.next()?.parse().ok()?,
+                "multipv" => multipv = tokens.next()?.parse().ok()?,
+                "score" => score = Score::parse(tokens.next()?, tokens.next()?),
+                // the rest of an `info string` line is free text
+                "string" => return None,
+                // `pv` is always the last field of the line
+                "pv" => {
+                    pv = tokens.by_ref().map(str::to_string).collect();
+                }
+                _ => {}
+            }
+        }
+
+        Some(PvLine { multipv, depth, score: score?, pv })
+    }
+
+    /// Formats the line as a UCI `info` line, the inverse of [`PvLine::parse`].
+    pub fn to_uci(&self) -> String {
+        let info = format!("info depth {} multipv {} score {}", self.depth, self.multipv, self.score.to_uci());
+        if self.pv.is_empty() {
+            info
+        } else {
+            format!("{} pv {}", info, self.pv.join(" "))
+        }
+    }
+}
+
+/// The outcome of a single `go` command.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Analysis {
+    /// Move the engine settled on, in UCI notation
+    pub best_move: String,
+    /// Expected reply, if the engine reported one
+    pub ponder: Option<String>,
+    /// Latest line reported for every MultiPV slot, best first
+    pub lines: Vec<PvLine>,
+}
+
+impl Analysis {
+    /// Records an `info` line, replacing the previous line of the same MultiPV slot.
+    pub fn record_info(&mut self, line: &str) {
+        let Some(pv_line) = PvLine::parse(line) else {
+            return;
+        };
+        match self.lines.iter_mut().find(|l| l.multipv == pv_line.multipv) {
+            Some(existing) => *existing = pv_line,
+            None => {
+                self.lines.push(pv_line);
+                self.lines.sort_by_key(|l| l.multipv);
+            }
+        }
+    }
+
+    /// Records the final `bestmove <move> [ponder <move>]` line.
+    pub fn record_best_move(&mut self, line: &str) {
+        let mut tokens = line.split_whitespace().skip(1);
+        self.best_move = tokens.next().unwrap_or_default().to_string();
+        if tokens.next() == Some("ponder") {
+            self.ponder = tokens.next().map(str::to_string);
+        }
+    }
+
+    /// Formats the analysis as the UCI output that would produce it: one
+    /// `info` line per MultiPV slot followed by the `bestmove` line.
+    pub fn to_uci_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self.lines.iter().map(PvLine::to_uci).collect();
+        match &self.ponder {
+            Some(ponder) => lines.push(format!("bestmove {} ponder {}", self.best_move, ponder)),
+            None => lines.push(format!("bestmove {}", self.best_move)),
+        }
+        lines
+    }
+
+    /// Rebuilds an analysis from UCI output lines.
+    pub fn from_uci_lines<I, S>(lines: I) -> Analysis
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut analysis = Analysis::default();
+        for line in lines {
+            let line = line.as_ref();
+            if line.starts_with("bestmove") {
+                analysis.record_best_move(line);
+            } else {
+                analysis.record_info(line);
+            }
+        }
+        analysis
+    }
+
+    /// The best line, if the engine reported any.
+    pub fn best_line(&self) -> Option<&PvLine> {
+        self.lines.first()
+    }
+
+    /// Evaluation of the best line from the point of view of the side to move.
+    pub fn score(&self) -> Option<Score> {
+        self.best_line().map(|line| line.score)
+    }
+}
@@ -0,0 +1,90 @@
+use crate::engine::{analysis::Analysis, position::UciPosition};
+use crate::constants::defaults;
+use crate::error::ChessError;
+
+/// Limits for a single search. Unset limits are not sent to the engine; with
+/// no limit at all the search goes to the default depth, as nothing would
+/// ever send the `stop` an infinite search waits for.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::engine::backend::SearchLimits;
+///
+/// assert_eq!(SearchLimits::depth(18).to_go_command(), "go depth 18");
+/// assert_eq!(SearchLimits::default().to_go_command(), "go depth 16");
+///
+/// let limits = SearchLimits { movetime_ms: Some(500), nodes: Some(1_000_000), ..Default::default() };
+/// assert_eq!(limits.to_go_command(), "go nodes 1000000 movetime 500");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SearchLimits {
+    /// Maximum search depth in plies
+    pub depth: Option<u8>,
+    /// Maximum number of nodes to search
+    pub nodes: Option<u64>,
+    /// Time to search in milliseconds
+    pub movetime_ms: Option<u64>,
+}
+
+impl SearchLimits {
+    /// Limits the search to a fixed depth.
+    pub fn depth(depth: u8) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    /// Builds the UCI `go` command for these limits.
+    pub fn to_go_command(&self) -> String {
+        let mut command = String::from("go");
+        if let Some(depth) = self.depth {
+            command.push_str(&format!(" depth {}", depth));
+        }
+        if let Some(nodes) = self.nodes {
+            command.push_str(&format!(" nodes {}", nodes));
+        }
+        if let Some(movetime) = self.movetime_ms {
+            command.push_str(&format!(" movetime {}", movetime));
+        }
+        if command == "go" {
+            command.push_str(&format!(" depth {}", defaults::DEFAULT_ENGINE_DEPTH));
+        }
+        command
+    }
+}
+
+/// Anything that can analyse a chess position: the UCI subprocess adapter
+/// ([`Engine`](crate::engine::engine::Engine)), the in-process
+/// [`MockBackend`](crate::engine::mock::MockBackend) used in tests, or an
+/// evaluator built into the crate.
+///
+/// The analysis pipeline only talks to this trait, so it can be pointed at
+/// any backend.
+pub trait EngineBackend {
+    /// Identifies the backend, e.g. the engine name from the UCI handshake.
+    fn name(&self) -> String;
+
+    /// Signals that the following positions belong to a new game.
+    fn new_game(&mut self) -> Result<(), ChessError>;
+
+    /// Switches Chess960 castling on or off for the following games.
+    ///
+    /// The default only supports standard chess and refuses to enable it.
+    fn set_chess960(&mut self, enabled: bool) -> Result<(), ChessError> {
+        if enabled {
+            return Err(ChessError::InvalidEngineOption {
+                name: String::from("UCI_Chess960"),
+                reason: String::from("the engine does not support this option"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Analyses `position` within `limits`.
+    fn analyse(&mut self, position: &UciPosition, limits: &SearchLimits) -> Result<Analysis, ChessError>;
+
+    /// Releases the backend's resources. The default does nothing.
+    fn quit(&mut self) {}
+}
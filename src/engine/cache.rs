@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::engine::{analysis::Analysis, backend::SearchLimits};
+use crate::error::ChessError;
+
+/// Separates the fields of a cache entry. Never appears in FENs or UCI output.
+const FIELD_SEPARATOR: char = '\t';
+
+/// Identifies a cached search: which engine, configured how, searched which
+/// position within which limits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// Engine identity, as reported by the backend
+    pub engine: String,
+    /// Engine options set before the search, e.g. `Hash=256, MultiPV=3`;
+    /// empty for an engine left at its defaults
+    pub options: String,
+    /// Search limits in `go` command form
+    pub limits: String,
+    /// Position as a normalized FEN (see [`normalize_fen`])
+    pub fen: String,
+}
+
+impl CacheKey {
+    pub fn new(engine: &str, limits: &SearchLimits, fen: &str) -> Self {
+        Self {
+            engine: engine.replace(FIELD_SEPARATOR, " "),
+            options: String::new(),
+            limits: limits.to_go_command(),
+            fen: normalize_fen(fen),
+        }
+    }
+
+    /// Sets the engine options the search ran with, as `(name, value)` pairs.
+    /// Their order does not matter.
+    pub fn with_options<'a, I>(mut self, options: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut options: Vec<String> = options
+            .into_iter()
+            .map(|(name, value)| format!("{}={}", name, value).replace(FIELD_SEPARATOR, " "))
+            .collect();
+        options.sort_by_key(|option| option.to_ascii_lowercase());
+        self.options = options.join(", ");
+        self
+    }
+}
+
+/// Hit and miss counts of an [`AnalysisCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of cached analyses
+    pub entries: usize,
+    /// Lookups answered from the cache
+    pub hits: usize,
+    /// Lookups that had to be searched
+    pub misses: usize,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lookups = self.hits + self.misses;
+        let hit_rate = if lookups == 0 { 0.0 } else { self.hits as f64 * 100.0 / lookups as f64 };
+        write!(
+            f,
+            "{} entries, {} hits, {} misses ({:.1}% hit rate)",
+            self.entries, self.hits, self.misses, hit_rate
+        )
+    }
+}
+
+/// Engine analyses stored by position, engine and search limits, so positions
+/// that were searched before are not searched again.
+///
+/// A cache opened from a file loads all entries up front and appends every new
+/// entry immediately, so results survive crashes and interrupted runs. Each
+/// line of the file holds one entry: engine, engine options, limits and
+/// normalized FEN followed by the UCI output of the search, separated by tabs.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::engine::analysis::Analysis;
+/// use analyzer::engine::backend::SearchLimits;
+/// use analyzer::engine::cache::{AnalysisCache, CacheKey};
+///
+/// let mut cache = AnalysisCache::in_memory();
+/// let key = CacheKey::new("Stockfish 16", &SearchLimits::depth(18),
+///     "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+///
+/// assert!(cache.get(&key).is_none());
+/// cache.insert(key.clone(), Analysis::from_uci_lines(["bestmove e7e5"])).unwrap();
+/// assert_eq!(cache.get(&key).unwrap().best_move, "e7e5");
+/// assert_eq!(cache.stats().hits, 1);
+/// ```
+pub struct AnalysisCache {
+    path: Option<PathBuf>,
+    file: Option<File>,
+    entries: HashMap<CacheKey, Analysis>,
+    hits: usize,
+    misses: usize,
+}
+
+impl AnalysisCache {
+    /// Creates a cache that lives only as long as the process.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            file: None,
+            entries: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Opens the cache file at `path`, creating it if it does not exist.
+    ///
+    /// Malformed lines, e.g. from an interrupted write, are skipped.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ChessError> {
+        let path = path.as_ref().to_path_buf();
+        let file_error = |e: std::io::Error| ChessError::FileError {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        };
+
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)
+            .map_err(file_error)?;
+
+        let mut cache = Self::in_memory();
+        for line in BufReader::new(&file).lines() {
+            if let Some((key, analysis)) = Self::parse_entry(&line.map_err(file_error)?) {
+                cache.entries.insert(key, analysis);
+            }
+        }
+        cache.path = Some(path);
+        cache.file = Some(file);
+        Ok(cache)
+    }
+
+    fn parse_entry(line: &str) -> Option<(CacheKey, Analysis)> {
+        let mut fields = line.split(FIELD_SEPARATOR);
+        let key = CacheKey {
+            engine: fields.next()?.to_string(),
+            options: fields.next()?.to_string(),
+            limits: fields.next()?.to_string(),
+            fen: fields.next()?.to_string(),
+        };
+        let output: Vec<&str> = fields.collect();
+        // an entry without its bestmove line was cut short
+        if !output.last()?.starts_with("bestmove") {
+            return None;
+        }
+        Some((key, Analysis::from_uci_lines(output)))
+    }
+
+    fn format_entry(key: &CacheKey, analysis: &Analysis) -> String {
+        let mut fields = vec![key.engine.clone(), key.options.clone(), key.limits.clone(), key.fen.clone()];
+        fields.extend(analysis.to_uci_lines());
+        fields.join(&FIELD_SEPARATOR.to_string())
+    }
+
+    /// Looks up a cached analysis, counting the lookup as a hit or a miss.
+    pub fn get(&mut self, key: &CacheKey) -> Option<Analysis> {
+        let found = self.entries.get(key).cloned();
+        match found {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        found
+    }
+
+    /// Stores an analysis, appending it to the cache file right away.
+    pub fn insert(&mut self, key: CacheKey, analysis: Analysis) -> Result<(), ChessError> {
+        if let Some(file) = self.file.as_mut() {
+            writeln!(file, "{}", Self::format_entry(&key, &analysis)).map_err(|e| ChessError::FileError {
+                path: self.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+                reason: e.to_string(),
+            })?;
+        }
+        self.entries.insert(key, analysis);
+        Ok(())
+    }
+
+    /// Number of cached analyses.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entry count plus hits and misses since the cache was opened.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.len(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+
+    /// Removes every entry.
+    pub fn clear(&mut self) -> Result<(), ChessError> {
+        self.retain(|_| false).map(|_| ())
+    }
+
+    /// Removes the entries of one engine, e.g. after upgrading it.
+    /// Returns the number of entries removed.
+    pub fn remove_engine(&mut self, engine: &str) -> Result<usize, ChessError> {
+        self.retain(|key| key.engine != engine)
+    }
+
+    /// Keeps only the entries matching `keep` and rewrites the cache file.
+    fn retain<F: Fn(&CacheKey) -> bool>(&mut self, keep: F) -> Result<usize, ChessError> {
+        let before = self.entries.len();
+        self.entries.retain(|key, _| keep(key));
+
+        if let Some(path) = &self.path {
+            let contents: String = self
+                .entries
+                .iter()
+                .map(|(key, analysis)| Self::format_entry(key, analysis) + "\n")
+                .collect();
+            fs::write(path, contents).map_err(|e| ChessError::FileError {
+                path: path.display().to_string(),
+                reason: e.to_string(),
+            })?;
+        }
+        Ok(before - self.entries.len())
+    }
+}
+
+/// Reduces a FEN to the fields that identify a position: placement, side to
+/// move, castling rights and en passant square. The move clocks are dropped,
+/// so the same position reached at a different move number shares an entry.
+pub fn normalize_fen(fen: &str) -> String {
+    fen.split_whitespace().take(4).collect::<Vec<_>>().join(" ")
+}
@@ -1,77 +1,323 @@
 use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, Command, Stdio};
-use crate::constants::defaults;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::config::EngineConfig;
+use crate::engine::{
+    analysis::Analysis,
+    backend::{EngineBackend, SearchLimits},
+    cache::{AnalysisCache, CacheKey},
+    options::EngineInfo,
+    position::UciPosition,
+};
+use crate::error::ChessError;
+
+/// Default engine executable, looked up in `PATH`
+const DEFAULT_ENGINE_PATH: &str = "stockfish";
+
+/// Grace period for the engine to exit after `quit` before it is killed
+const QUIT_GRACE_PERIOD: Duration = Duration::from_millis(500);
 
 pub struct Engine {
     engine: Child,
-    depth:usize
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    info: EngineInfo,
+    depth:u8,
+    timeout: Option<Duration>,
+    threads: Option<u32>,
+    hash_mb: Option<u32>,
+    /// Values sent with `setoption`, latest per option, which cached
+    /// analyses are keyed by
+    options: Vec<(String, String)>,
+    cache: Option<Arc<Mutex<AnalysisCache>>>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Engine{
+    /// Starts Stockfish with the default configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the engine cannot be started or fails the `uci` handshake.
+    /// Use [`Engine::with_config`] to handle these errors instead.
     pub fn new() -> Self{
-        let engine = Self::start_engine().expect("Failed to start");
-        
-        let mut instance = Self{
-            engine,
-            depth: defaults::DEFAULT_ENGINE_DEPTH as usize
-        };
-        instance.init();
-        instance
+        Self::with_config(&EngineConfig::default()).expect("Failed to start")
     }
 
-    fn start_engine() -> std::io::Result<Child> {
-        Command::new("stockfish")
+    /// Starts the engine described by `config` and performs the `uci` handshake.
+    ///
+    /// `config.timeout_ms` bounds every wait for an engine response
+    /// (`uciok`, `readyok`, `bestmove`).
+    pub fn with_config(config: &EngineConfig) -> Result<Self, ChessError> {
+        let path = config.engine_path.as_deref().unwrap_or(DEFAULT_ENGINE_PATH);
+        let mut engine = Command::new(path)
+            .args(&config.engine_args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .spawn()
+            .map_err(|e| ChessError::EngineStart {
+                path: path.to_string(),
+                reason: e.to_string(),
+            })?;
+        let stdin = engine.stdin.take().expect("Failed to open stdin");
+        let lines = Self::spawn_reader(engine.stdout.take().expect("Failed to open stdout"));
+
+        let mut instance = Self{
+            engine,
+            stdin,
+            lines,
+            info: EngineInfo::default(),
+            depth: config.depth,
+            timeout: config.timeout_ms.map(|ms| Duration::from_millis(ms as u64)),
+            threads: config.threads,
+            hash_mb: config.hash_mb,
+            options: Vec::new(),
+            cache: None,
+        };
+        instance.init()?;
+        Ok(instance)
     }
 
-    fn init(&mut self){
+    /// Forwards engine output line by line over a channel, so reads can time out
+    /// and a crashed engine shows up as a closed channel.
+    fn spawn_reader(stdout: ChildStdout) -> Receiver<String> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        receiver
+    }
+
+    fn init(&mut self) -> Result<(), ChessError> {
         // Send UCI commands and receive responses
-        self.send_uci_command("uci");
-        self.send_uci_command("isready");
-        //engine.send_uci_command("ucinewgame");
-        self.send_uci_command("setoption name MultiPV value 3");
+        self.send_uci_command("uci")?;
+        self.read_handshake()?;
+        if self.info.option("MultiPV").is_some() {
+            self.set_option("MultiPV", Some("3"))?;
+        }
+        if let Some(threads) = self.threads {
+            self.set_option("Threads", Some(&threads.to_string()))?;
+        }
+        if let Some(hash_mb) = self.hash_mb {
+            self.set_option("Hash", Some(&hash_mb.to_string()))?;
+        }
+        self.wait_ready()
     }
 
-    pub fn send_uci_command(&mut self, command: &str) {
-        let stdin = self.engine.stdin.as_mut().expect("Failed to open stdin");
-        
-        writeln!(stdin, "{}", command).expect(&format!("Failed to write command {}",command));
+    /// Collects the `id` and `option` lines the engine sends before `uciok`.
+    fn read_handshake(&mut self) -> Result<(), ChessError> {
+        let deadline = self.deadline();
+        loop {
+            let line = self.read_line("uciok", deadline)?;
+            if line.trim() == "uciok" {
+                return Ok(());
+            }
+            self.info.parse_line(&line);
+        }
     }
 
-    pub fn receive_best_move(&mut self) -> String {
-        let stdout = self.engine.stdout.as_mut().expect("Failed to open stdout");
-        let reader = BufReader::new(stdout);
+    /// Returns the identity and options the engine announced during the handshake.
+    pub fn info(&self) -> &EngineInfo {
+        &self.info
+    }
 
-        for line in reader.lines() {
-            let line = line.expect("Failed to read line");
-            //println!("{}",line);
-            if line.starts_with("bestmove") {
-                println!("{}",line);
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if let Some(best_move) = parts.get(1) {
-                    return best_move.to_string();
-                }
+    /// Sends `setoption` after checking the value against the announced option.
+    ///
+    /// Pass `None` as the value for button options.
+    pub fn set_option(&mut self, name: &str, value: Option<&str>) -> Result<(), ChessError> {
+        self.info.validate(name, value)?;
+        match value {
+            Some(value) => {
+                self.send_uci_command(&format!("setoption name {} value {}", name, value))?;
+                // recorded under the name the engine announced, whatever the case used here
+                let name = self.info.option(name).map_or(name, |option| option.name.as_str()).to_string();
+                self.options.retain(|(set, _)| *set != name);
+                self.options.push((name, value.to_string()));
+                Ok(())
             }
+            None => self.send_uci_command(&format!("setoption name {}", name)),
         }
+    }
+
+    pub fn send_uci_command(&mut self, command: &str) -> Result<(), ChessError> {
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|_| ChessError::EngineTerminated {
+                waiting_for: command.to_string(),
+            })
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.timeout.map(|timeout| Instant::now() + timeout)
+    }
 
-        String::new()
+    /// Reads the next line of engine output, failing once `deadline` has passed
+    /// or the engine has exited.
+    fn read_line(&mut self, waiting_for: &str, deadline: Option<Instant>) -> Result<String, ChessError> {
+        let received = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                self.lines.recv_timeout(remaining)
+            }
+            None => self.lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        received.map_err(|error| match error {
+            RecvTimeoutError::Timeout => ChessError::EngineTimeout {
+                waiting_for: waiting_for.to_string(),
+                timeout_ms: self.timeout.map_or(0, |timeout| timeout.as_millis() as u64),
+            },
+            RecvTimeoutError::Disconnected => ChessError::EngineTerminated {
+                waiting_for: waiting_for.to_string(),
+            },
+        })
     }
 
-    pub fn process_fen(&mut self,fen: &str)->String{
+    /// Reads engine output until a line starting with `prefix` arrives and returns it.
+    fn wait_for(&mut self, prefix: &str) -> Result<String, ChessError> {
+        let deadline = self.deadline();
+        loop {
+            let line = self.read_line(prefix, deadline)?;
+            if line.starts_with(prefix) {
+                return Ok(line);
+            }
+        }
+    }
 
-        self.send_uci_command(&format!("position fen {}",fen));
-        self.send_uci_command( &format!("go depth {}",self.depth));
+    /// Sends `isready` and blocks until the engine answers `readyok`.
+    pub fn wait_ready(&mut self) -> Result<(), ChessError> {
+        self.send_uci_command("isready")?;
+        self.wait_for("readyok").map(|_| ())
+    }
 
-        // Receive and print the best move from Stockfish
-        let best_move = self.receive_best_move();
-        best_move
+    /// Tells the engine that the following positions belong to a different game.
+    ///
+    /// Clears the engine's hash and history so evaluations from a previous game
+    /// cannot leak into the next one.
+    pub fn new_game(&mut self) -> Result<(), ChessError> {
+        self.send_uci_command("ucinewgame")?;
+        self.wait_ready()
     }
 
+    /// Consults `cache` before every search and stores every new result in it.
+    ///
+    /// Only positions with a known FEN are cached. The cache can be shared by
+    /// several engines, e.g. the workers of an engine pool.
+    pub fn set_cache(&mut self, cache: Arc<Mutex<AnalysisCache>>) {
+        self.cache = Some(cache);
+    }
+
+    /// Collects `info` lines until `bestmove` arrives.
+    pub fn receive_analysis(&mut self) -> Result<Analysis, ChessError> {
+        let deadline = self.deadline();
+        let mut analysis = Analysis::default();
+        loop {
+            let line = self.read_line("bestmove", deadline)?;
+            if line.starts_with("bestmove") {
+                analysis.record_best_move(&line);
+                return Ok(analysis);
+            }
+            analysis.record_info(&line);
+        }
+    }
+
+    /// Searches a position given as a starting point plus the moves played from it.
+    ///
+    /// Unlike [`Engine::process_fen`], the engine sees the full move history, so
+    /// it can detect repetitions and reuse its hash between consecutive plies.
+    pub fn process_position(&mut self, position: &UciPosition) -> Result<Analysis, ChessError> {
+        let limits = SearchLimits::depth(self.depth);
+        self.analyse(position, &limits)
+    }
+
+    pub fn process_fen(&mut self,fen: &str) -> Result<Analysis, ChessError> {
+        self.process_position(&UciPosition::from_fen(fen))
+    }
+
+    /// Asks the engine to exit, killing it if it does not within a short grace period.
     pub fn quit(&mut self) {
-        self.send_uci_command("quit");
-        self.engine.wait().expect("Failed to wait for Stockfish");
+        let _ = self.send_uci_command("quit");
+
+        let deadline = Instant::now() + QUIT_GRACE_PERIOD;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.engine.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.engine.kill();
+        let _ = self.engine.wait();
+    }
+
+}
+
+impl EngineBackend for Engine {
+    fn name(&self) -> String {
+        self.info.name.clone().unwrap_or_else(|| String::from("unknown engine"))
+    }
+
+    fn new_game(&mut self) -> Result<(), ChessError> {
+        Engine::new_game(self)
+    }
+
+    fn set_chess960(&mut self, enabled: bool) -> Result<(), ChessError> {
+        // engines without the option only play standard chess, which is fine when asked for that
+        if !enabled && self.info.option("UCI_Chess960").is_none() {
+            return Ok(());
+        }
+        self.set_option("UCI_Chess960", Some(if enabled { "true" } else { "false" }))
+    }
+
+    fn analyse(&mut self, position: &UciPosition, limits: &SearchLimits) -> Result<Analysis, ChessError> {
+        let key = match (&self.cache, &position.fen) {
+            (Some(_), Some(fen)) => Some(
+                CacheKey::new(&self.name(), limits, fen)
+                    .with_options(self.options.iter().map(|(name, value)| (name.as_str(), value.as_str()))),
+            ),
+            _ => None,
+        };
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            if let Some(analysis) = cache.lock().expect("analysis cache poisoned").get(key) {
+                return Ok(analysis);
+            }
+        }
+
+        self.send_uci_command(&position.to_command())?;
+        self.send_uci_command(&limits.to_go_command())?;
+        let analysis = self.receive_analysis()?;
+
+        if let (Some(cache), Some(key)) = (&self.cache, key) {
+            cache.lock().expect("analysis cache poisoned").insert(key, analysis.clone())?;
+        }
+        Ok(analysis)
     }
 
-}
\ No newline at end of file
+    fn quit(&mut self) {
+        Engine::quit(self)
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        // make sure a hung or forgotten engine does not outlive us
+        if let Ok(None) = self.engine.try_wait() {
+            let _ = self.engine.kill();
+            let _ = self.engine.wait();
+        }
+    }
+}
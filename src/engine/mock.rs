@@ -0,0 +1,91 @@
+use crate::engine::{
+    analysis::Analysis,
+    backend::{EngineBackend, SearchLimits},
+    position::UciPosition,
+};
+use crate::error::ChessError;
+
+type Responder = Box<dyn FnMut(&UciPosition, &SearchLimits) -> Result<Analysis, ChessError> + Send>;
+
+/// In-process backend that answers with canned analyses, for testing code
+/// built on [`EngineBackend`] without starting an engine.
+///
+/// Every call is recorded, so tests can check which positions were requested.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::engine::analysis::Analysis;
+/// use analyzer::engine::backend::{EngineBackend, SearchLimits};
+/// use analyzer::engine::mock::MockBackend;
+/// use analyzer::engine::position::UciPosition;
+///
+/// let mut analysis = Analysis::default();
+/// analysis.record_info("info depth 10 score cp 30 pv e2e4");
+/// analysis.record_best_move("bestmove e2e4");
+///
+/// let mut backend = MockBackend::new(vec![analysis]);
+/// let result = backend.analyse(&UciPosition::startpos(), &SearchLimits::depth(10)).unwrap();
+/// assert_eq!(result.best_move, "e2e4");
+/// assert_eq!(backend.positions.len(), 1);
+/// ```
+pub struct MockBackend {
+    responder: Responder,
+    /// Positions analysed so far, in order
+    pub positions: Vec<UciPosition>,
+    /// Number of `new_game` calls
+    pub games: usize,
+    /// Whether Chess960 castling was last switched on
+    pub chess960: bool,
+}
+
+impl MockBackend {
+    /// Replays `responses` in order, repeating the last one once they run out.
+    ///
+    /// With no responses at all every search fails with
+    /// [`ChessError::EngineTerminated`].
+    pub fn new(responses: Vec<Analysis>) -> Self {
+        let mut next = 0;
+        Self::with_responder(move |_, _| {
+            let response = responses.get(next).or(responses.last()).cloned();
+            next += 1;
+            response.ok_or_else(|| ChessError::EngineTerminated {
+                waiting_for: String::from("bestmove"),
+            })
+        })
+    }
+
+    /// Computes every answer with `responder`, e.g. to vary it by position.
+    pub fn with_responder<F>(responder: F) -> Self
+    where
+        F: FnMut(&UciPosition, &SearchLimits) -> Result<Analysis, ChessError> + Send + 'static,
+    {
+        Self {
+            responder: Box::new(responder),
+            positions: Vec::new(),
+            games: 0,
+            chess960: false,
+        }
+    }
+}
+
+impl EngineBackend for MockBackend {
+    fn name(&self) -> String {
+        String::from("mock")
+    }
+
+    fn new_game(&mut self) -> Result<(), ChessError> {
+        self.games += 1;
+        Ok(())
+    }
+
+    fn set_chess960(&mut self, enabled: bool) -> Result<(), ChessError> {
+        self.chess960 = enabled;
+        Ok(())
+    }
+
+    fn analyse(&mut self, position: &UciPosition, limits: &SearchLimits) -> Result<Analysis, ChessError> {
+        self.positions.push(position.clone());
+        (self.responder)(position, limits)
+    }
+}
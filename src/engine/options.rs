@@ -0,0 +1,240 @@
+use std::fmt;
+
+use crate::error::ChessError;
+
+/// Identity and configurable options an engine announces during the `uci` handshake.
+///
+/// Built from the `id name`, `id author` and `option name ...` lines the
+/// engine sends before `uciok`.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::engine::options::EngineInfo;
+///
+/// let info = EngineInfo::from_handshake([
+///     "id name Stockfish 16",
+///     "id author the Stockfish developers",
+///     "option name Threads type spin default 1 min 1 max 1024",
+///     "uciok",
+/// ]);
+/// assert_eq!(info.name.as_deref(), Some("Stockfish 16"));
+/// assert!(info.option("threads").is_some());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EngineInfo {
+    /// Engine name from `id name`
+    pub name: Option<String>,
+    /// Engine author from `id author`
+    pub author: Option<String>,
+    /// Options in the order the engine announced them
+    pub options: Vec<EngineOption>,
+}
+
+impl EngineInfo {
+    /// Builds engine information from the lines of a `uci` handshake.
+    ///
+    /// Lines that are neither `id` nor `option` lines are ignored.
+    pub fn from_handshake<I, S>(lines: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut info = Self::default();
+        for line in lines {
+            info.parse_line(line.as_ref());
+        }
+        info
+    }
+
+    /// Records a single handshake line.
+    pub fn parse_line(&mut self, line: &str) {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("id name ") {
+            self.name = Some(name.trim().to_string());
+        } else if let Some(author) = line.strip_prefix("id author ") {
+            self.author = Some(author.trim().to_string());
+        } else if line.starts_with("option ") {
+            if let Some(option) = EngineOption::parse(line) {
+                self.options.push(option);
+            }
+        }
+    }
+
+    /// Looks up an option by name. Option names are case-insensitive in UCI.
+    pub fn option(&self, name: &str) -> Option<&EngineOption> {
+        self.options
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Checks that `setoption name <name> value <value>` would be accepted by the engine.
+    pub fn validate(&self, name: &str, value: Option<&str>) -> Result<(), ChessError> {
+        match self.option(name) {
+            Some(option) => option.validate(value),
+            None => Err(ChessError::InvalidEngineOption {
+                name: name.to_string(),
+                reason: String::from("the engine does not support this option"),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for EngineInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Engine: {}", self.name.as_deref().unwrap_or("unknown"))?;
+        writeln!(f, "Author: {}", self.author.as_deref().unwrap_or("unknown"))?;
+        writeln!(f, "Options:")?;
+        for option in &self.options {
+            writeln!(f, "  {}", option)?;
+        }
+        Ok(())
+    }
+}
+
+/// The type of an engine option together with its default value and limits.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionKind {
+    /// Boolean option (`type check`)
+    Check { default: bool },
+    /// Integer option within a range (`type spin`)
+    Spin { default: i64, min: i64, max: i64 },
+    /// One of a fixed set of strings (`type combo`)
+    Combo { default: String, vars: Vec<String> },
+    /// Action without a value (`type button`)
+    Button,
+    /// Free-form text (`type string`)
+    String { default: String },
+}
+
+/// A single option announced by the engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineOption {
+    /// Option name as announced by the engine
+    pub name: String,
+    /// Option type, default value and limits
+    pub kind: OptionKind,
+}
+
+impl EngineOption {
+    /// Parses an `option name <name> type <type> [default ..] [min ..] [max ..] [var ..]*` line.
+    ///
+    /// Returns `None` when the line has no name or an unknown type.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut name: Vec<&str> = Vec::new();
+        let mut kind: Vec<&str> = Vec::new();
+        let mut default: Option<Vec<&str>> = None;
+        let mut min: Vec<&str> = Vec::new();
+        let mut max: Vec<&str> = Vec::new();
+        let mut vars: Vec<Vec<&str>> = Vec::new();
+
+        let mut field: Option<&str> = None;
+        for token in line.split_whitespace().skip(1) {
+            // names may contain spaces, so only `type` ends them
+            let is_keyword = match field {
+                Some("name") => token == "type",
+                _ => matches!(token, "name" | "type" | "default" | "min" | "max" | "var"),
+            };
+            if is_keyword {
+                field = Some(token);
+                match token {
+                    "default" => default = Some(Vec::new()),
+                    "var" => vars.push(Vec::new()),
+                    _ => {}
+                }
+                continue;
+            }
+
+            match field {
+                Some("name") => name.push(token),
+                Some("type") => kind.push(token),
+                Some("default") => default.get_or_insert_with(Vec::new).push(token),
+                Some("min") => min.push(token),
+                Some("max") => max.push(token),
+                Some("var") => vars.last_mut()?.push(token),
+                _ => return None,
+            }
+        }
+
+        if name.is_empty() {
+            return None;
+        }
+        let default = default.map(|words| words.join(" ")).unwrap_or_default();
+        let kind = match kind.join(" ").as_str() {
+            "check" => OptionKind::Check { default: default == "true" },
+            "spin" => OptionKind::Spin {
+                default: default.parse().ok()?,
+                min: min.join(" ").parse().ok()?,
+                max: max.join(" ").parse().ok()?,
+            },
+            "combo" => OptionKind::Combo {
+                default,
+                vars: vars.iter().map(|words| words.join(" ")).collect(),
+            },
+            "button" => OptionKind::Button,
+            "string" => OptionKind::String {
+                default: if default == "<empty>" { String::new() } else { default },
+            },
+            _ => return None,
+        };
+
+        Some(Self { name: name.join(" "), kind })
+    }
+
+    /// Checks a value for `setoption` against the option's type and limits.
+    ///
+    /// Buttons take no value; every other type requires one.
+    pub fn validate(&self, value: Option<&str>) -> Result<(), ChessError> {
+        let invalid = |reason: String| ChessError::InvalidEngineOption {
+            name: self.name.clone(),
+            reason,
+        };
+
+        match (&self.kind, value) {
+            (OptionKind::Button, None) => Ok(()),
+            (OptionKind::Button, Some(_)) => Err(invalid(String::from("buttons take no value"))),
+            (_, None) => Err(invalid(String::from("a value is required"))),
+            (OptionKind::Check { .. }, Some(value)) => match value {
+                "true" | "false" => Ok(()),
+                _ => Err(invalid(format!("expected true or false, got '{}'", value))),
+            },
+            (OptionKind::Spin { min, max, .. }, Some(value)) => match value.parse::<i64>() {
+                Ok(number) if (*min..=*max).contains(&number) => Ok(()),
+                Ok(number) => Err(invalid(format!("{} is outside {}..={}", number, min, max))),
+                Err(_) => Err(invalid(format!("expected an integer, got '{}'", value))),
+            },
+            (OptionKind::Combo { vars, .. }, Some(value)) => {
+                if vars.iter().any(|var| var.eq_ignore_ascii_case(value)) {
+                    Ok(())
+                } else {
+                    Err(invalid(format!("expected one of {}, got '{}'", vars.join(", "), value)))
+                }
+            }
+            (OptionKind::String { .. }, Some(_)) => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for EngineOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            OptionKind::Check { default } => write!(f, "{} (check, default {})", self.name, default),
+            OptionKind::Spin { default, min, max } => write!(
+                f,
+                "{} (spin, default {}, min {}, max {})",
+                self.name, default, min, max
+            ),
+            OptionKind::Combo { default, vars } => write!(
+                f,
+                "{} (combo, default {}, one of {})",
+                self.name,
+                default,
+                vars.join(", ")
+            ),
+            OptionKind::Button => write!(f, "{} (button)", self.name),
+            OptionKind::String { default } => {
+                write!(f, "{} (string, default '{}')", self.name, default)
+            }
+        }
+    }
+}
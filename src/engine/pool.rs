@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::config::EngineConfig;
+use crate::engine::{
+    analysis::Analysis,
+    backend::{EngineBackend, SearchLimits},
+    cache::AnalysisCache,
+    engine::Engine,
+    position::UciPosition,
+};
+use crate::error::ChessError;
+use crate::game_analysis::{analyse_game_with, Lookups, PlyAnalysis};
+use crate::pgn::Pgn;
+use crate::polyglot::PolyglotBook;
+use crate::syzygy::Tablebase;
+
+/// Starts a fresh backend for a pool worker.
+pub type BackendFactory = dyn Fn() -> Result<Box<dyn EngineBackend + Send>, ChessError> + Send + Sync;
+
+/// Default number of times a search is retried on a restarted engine
+const DEFAULT_MAX_RETRIES: usize = 2;
+
+/// A pool of engines analysing games or positions concurrently.
+///
+/// Every worker thread owns one backend created by the pool's factory, so a
+/// pool of UCI engines runs one engine process per worker. Results always come
+/// back in input order, whatever order the workers finish in.
+///
+/// When a search fails because the engine crashed or stopped answering, the
+/// worker replaces its engine with a new one and retries the same position, up
+/// to [`EnginePool::with_max_retries`] times. Positions carry their full move
+/// history, so the new engine needs no other state to continue.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use analyzer::config::EngineConfig;
+/// use analyzer::engine::backend::SearchLimits;
+/// use analyzer::engine::pool::EnginePool;
+/// use analyzer::pgn::Pgn;
+///
+/// let config = EngineConfig { threads: Some(2), hash_mb: Some(256), ..Default::default() };
+/// let pool = EnginePool::uci(config, 4);
+///
+/// let games = Pgn::parse_all(std::fs::read_to_string("games.pgn").unwrap());
+/// for result in pool.analyse_games(games, &SearchLimits::depth(18)) {
+///     println!("{} plies analysed", result.unwrap().len());
+/// }
+/// ```
+pub struct EnginePool {
+    factory: Arc<BackendFactory>,
+    size: usize,
+    max_retries: usize,
+    book: Option<Arc<PolyglotBook>>,
+    tablebase: Option<Arc<Tablebase>>,
+}
+
+impl EnginePool {
+    /// Creates a pool of `size` workers, each running a backend built by `factory`.
+    pub fn new<F>(factory: F, size: usize) -> Self
+    where
+        F: Fn() -> Result<Box<dyn EngineBackend + Send>, ChessError> + Send + Sync + 'static,
+    {
+        Self {
+            factory: Arc::new(factory),
+            size: size.max(1),
+            max_retries: DEFAULT_MAX_RETRIES,
+            book: None,
+            tablebase: None,
+        }
+    }
+
+    /// Creates a pool of `size` UCI engines started from `config`.
+    pub fn uci(config: EngineConfig, size: usize) -> Self {
+        Self::new(
+            move || Ok(Box::new(Engine::with_config(&config)?) as Box<dyn EngineBackend + Send>),
+            size,
+        )
+    }
+
+    /// Creates a pool of `size` UCI engines started from `config` that share
+    /// one analysis cache, so no position is searched twice by any of them.
+    pub fn uci_with_cache(config: EngineConfig, size: usize, cache: Arc<Mutex<AnalysisCache>>) -> Self {
+        Self::new(
+            move || {
+                let mut engine = Engine::with_config(&config)?;
+                engine.set_cache(Arc::clone(&cache));
+                Ok(Box::new(engine) as Box<dyn EngineBackend + Send>)
+            },
+            size,
+        )
+    }
+
+    /// Sets how often a failed search is retried on a restarted engine.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Marks the moves of analysed games found in `book` as book moves and
+    /// skips the positions between them, see [`Lookups::book`].
+    pub fn with_book(mut self, book: Arc<PolyglotBook>) -> Self {
+        self.book = Some(book);
+        self
+    }
+
+    /// Adds the verdict of `tablebase` to the endgame moves of analysed games.
+    pub fn with_tablebase(mut self, tablebase: Arc<Tablebase>) -> Self {
+        self.tablebase = Some(tablebase);
+        self
+    }
+
+    /// Number of workers in the pool.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Analyses independent positions concurrently, returning results in input order.
+    pub fn analyse_positions(
+        &self,
+        positions: Vec<UciPosition>,
+        limits: &SearchLimits,
+    ) -> Vec<Result<Analysis, ChessError>> {
+        let limits = *limits;
+        let mut results = Vec::new();
+        self.run(
+            positions,
+            move |worker, position| worker.analyse(&position, &limits),
+            |_, result| results.push(result),
+        );
+        results
+    }
+
+    /// Analyses whole games concurrently, one game per worker at a time,
+    /// returning results in input order.
+    pub fn analyse_games(
+        &self,
+        games: Vec<Pgn>,
+        limits: &SearchLimits,
+    ) -> Vec<Result<Vec<PlyAnalysis>, ChessError>> {
+        let mut results = Vec::new();
+        self.analyse_games_with(games, limits, |_, result| results.push(result));
+        results
+    }
+
+    /// Like [`EnginePool::analyse_games`], but hands every result to `emit` as
+    /// soon as it and all games before it are done, so output can be streamed.
+    pub fn analyse_games_with<E>(&self, games: Vec<Pgn>, limits: &SearchLimits, emit: E)
+    where
+        E: FnMut(usize, Result<Vec<PlyAnalysis>, ChessError>),
+    {
+        let limits = *limits;
+        let lookups = Lookups {
+            book: self.book.as_deref(),
+            tablebase: self.tablebase.as_deref(),
+        };
+        self.run(
+            games,
+            move |worker, mut pgn| {
+                worker.new_game(pgn.is_chess960())?;
+                analyse_game_with(&mut pgn, lookups, |position| worker.analyse(position, &limits))
+            },
+            emit,
+        );
+    }
+
+    /// Distributes `jobs` over the workers and emits results in input order.
+    fn run<J, R, W, E>(&self, jobs: Vec<J>, work: W, mut emit: E)
+    where
+        J: Send,
+        R: Send,
+        W: Fn(&mut Worker, J) -> Result<R, ChessError> + Sync,
+        E: FnMut(usize, Result<R, ChessError>),
+    {
+        let total = jobs.len();
+        let queue = Mutex::new(jobs.into_iter().enumerate());
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..self.size.min(total) {
+                let sender = sender.clone();
+                let queue = &queue;
+                let work = &work;
+                let mut worker = Worker::new(Arc::clone(&self.factory), self.max_retries);
+                scope.spawn(move || {
+                    loop {
+                        let job = queue.lock().expect("job queue poisoned").next();
+                        let Some((index, job)) = job else {
+                            break;
+                        };
+                        if sender.send((index, work(&mut worker, job))).is_err() {
+                            break;
+                        }
+                    }
+                    worker.quit();
+                });
+            }
+            drop(sender);
+
+            // hold back results that finish early until everything before them is done
+            let mut pending = BTreeMap::new();
+            let mut next = 0;
+            for (index, result) in receiver {
+                pending.insert(index, result);
+                while let Some(result) = pending.remove(&next) {
+                    emit(next, result);
+                    next += 1;
+                }
+            }
+        });
+    }
+}
+
+/// A worker's backend, started lazily and replaced after a crash.
+struct Worker {
+    factory: Arc<BackendFactory>,
+    backend: Option<Box<dyn EngineBackend + Send>>,
+    max_retries: usize,
+    /// Whether the current game is Chess960, re-applied after a restart
+    chess960: bool,
+}
+
+impl Worker {
+    fn new(factory: Arc<BackendFactory>, max_retries: usize) -> Self {
+        Self {
+            factory,
+            backend: None,
+            max_retries,
+            chess960: false,
+        }
+    }
+
+    fn backend(&mut self) -> Result<&mut Box<dyn EngineBackend + Send>, ChessError> {
+        if self.backend.is_none() {
+            self.backend = Some((self.factory)()?);
+        }
+        Ok(self.backend.as_mut().expect("backend was just started"))
+    }
+
+    fn new_game(&mut self, chess960: bool) -> Result<(), ChessError> {
+        self.chess960 = chess960;
+        let backend = self.backend()?;
+        backend.set_chess960(chess960)?;
+        backend.new_game()
+    }
+
+    /// Analyses a position, restarting the engine and retrying when it crashed or hung.
+    fn analyse(&mut self, position: &UciPosition, limits: &SearchLimits) -> Result<Analysis, ChessError> {
+        let mut retries = 0;
+        loop {
+            match self.backend()?.analyse(position, limits) {
+                Err(error @ (ChessError::EngineTerminated { .. } | ChessError::EngineTimeout { .. }))
+                    if retries < self.max_retries =>
+                {
+                    eprintln!("[Chess Analyzer] Warning: {}, restarting engine", error);
+                    self.restart()?;
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Replaces the backend with a fresh one ready for a new game.
+    fn restart(&mut self) -> Result<(), ChessError> {
+        if let Some(mut backend) = self.backend.take() {
+            backend.quit();
+        }
+        self.new_game(self.chess960)
+    }
+
+    fn quit(&mut self) {
+        if let Some(mut backend) = self.backend.take() {
+            backend.quit();
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::constants::game_state;
+
+/// A position in the form a UCI engine expects it: a starting point plus the
+/// moves played from there, in UCI long algebraic notation (`e2e4`, `e7e8q`).
+///
+/// Sending the move history instead of a bare FEN for every ply lets the engine
+/// see repetitions and reuse its hash table between consecutive positions.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::engine::position::UciPosition;
+///
+/// let mut position = UciPosition::startpos();
+/// position.push("e2e4");
+/// position.push("e7e5");
+/// assert_eq!(position.to_command(), "position startpos moves e2e4 e7e5");
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UciPosition {
+    /// Starting FEN, or `None` for the standard starting position
+    pub start_fen: Option<String>,
+    /// Moves played from the starting point in UCI notation
+    pub moves: Vec<String>,
+    /// FEN of the position reached after `moves`, when known. Not sent to the
+    /// engine; used to recognise the same position reached by other move orders.
+    pub fen: Option<String>,
+}
+
+impl UciPosition {
+    /// Creates a position at the standard starting position with no moves played.
+    pub fn startpos() -> Self {
+        Self {
+            start_fen: None,
+            moves: Vec::new(),
+            fen: Some(game_state::STARTING_FEN.to_string()),
+        }
+    }
+
+    /// Creates a position from a FEN string with no moves played.
+    pub fn from_fen(fen: &str) -> Self {
+        Self {
+            start_fen: Some(fen.to_string()),
+            moves: Vec::new(),
+            fen: Some(fen.to_string()),
+        }
+    }
+
+    /// Appends a move in UCI notation. The resulting FEN is no longer known
+    /// until [`UciPosition::set_fen`] is called.
+    pub fn push(&mut self, uci: &str) {
+        self.moves.push(uci.to_string());
+        self.fen = None;
+    }
+
+    /// Records the FEN of the position reached after the current moves.
+    pub fn set_fen(&mut self, fen: &str) {
+        self.fen = Some(fen.to_string());
+    }
+
+    /// Builds the `position` command for this position.
+    pub fn to_command(&self) -> String {
+        let mut command = match &self.start_fen {
+            Some(fen) => format!("position fen {}", fen),
+            None => String::from("position startpos"),
+        };
+        if !self.moves.is_empty() {
+            command.push_str(" moves ");
+            command.push_str(&self.moves.join(" "));
+        }
+        command
+    }
+}
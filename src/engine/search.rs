@@ -0,0 +1,676 @@
+//! Alpha-beta search built into the crate.
+//!
+//! [`NativeEngine`] is an [`EngineBackend`] that needs no engine executable:
+//! it searches positions on the crate's own [`Board`] and scores them with the
+//! static evaluation of [`crate::evaluation`]. It is far weaker than
+//! Stockfish, but good enough to point out blunders on machines where no UCI
+//! engine is installed.
+//!
+//! The search is the classic recipe: iterative deepening over a negamax
+//! alpha-beta search with a quiescence search of captures at the leaves. Moves
+//! are tried in the order hash move, captures by most valuable victim and
+//! least valuable attacker (MVV-LVA), killer moves, then quiet moves by their
+//! history score. A transposition table keyed by the Polyglot hash of the
+//! position carries results between iterations and between the positions of
+//! a game.
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    board::Board,
+    color::Color,
+    components::Piece,
+    engine::{
+        analysis::{Analysis, PvLine, Score},
+        backend::{EngineBackend, SearchLimits},
+        position::UciPosition,
+    },
+    error::ChessError,
+    evaluation::evaluate,
+    polyglot::zobrist_key,
+    r#move::Move,
+    setup::Setup,
+    square::Square,
+};
+
+/// Time a search may take when the limits set none, so that a depth meant
+/// for a UCI engine does not keep the native search busy for minutes
+pub const DEFAULT_MOVETIME_MS: u64 = 1000;
+
+/// Default size of the transposition table in megabytes
+pub const DEFAULT_HASH_MB: u32 = 16;
+
+/// Deepest iteration and longest line the search considers
+const MAX_PLY: usize = 64;
+const MATE: i32 = 32_000;
+/// Scores beyond this are mates
+const MATE_BOUND: i32 = MATE - MAX_PLY as i32 * 2;
+const INFINITY: i32 = MATE + 1;
+/// How many nodes pass between checks of the clock
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// The native search as an [`EngineBackend`].
+///
+/// Positions may start from a FEN, including a Chess960 one. Without a
+/// time or node limit in [`SearchLimits`] a
+/// search stops after [`DEFAULT_MOVETIME_MS`], see
+/// [`NativeEngine::with_default_movetime`]; [`NativeEngine::search`] can also
+/// search until told to stop.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::engine::backend::{EngineBackend, SearchLimits};
+/// use analyzer::engine::position::UciPosition;
+/// use analyzer::engine::search::NativeEngine;
+///
+/// let mut engine = NativeEngine::new();
+/// let mut position = UciPosition::startpos();
+/// for uci in ["f2f3", "e7e5", "g2g4"] {
+///     position.push(uci);
+/// }
+/// let analysis = engine.analyse(&position, &SearchLimits::depth(2)).unwrap();
+/// assert_eq!(analysis.best_move, "d8h4");
+/// assert_eq!(analysis.score().unwrap().to_uci(), "mate 1");
+/// ```
+pub struct NativeEngine {
+    table: TranspositionTable,
+    default_movetime_ms: u64,
+    /// Whether `UCI_Chess960` is set
+    chess960: bool,
+}
+
+impl Default for NativeEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NativeEngine {
+    /// Creates an engine with a [`DEFAULT_HASH_MB`] transposition table.
+    pub fn new() -> Self {
+        Self::with_hash_mb(DEFAULT_HASH_MB)
+    }
+
+    /// Creates an engine with a transposition table of about `hash_mb` megabytes.
+    pub fn with_hash_mb(hash_mb: u32) -> Self {
+        let entries = (hash_mb.max(1) as usize * 1024 * 1024) / std::mem::size_of::<Option<TableEntry>>();
+        Self {
+            table: TranspositionTable::new(entries),
+            default_movetime_ms: DEFAULT_MOVETIME_MS,
+            chess960: false,
+        }
+    }
+
+    /// Sets the time a search may take when its limits set neither time nor nodes.
+    pub fn with_default_movetime(mut self, movetime_ms: u64) -> Self {
+        self.default_movetime_ms = movetime_ms;
+        self
+    }
+}
+
+impl EngineBackend for NativeEngine {
+    fn name(&self) -> String {
+        String::from("analyzer native")
+    }
+
+    fn new_game(&mut self) -> Result<(), ChessError> {
+        self.table.clear();
+        Ok(())
+    }
+
+    fn set_chess960(&mut self, enabled: bool) -> Result<(), ChessError> {
+        self.chess960 = enabled;
+        Ok(())
+    }
+
+    fn analyse(&mut self, position: &UciPosition, limits: &SearchLimits) -> Result<Analysis, ChessError> {
+        self.search(position, limits, SearchControl::default())
+    }
+}
+
+/// Hooks into a running [`NativeEngine::search`].
+#[derive(Default)]
+pub struct SearchControl<'a> {
+    /// Stops the search once set, e.g. from another thread. The first
+    /// iteration always completes, so there is a move to report.
+    pub stop: Option<&'a AtomicBool>,
+    /// Searches until stopped or a limit is reached, without the default
+    /// movetime
+    pub infinite: bool,
+    /// Called after every completed iteration
+    pub on_iteration: Option<&'a mut dyn FnMut(&Iteration)>,
+}
+
+/// Progress report of a search after a completed iteration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Iteration {
+    pub line: PvLine,
+    /// Nodes searched so far
+    pub nodes: u64,
+    /// Time since the search started
+    pub elapsed: Duration,
+}
+
+impl Iteration {
+    /// Formats the iteration as a UCI `info` line.
+    pub fn to_uci(&self) -> String {
+        let ms = self.elapsed.as_millis() as u64;
+        let info = format!(
+            "info depth {} score {} nodes {} nps {} time {}",
+            self.line.depth,
+            self.line.score.to_uci(),
+            self.nodes,
+            self.nodes * 1000 / ms.max(1),
+            ms
+        );
+        if self.line.pv.is_empty() {
+            info
+        } else {
+            format!("{} pv {}", info, self.line.pv.join(" "))
+        }
+    }
+}
+
+impl NativeEngine {
+    /// Like [`EngineBackend::analyse`], with the search controlled from
+    /// outside as [`SearchControl`] describes.
+    pub fn search(
+        &mut self,
+        position: &UciPosition,
+        limits: &SearchLimits,
+        control: SearchControl,
+    ) -> Result<Analysis, ChessError> {
+        let (mut board, first_index) = match &position.start_fen {
+            Some(fen) => {
+                let setup = Setup::from_fen(fen)?;
+                let first_index = setup.ply_index();
+                (setup.build()?, first_index)
+            }
+            None => (Board::init(), 0),
+        };
+        // with UCI_Chess960 castling is written as the king taking its rook
+        board.chess960 |= self.chess960;
+
+        // replay the game, remembering the positions for repetitions
+        let mut history = Vec::with_capacity(position.moves.len() + MAX_PLY);
+        for (index, uci) in (first_index..).zip(&position.moves) {
+            let mov = Move::validate_uci(&board, uci, index)?;
+            history.push(zobrist_key(&board, side_at(index)));
+            board.apply_move(&mov);
+        }
+
+        let default_movetime_ms = (!control.infinite).then_some(self.default_movetime_ms);
+        let clock = TimeManager::new(limits, default_movetime_ms, control.stop);
+        let mut search = Search::new(&mut self.table, clock, history);
+        let index = first_index + position.moves.len() as u16;
+        Ok(search.iterate(&board, index, limits.depth, control.on_iteration))
+    }
+}
+
+/// When a search has to stop.
+struct TimeManager<'a> {
+    start: Instant,
+    /// No new iteration starts after this
+    soft: Option<Duration>,
+    /// The search is abandoned after this
+    hard: Option<Duration>,
+    max_nodes: Option<u64>,
+    stop: Option<&'a AtomicBool>,
+}
+
+impl<'a> TimeManager<'a> {
+    fn new(limits: &SearchLimits, default_movetime_ms: Option<u64>, stop: Option<&'a AtomicBool>) -> Self {
+        let movetime = match (limits.movetime_ms, limits.nodes) {
+            (Some(movetime), _) => Some(movetime),
+            (None, Some(_)) => None,
+            (None, None) => default_movetime_ms,
+        };
+        let hard = movetime.map(Duration::from_millis);
+        Self {
+            start: Instant::now(),
+            // an iteration takes several times as long as the one before
+            soft: hard.map(|hard| hard / 2),
+            hard,
+            max_nodes: limits.nodes,
+            stop,
+        }
+    }
+
+    /// Whether the search has to stop after `nodes` nodes. The clock is
+    /// only read every [`CLOCK_INTERVAL`] nodes.
+    fn must_stop(&self, nodes: u64) -> bool {
+        self.stop.is_some_and(|stop| stop.load(Ordering::Relaxed))
+            || self.max_nodes.is_some_and(|max| nodes >= max)
+            || (nodes.is_multiple_of(CLOCK_INTERVAL) && self.hard.is_some_and(|hard| self.start.elapsed() >= hard))
+    }
+
+    fn may_start_iteration(&self) -> bool {
+        self.soft.is_none_or(|soft| self.start.elapsed() < soft)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The score is at least this
+    Lower,
+    /// The score is at most this
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TableEntry {
+    key: u64,
+    depth: u8,
+    score: i32,
+    bound: Bound,
+    best: Option<MoveKey>,
+}
+
+/// Results of earlier searches by position, replacing older entries of the
+/// same slot when they were searched less deeply or belong to another position.
+struct TranspositionTable {
+    entries: Vec<Option<TableEntry>>,
+}
+
+impl TranspositionTable {
+    fn new(entries: usize) -> Self {
+        Self {
+            entries: vec![None; entries.max(1)],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+
+    fn get(&self, key: u64) -> Option<TableEntry> {
+        self.entries[self.slot(key)].filter(|entry| entry.key == key)
+    }
+
+    fn store(&mut self, entry: TableEntry) {
+        let slot = self.slot(entry.key);
+        let replace = match self.entries[slot] {
+            Some(old) => old.key != entry.key || old.depth <= entry.depth,
+            None => true,
+        };
+        if replace {
+            self.entries[slot] = Some(entry);
+        }
+    }
+}
+
+/// Source, target and promotion of a move packed into 16 bits.
+type MoveKey = u16;
+
+fn move_key(board: &Board, mov: &Move) -> MoveKey {
+    let (from, to) = move_squares(board, mov);
+    let promotion = match mov.promotion {
+        Some(Piece::Knight) => 1,
+        Some(Piece::Bishop) => 2,
+        Some(Piece::Rook) => 3,
+        Some(Piece::Queen) => 4,
+        _ => 0,
+    };
+    u16::from(from.index()) | (u16::from(to.index()) << 6) | (promotion << 12)
+}
+
+/// Source and target square of a move generated by `board`. Castling is the
+/// king taking its own rook, as in Chess960 UCI, so that no other move shares
+/// its squares.
+fn move_squares(board: &Board, mov: &Move) -> (Square, Square) {
+    if let Some(castling) = mov.castling {
+        let ((king_source, _), (rook_source, _)) = board.castling_squares(mov.color(), castling);
+        return (king_source, rook_source);
+    }
+    match (mov.get_source_square(), mov.get_target_square()) {
+        (Some(from), Some(to)) => (from, to),
+        _ => unreachable!("generated moves have both squares"),
+    }
+}
+
+fn side_at(index: u16) -> Color {
+    if index.is_multiple_of(2) {
+        Color::White
+    } else {
+        Color::Black
+    }
+}
+
+/// Value of a piece for move ordering
+fn ordering_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight | Piece::Bishop => 3,
+        Piece::Rook => 5,
+        Piece::Queen => 9,
+        Piece::King => 20,
+    }
+}
+
+/// State of one search: the clock, the node count and the move ordering
+/// heuristics.
+struct Search<'a> {
+    table: &'a mut TranspositionTable,
+    clock: TimeManager<'a>,
+    /// Keys of the positions of the game and of the current line before the
+    /// position being searched
+    history: Vec<u64>,
+    nodes: u64,
+    stopped: bool,
+    /// Whether limits may interrupt the current iteration; the first one
+    /// always completes so that there is a move to report
+    interruptible: bool,
+    /// Two quiet moves per ply that recently caused a cutoff
+    killers: [[Option<MoveKey>; 2]; MAX_PLY],
+    /// Cutoffs of quiet moves by source and target square
+    history_scores: Box<[[i32; 64]; 64]>,
+    /// Principal variations found below every ply
+    pv: Vec<Vec<String>>,
+}
+
+impl<'a> Search<'a> {
+    fn new(table: &'a mut TranspositionTable, clock: TimeManager<'a>, history: Vec<u64>) -> Self {
+        Self {
+            table,
+            clock,
+            history,
+            nodes: 0,
+            stopped: false,
+            interruptible: false,
+            killers: [[None; 2]; MAX_PLY],
+            history_scores: Box::new([[0; 64]; 64]),
+            pv: vec![Vec::new(); MAX_PLY + 1],
+        }
+    }
+
+    /// Deepens the search one ply at a time until a limit is hit, keeping
+    /// the result of the last completed iteration.
+    fn iterate(
+        &mut self,
+        board: &Board,
+        index: u16,
+        max_depth: Option<u8>,
+        mut on_iteration: Option<&mut dyn FnMut(&Iteration)>,
+    ) -> Analysis {
+        let mut analysis = Analysis::default();
+        if board.legal_moves_unnamed(index).is_empty() {
+            // reported like Stockfish does: mate 0 when checkmated, a draw when stalemated
+            let score = if board.is_in_check(side_at(index)) { Score::Mate(0) } else { Score::Centipawns(0) };
+            let line = PvLine { multipv: 1, depth: 0, score, pv: Vec::new() };
+            if let Some(report) = on_iteration.as_mut() {
+                report(&Iteration { line: line.clone(), nodes: 0, elapsed: self.clock.start.elapsed() });
+            }
+            analysis.best_move = String::from("(none)");
+            analysis.lines = vec![line];
+            return analysis;
+        }
+
+        let max_depth = max_depth.map_or(MAX_PLY, usize::from).clamp(1, MAX_PLY - 1);
+        for depth in 1..=max_depth {
+            self.interruptible = depth > 1;
+            let score = self.negamax(board, index, depth as i32, 0, -INFINITY, INFINITY);
+            // an interrupted iteration is discarded
+            if self.stopped || self.pv[0].is_empty() {
+                break;
+            }
+            analysis.best_move = self.pv[0][0].clone();
+            analysis.ponder = self.pv[0].get(1).cloned();
+            analysis.lines = vec![PvLine {
+                multipv: 1,
+                depth: depth as u32,
+                score: to_score(score),
+                pv: self.pv[0].clone(),
+            }];
+            if let Some(report) = on_iteration.as_mut() {
+                report(&Iteration {
+                    line: analysis.lines[0].clone(),
+                    nodes: self.nodes,
+                    elapsed: self.clock.start.elapsed(),
+                });
+            }
+            let mate_found = score.abs() >= MATE_BOUND && (MATE - score.abs()) as usize <= depth;
+            if mate_found || !self.clock.may_start_iteration() {
+                break;
+            }
+        }
+        analysis
+    }
+
+    fn negamax(&mut self, board: &Board, index: u16, depth: i32, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.pv[ply].clear();
+        if depth <= 0 || ply >= MAX_PLY {
+            return self.quiescence(board, index, ply, alpha, beta);
+        }
+        if self.tick() {
+            return 0;
+        }
+
+        let side = side_at(index);
+        let key = zobrist_key(board, side);
+        if ply > 0 && (board.half_move_count >= 100 || self.repeated(key, board.half_move_count)) {
+            return 0;
+        }
+
+        let entry = self.table.get(key);
+        if let Some(entry) = entry.filter(|entry| ply > 0 && i32::from(entry.depth) >= depth) {
+            let score = score_from_table(entry.score, ply);
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if cutoff {
+                return score;
+            }
+        }
+
+        let mut moves = board.legal_moves_unnamed(index);
+        if moves.is_empty() {
+            return if board.is_in_check(side) { -MATE + ply as i32 } else { 0 };
+        }
+        self.order(board, &mut moves, entry.and_then(|entry| entry.best), ply);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        self.history.push(key);
+        for mov in &moves {
+            let mut next = board.clone();
+            next.apply_move(mov);
+            let score = -self.negamax(&next, index + 1, depth - 1, ply + 1, -beta, -alpha);
+            if self.stopped {
+                break;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(move_key(board, mov));
+            }
+            if score > alpha {
+                alpha = score;
+                self.update_pv(board, ply, mov);
+            }
+            if alpha >= beta {
+                if !mov.is_capture && mov.promotion.is_none() {
+                    self.record_cutoff(board, mov, depth, ply);
+                }
+                break;
+            }
+        }
+        self.history.pop();
+        if self.stopped {
+            return 0;
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.table.store(TableEntry {
+            key,
+            depth: depth as u8,
+            score: score_to_table(best_score, ply),
+            bound,
+            best: best_move,
+        });
+        best_score
+    }
+
+    /// Searches captures and promotions until the position is quiet, so that
+    /// no position is judged in the middle of an exchange. In check every
+    /// move is searched.
+    fn quiescence(&mut self, board: &Board, index: u16, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.pv[ply].clear();
+        if self.tick() {
+            return 0;
+        }
+
+        let side = side_at(index);
+        let in_check = board.is_in_check(side);
+        let stand_pat = evaluate(board).relative(side);
+        if ply >= MAX_PLY {
+            return stand_pat;
+        }
+        if !in_check {
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+        }
+
+        let mut moves = board.legal_moves_unnamed(index);
+        if moves.is_empty() {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+        if !in_check {
+            moves.retain(|mov| mov.is_capture || mov.promotion.is_some());
+        }
+        self.order(board, &mut moves, None, ply);
+
+        let mut best_score = if in_check { -INFINITY } else { stand_pat };
+        for mov in &moves {
+            let mut next = board.clone();
+            next.apply_move(mov);
+            let score = -self.quiescence(&next, index + 1, ply + 1, -beta, -alpha);
+            if self.stopped {
+                return 0;
+            }
+            best_score = best_score.max(score);
+            if score > alpha {
+                alpha = score;
+                self.update_pv(board, ply, mov);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        best_score
+    }
+
+    /// Counts a node and tells whether the search has to stop.
+    fn tick(&mut self) -> bool {
+        self.nodes += 1;
+        if self.interruptible && self.clock.must_stop(self.nodes) {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+
+    /// Whether the position occurred before with the same side to move since
+    /// the last capture or pawn move.
+    fn repeated(&self, key: u64, half_moves: u8) -> bool {
+        self.history
+            .iter()
+            .rev()
+            .take(usize::from(half_moves))
+            .skip(1)
+            .step_by(2)
+            .any(|&earlier| earlier == key)
+    }
+
+    fn order(&self, board: &Board, moves: &mut [Move], hash_move: Option<MoveKey>, ply: usize) {
+        let killers = self.killers.get(ply).copied().unwrap_or_default();
+        moves.sort_by_cached_key(|mov| {
+            let key = move_key(board, mov);
+            let (from, to) = move_squares(board, mov);
+            let score = if Some(key) == hash_move {
+                1_000_000
+            } else if mov.is_capture {
+                let victim = board.get_piece_at(to).unwrap_or(Piece::Pawn);
+                100_000 + 10 * ordering_value(victim) - ordering_value(mov.piece)
+            } else if mov.promotion == Some(Piece::Queen) {
+                90_000
+            } else if Some(key) == killers[0] {
+                80_000
+            } else if Some(key) == killers[1] {
+                70_000
+            } else {
+                self.history_scores[usize::from(from.index())][usize::from(to.index())]
+            };
+            std::cmp::Reverse(score)
+        });
+    }
+
+    fn record_cutoff(&mut self, board: &Board, mov: &Move, depth: i32, ply: usize) {
+        let key = move_key(board, mov);
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(key) {
+            killers[1] = killers[0];
+            killers[0] = Some(key);
+        }
+        let (from, to) = move_squares(board, mov);
+        let score = &mut self.history_scores[usize::from(from.index())][usize::from(to.index())];
+        *score = (*score + depth * depth).min(60_000);
+    }
+
+    fn update_pv(&mut self, board: &Board, ply: usize, mov: &Move) {
+        let mut line = Vec::with_capacity(self.pv[ply + 1].len() + 1);
+        line.push(mov.to_uci_on(board).expect("generated moves have both squares"));
+        line.extend(self.pv[ply + 1].iter().cloned());
+        self.pv[ply] = line;
+    }
+}
+
+/// Mate scores count from the root; the table stores them counted from the
+/// position, which may be reached at another ply.
+fn score_to_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// The score as a UCI engine reports it, mates in moves rather than plies.
+fn to_score(score: i32) -> Score {
+    if score.abs() >= MATE_BOUND {
+        let plies = MATE - score.abs();
+        let moves = (plies + 1) / 2;
+        Score::Mate(if score > 0 { moves } else { -moves })
+    } else {
+        Score::Centipawns(score)
+    }
+}
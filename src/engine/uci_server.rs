@@ -0,0 +1,342 @@
+//! The native search as a UCI engine.
+//!
+//! [`UciServer`] speaks the engine side of the UCI protocol for
+//! [`NativeEngine`], so the built-in search can be loaded into a GUI or a
+//! tournament manager, or driven by this crate's own
+//! [`Engine`](crate::engine::engine::Engine) client. `analyzer uci` runs it on
+//! stdin and stdout.
+//!
+//! Searches run on a worker thread, so `stop`, `ponderhit` and `isready` are
+//! answered while the engine thinks. As the protocol requires, a search
+//! started with `go infinite` or `go ponder` reports its `bestmove` only once
+//! the GUI sends `stop` (or `ponderhit`, after which the search finishes
+//! within the time the `go` command allowed).
+//!
+//! Supported commands: `uci`, `isready`, `setoption` (`Hash`, `Clear Hash`,
+//! `Ponder`, `UCI_Chess960`), `ucinewgame`, `position`, `go` (`depth`, `nodes`, `movetime`,
+//! `wtime`, `btime`, `winc`, `binc`, `movestogo`, `infinite`, `ponder`),
+//! `stop`, `ponderhit` and `quit`. Other commands are ignored.
+
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::engine::{
+    backend::{EngineBackend, SearchLimits},
+    position::UciPosition,
+    search::{Iteration, NativeEngine, SearchControl, DEFAULT_HASH_MB},
+};
+
+/// Largest transposition table the `Hash` option accepts, in megabytes
+const MAX_HASH_MB: u32 = 4096;
+
+/// Time kept in reserve when the move time comes from the clock, for the
+/// GUI's and the operating system's delays
+const MOVE_OVERHEAD_MS: u64 = 50;
+
+/// Moves the remaining time is split across when `movestogo` is not given
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+/// How often a finished infinite search checks whether it may report
+const HOLD_POLL: Duration = Duration::from_millis(5);
+
+/// Serves one UCI session, writing responses to `W`.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::engine::uci_server::UciServer;
+///
+/// let mut server = UciServer::new(std::io::stdout());
+/// assert!(server.handle("position startpos moves f2f3 e7e5 g2g4"));
+/// assert!(server.handle("go depth 2")); // prints "bestmove d8h4"
+/// assert!(!server.handle("quit"));
+/// ```
+pub struct UciServer<W: Write + Send + 'static> {
+    output: Arc<Mutex<W>>,
+    /// The engine, while no search holds it
+    engine: Option<NativeEngine>,
+    position: UciPosition,
+    search: Option<RunningSearch>,
+    /// Whether `UCI_Chess960` is set, kept when `Hash` replaces the engine
+    chess960: bool,
+}
+
+/// A search on the worker thread.
+struct RunningSearch {
+    flags: Arc<SearchFlags>,
+    /// Move time to allow once a ponder search is confirmed
+    ponder_movetime_ms: Option<u64>,
+    handle: JoinHandle<NativeEngine>,
+}
+
+struct SearchFlags {
+    stop: AtomicBool,
+    /// Keeps a finished infinite or ponder search from reporting its move
+    hold: AtomicBool,
+}
+
+/// The parameters of a `go` command.
+#[derive(Debug, Default)]
+struct Go {
+    limits: SearchLimits,
+    infinite: bool,
+    ponder: bool,
+    /// Remaining time and increment of White and Black
+    time_ms: [Option<u64>; 2],
+    increment_ms: [u64; 2],
+    moves_to_go: Option<u64>,
+}
+
+impl<W: Write + Send + 'static> UciServer<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            output: Arc::new(Mutex::new(output)),
+            engine: Some(NativeEngine::new()),
+            position: UciPosition::startpos(),
+            search: None,
+            chess960: false,
+        }
+    }
+
+    /// Handles commands from `input` until `quit` or the end of the input,
+    /// then waits for a running search to report.
+    pub fn run<R: BufRead>(&mut self, input: R) {
+        for line in input.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if !self.handle(&line) {
+                return;
+            }
+        }
+        self.finish_search(false);
+    }
+
+    /// Handles one command. Returns `false` after `quit`.
+    pub fn handle(&mut self, line: &str) -> bool {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("uci") => {
+                let name = self.engine().name();
+                self.send(&format!("id name {} {}", name, env!("CARGO_PKG_VERSION")));
+                self.send("id author the analyzer authors");
+                self.send(&format!(
+                    "option name Hash type spin default {} min 1 max {}",
+                    DEFAULT_HASH_MB, MAX_HASH_MB
+                ));
+                self.send("option name Clear Hash type button");
+                self.send("option name Ponder type check default false");
+                self.send("option name UCI_Chess960 type check default false");
+                self.send("uciok");
+            }
+            Some("isready") => self.send("readyok"),
+            Some("setoption") => self.set_option(&tokens.collect::<Vec<_>>()),
+            Some("ucinewgame") => {
+                let _ = self.engine().new_game();
+            }
+            Some("position") => {
+                if let Some(position) = parse_position(&tokens.collect::<Vec<_>>()) {
+                    self.finish_search(true);
+                    self.position = position;
+                }
+            }
+            Some("go") => {
+                let go = parse_go(&tokens.collect::<Vec<_>>());
+                self.start_search(go);
+            }
+            Some("stop") => self.finish_search(true),
+            Some("ponderhit") => self.ponder_hit(),
+            Some("quit") => {
+                self.finish_search(true);
+                return false;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn send(&self, line: &str) {
+        send(&self.output, line);
+    }
+
+    /// The engine, after waiting for a running search to report.
+    fn engine(&mut self) -> &mut NativeEngine {
+        self.finish_search(true);
+        self.engine.as_mut().expect("the engine is back once the search is finished")
+    }
+
+    fn set_option(&mut self, tokens: &[&str]) {
+        // setoption name <name with spaces> [value <value>]; anything else is ignored
+        let Some((&"name", tokens)) = tokens.split_first() else {
+            return;
+        };
+        let value_at = tokens.iter().position(|&token| token == "value");
+        let name = tokens[..value_at.unwrap_or(tokens.len())].join(" ");
+        let value = value_at.map(|at| tokens[at + 1..].join(" "));
+        match (name.to_ascii_lowercase().as_str(), value) {
+            ("hash", Some(value)) => match value.parse::<u32>() {
+                Ok(hash_mb) if (1..=MAX_HASH_MB).contains(&hash_mb) => {
+                    self.finish_search(true);
+                    let mut engine = NativeEngine::with_hash_mb(hash_mb);
+                    let _ = engine.set_chess960(self.chess960);
+                    self.engine = Some(engine);
+                }
+                _ => self.send(&format!("info string Invalid Hash value '{}'", value)),
+            },
+            ("clear hash", _) => {
+                let _ = self.engine().new_game();
+            }
+            ("ponder", _) => {}
+            ("uci_chess960", Some(value)) => match value.as_str() {
+                "true" | "false" => {
+                    let chess960 = value == "true";
+                    self.chess960 = chess960;
+                    let _ = self.engine().set_chess960(chess960);
+                }
+                _ => self.send(&format!("info string Invalid UCI_Chess960 value '{}'", value)),
+            },
+            _ => self.send(&format!("info string Unknown option '{}'", name)),
+        }
+    }
+
+    fn start_search(&mut self, go: Go) {
+        self.finish_search(true);
+        let mut engine = self.engine.take().expect("no search is running");
+
+        let clock_movetime = self.clock_movetime(&go);
+        let mut limits = go.limits;
+        limits.movetime_ms = if go.ponder { None } else { go.limits.movetime_ms.or(clock_movetime) };
+        let infinite = go.infinite || go.ponder;
+        let flags = Arc::new(SearchFlags {
+            stop: AtomicBool::new(false),
+            hold: AtomicBool::new(infinite),
+        });
+
+        let position = self.position.clone();
+        let output = Arc::clone(&self.output);
+        let thread_flags = Arc::clone(&flags);
+        let handle = thread::spawn(move || {
+            let mut report = |iteration: &Iteration| send(&output, &iteration.to_uci());
+            let control = SearchControl {
+                stop: Some(&thread_flags.stop),
+                infinite,
+                on_iteration: Some(&mut report),
+            };
+            let result = engine.search(&position, &limits, control);
+            while thread_flags.hold.load(Ordering::Relaxed) && !thread_flags.stop.load(Ordering::Relaxed) {
+                thread::sleep(HOLD_POLL);
+            }
+            match result {
+                Ok(analysis) => match analysis.ponder {
+                    Some(ponder) => send(&output, &format!("bestmove {} ponder {}", analysis.best_move, ponder)),
+                    None => send(&output, &format!("bestmove {}", analysis.best_move)),
+                },
+                Err(error) => {
+                    send(&output, &format!("info string {}", error));
+                    send(&output, "bestmove 0000");
+                }
+            }
+            engine
+        });
+
+        self.search = Some(RunningSearch {
+            flags,
+            ponder_movetime_ms: if go.ponder { go.limits.movetime_ms.or(clock_movetime) } else { None },
+            handle,
+        });
+    }
+
+    /// Waits for the running search to report its move, stopping it first
+    /// if `stop` is set.
+    fn finish_search(&mut self, stop: bool) {
+        let Some(search) = self.search.take() else {
+            return;
+        };
+        if stop {
+            search.flags.stop.store(true, Ordering::Relaxed);
+        }
+        search.flags.hold.store(false, Ordering::Relaxed);
+        let engine = search.handle.join().unwrap_or_else(|_| NativeEngine::new());
+        self.engine = Some(engine);
+    }
+
+    /// The opponent played the expected move: the ponder search becomes a
+    /// normal one, limited by the time the `go` command allowed.
+    fn ponder_hit(&mut self) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        search.flags.hold.store(false, Ordering::Relaxed);
+        if let Some(movetime_ms) = search.ponder_movetime_ms {
+            let flags = Arc::clone(&search.flags);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(movetime_ms));
+                flags.stop.store(true, Ordering::Relaxed);
+            });
+        }
+    }
+
+    /// Time to spend on the move from the clock of the side to move.
+    fn clock_movetime(&self, go: &Go) -> Option<u64> {
+        let side = if self.side_to_move_is_white() { 0 } else { 1 };
+        let remaining = go.time_ms[side]?;
+        let moves_to_go = go.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let share = remaining / moves_to_go + go.increment_ms[side] * 3 / 4;
+        Some(share.min(remaining.saturating_sub(MOVE_OVERHEAD_MS)).max(1))
+    }
+
+    fn side_to_move_is_white(&self) -> bool {
+        let white_starts = match &self.position.start_fen {
+            Some(fen) => fen.split_whitespace().nth(1) != Some("b"),
+            None => true,
+        };
+        white_starts == self.position.moves.len().is_multiple_of(2)
+    }
+}
+
+fn send<W: Write>(output: &Mutex<W>, line: &str) {
+    let mut output = output.lock().expect("UCI output poisoned");
+    // a GUI that went away cannot be told anything
+    let _ = writeln!(output, "{}", line).and_then(|_| output.flush());
+}
+
+/// Parses the arguments of `position`: `startpos` or `fen <fen>`, then
+/// optionally `moves` and the moves in UCI notation.
+fn parse_position(tokens: &[&str]) -> Option<UciPosition> {
+    let moves_at = tokens.iter().position(|&token| token == "moves").unwrap_or(tokens.len());
+    let mut position = match tokens.first() {
+        Some(&"startpos") => UciPosition::startpos(),
+        Some(&"fen") if moves_at > 1 => UciPosition::from_fen(&tokens[1..moves_at].join(" ")),
+        _ => return None,
+    };
+    for uci in tokens.iter().skip(moves_at + 1) {
+        position.push(uci);
+    }
+    Some(position)
+}
+
+/// Parses the arguments of `go`; unknown and malformed ones are skipped.
+fn parse_go(tokens: &[&str]) -> Go {
+    let mut go = Go::default();
+    let mut tokens = tokens.iter();
+    while let Some(&token) = tokens.next() {
+        let mut number = || tokens.next().and_then(|value| value.parse::<u64>().ok());
+        match token {
+            "depth" => go.limits.depth = number().map(|depth| depth.min(u64::from(u8::MAX)) as u8),
+            "nodes" => go.limits.nodes = number(),
+            "movetime" => go.limits.movetime_ms = number(),
+            "wtime" => go.time_ms[0] = number(),
+            "btime" => go.time_ms[1] = number(),
+            "winc" => go.increment_ms[0] = number().unwrap_or(0),
+            "binc" => go.increment_ms[1] = number().unwrap_or(0),
+            "movestogo" => go.moves_to_go = number(),
+            "infinite" => go.infinite = true,
+            "ponder" => go.ponder = true,
+            _ => {}
+        }
+    }
+    go
+}
@@ -0,0 +1,522 @@
+//! Static evaluation without an engine.
+//!
+//! [`evaluate`] scores a position from its pieces alone: material,
+//! piece-square tables, pawn structure, king safety and mobility. Every term
+//! is scored twice, for the middlegame and for the endgame, and the two are
+//! blended by how much material is left (a tapered evaluation), so that a
+//! centralised king is a liability with queens on the board and an asset
+//! without them.
+//!
+//! The result keeps each term per side, which is what makes it useful for
+//! explaining a position: the [`Display`](std::fmt::Display) of an
+//! [`Evaluation`] is a small table of where each side's advantage comes from.
+//!
+//! ```rust
+//! use analyzer::board::Board;
+//! use analyzer::evaluation::{evaluate, EvalTerm};
+//!
+//! let evaluation = evaluate(&Board::init());
+//! assert_eq!(evaluation.total(), 0);
+//! assert_eq!(evaluation.term(EvalTerm::Material).white, evaluation.term(EvalTerm::Material).black);
+//! ```
+
+use std::fmt;
+
+use crate::{
+    attacks,
+    bitboard::{Bitboard, Direction},
+    board::Board,
+    color::Color,
+    components::Piece,
+    square::Square,
+};
+
+/// Game phase of the starting material; minor pieces count 1, rooks 2 and
+/// queens 4
+pub const MAX_PHASE: i32 = 24;
+
+/// The parts an evaluation is made of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvalTerm {
+    /// Piece values
+    Material,
+    /// Bonuses and penalties for where pieces stand
+    PieceSquares,
+    /// Doubled, isolated and passed pawns
+    PawnStructure,
+    /// Pawn shield, open files and attackers near the king
+    KingSafety,
+    /// Squares the pieces reach
+    Mobility,
+}
+
+impl EvalTerm {
+    /// All terms, in the order they are reported.
+    pub const ALL: [EvalTerm; 5] = [
+        EvalTerm::Material,
+        EvalTerm::PieceSquares,
+        EvalTerm::PawnStructure,
+        EvalTerm::KingSafety,
+        EvalTerm::Mobility,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EvalTerm::Material => "material",
+            EvalTerm::PieceSquares => "piece-squares",
+            EvalTerm::PawnStructure => "pawn-structure",
+            EvalTerm::KingSafety => "king-safety",
+            EvalTerm::Mobility => "mobility",
+        }
+    }
+}
+
+/// One term for both sides, in centipawns after tapering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TermScore {
+    pub term: EvalTerm,
+    pub white: i32,
+    pub black: i32,
+}
+
+impl TermScore {
+    /// The term from White's point of view.
+    pub fn net(&self) -> i32 {
+        self.white - self.black
+    }
+}
+
+/// A static evaluation with its breakdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    /// Material left on the board, from [`MAX_PHASE`] in the opening down to
+    /// 0 with only kings and pawns
+    pub phase: i32,
+    /// Every term of [`EvalTerm::ALL`], in that order
+    pub terms: Vec<TermScore>,
+}
+
+impl Evaluation {
+    /// Centipawns from White's point of view.
+    pub fn total(&self) -> i32 {
+        self.terms.iter().map(TermScore::net).sum()
+    }
+
+    /// Centipawns from the point of view of `side`.
+    pub fn relative(&self, side: Color) -> i32 {
+        match side {
+            Color::White => self.total(),
+            Color::Black => -self.total(),
+        }
+    }
+
+    /// The score of one term.
+    pub fn term(&self, term: EvalTerm) -> TermScore {
+        self.terms
+            .iter()
+            .copied()
+            .find(|score| score.term == term)
+            .expect("an evaluation holds every term")
+    }
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<16}{:>8}{:>8}{:>8}", "term", "white", "black", "total")?;
+        for score in &self.terms {
+            writeln!(
+                f,
+                "{:<16}{:>8}{:>8}{:>8}",
+                score.term.name(),
+                pawns(score.white),
+                pawns(score.black),
+                pawns(score.net())
+            )?;
+        }
+        write!(f, "{:<16}{:>24}", format!("phase {}/{}", self.phase, MAX_PHASE), pawns(self.total()))
+    }
+}
+
+fn pawns(cp: i32) -> String {
+    format!("{:+.2}", f64::from(cp) / 100.0)
+}
+
+/// A middlegame and an endgame value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Phased(i32, i32);
+
+impl std::ops::AddAssign for Phased {
+    fn add_assign(&mut self, other: Phased) {
+        self.0 += other.0;
+        self.1 += other.1;
+    }
+}
+
+impl std::ops::Mul<i32> for Phased {
+    type Output = Phased;
+
+    fn mul(self, factor: i32) -> Phased {
+        Phased(self.0 * factor, self.1 * factor)
+    }
+}
+
+impl Phased {
+    fn taper(self, phase: i32) -> i32 {
+        (self.0 * phase + self.1 * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+/// Evaluates a position statically, see the module documentation.
+pub fn evaluate(board: &Board) -> Evaluation {
+    let phase = game_phase(board);
+    let mut sides = [[Phased::default(); 5]; 2];
+    for (side, color) in [Color::White, Color::Black].into_iter().enumerate() {
+        let view = SideView::new(board, color);
+        sides[side] = [
+            view.material(),
+            view.piece_squares(),
+            view.pawn_structure(),
+            view.king_safety(),
+            view.mobility(),
+        ];
+    }
+    let terms = EvalTerm::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(i, term)| TermScore {
+            term,
+            white: sides[0][i].taper(phase),
+            black: sides[1][i].taper(phase),
+        })
+        .collect();
+    Evaluation { phase, terms }
+}
+
+fn game_phase(board: &Board) -> i32 {
+    let count = |piece: Piece| board.by_piece.get(piece).get().count_ones() as i32;
+    let phase = count(Piece::Knight) + count(Piece::Bishop) + 2 * count(Piece::Rook) + 4 * count(Piece::Queen);
+    phase.min(MAX_PHASE)
+}
+
+/// Piece values, middlegame and endgame
+fn piece_value(piece: Piece) -> Phased {
+    match piece {
+        Piece::Pawn => Phased(82, 94),
+        Piece::Knight => Phased(337, 281),
+        Piece::Bishop => Phased(365, 297),
+        Piece::Rook => Phased(477, 512),
+        Piece::Queen => Phased(1025, 936),
+        Piece::King => Phased(0, 0),
+    }
+}
+
+/// Files next to the file of `square`.
+fn adjacent_files(square: u8) -> Bitboard {
+    let file = Bitboard::file_of(square);
+    file.shift(Direction::East) | file.shift(Direction::West)
+}
+
+/// Squares in front of `square` from the point of view of `color`, on its
+/// file and both adjacent ones.
+fn front_span(square: u8, color: Color) -> Bitboard {
+    let files = adjacent_files(square) | Bitboard::file_of(square);
+    let rank = square / 8;
+    let ahead = match color {
+        Color::White if rank < 7 => Bitboard::FULL << (8 * (rank + 1)),
+        Color::Black if rank > 0 => Bitboard::FULL >> (8 * (8 - rank)),
+        _ => Bitboard::EMPTY,
+    };
+    files & ahead
+}
+
+/// The pieces of one side and those of the other.
+struct SideView<'a> {
+    board: &'a Board,
+    color: Color,
+    own: Bitboard,
+    enemy: Bitboard,
+}
+
+impl<'a> SideView<'a> {
+    fn new(board: &'a Board, color: Color) -> Self {
+        SideView {
+            board,
+            color,
+            own: board.by_color.get(color),
+            enemy: board.by_color.get(color.get_opposite()),
+        }
+    }
+
+    fn pieces(&self, piece: Piece) -> Bitboard {
+        self.board.by_piece.get(piece) & self.own
+    }
+
+    fn enemy_pieces(&self, piece: Piece) -> Bitboard {
+        self.board.by_piece.get(piece) & self.enemy
+    }
+
+    /// Rank of `square` counted from this side's back rank, 0 to 7.
+    fn relative_rank(&self, square: u8) -> u8 {
+        match self.color {
+            Color::White => square / 8,
+            Color::Black => 7 - square / 8,
+        }
+    }
+
+    fn material(&self) -> Phased {
+        let mut score = Phased::default();
+        for piece in Piece::get_all() {
+            score += piece_value(piece) * self.pieces(piece).popcount() as i32;
+        }
+        score
+    }
+
+    fn piece_squares(&self) -> Phased {
+        let mut score = Phased::default();
+        for piece in Piece::get_all() {
+            let (middlegame, endgame) = piece_square_tables(piece);
+            for square in self.pieces(piece) {
+                // tables are written from White's side, rank 8 first
+                let index = match self.color {
+                    Color::White => square ^ 56,
+                    Color::Black => square,
+                } as usize;
+                score += Phased(middlegame[index], endgame[index]);
+            }
+        }
+        score
+    }
+
+    fn pawn_structure(&self) -> Phased {
+        let pawns = self.pieces(Piece::Pawn);
+        let enemy_pawns = self.enemy_pieces(Piece::Pawn);
+        let mut score = Phased::default();
+        for file in (0..8).map(Bitboard::file_of) {
+            let on_file = (pawns & file).popcount() as i32;
+            if on_file > 1 {
+                score += DOUBLED_PAWN * (on_file - 1);
+            }
+        }
+        for square in pawns {
+            if (pawns & adjacent_files(square)).is_empty() {
+                score += ISOLATED_PAWN;
+            }
+            if (enemy_pawns & front_span(square, self.color)).is_empty() {
+                score += PASSED_PAWN[self.relative_rank(square) as usize];
+            }
+        }
+        score
+    }
+
+    fn king_safety(&self) -> Phased {
+        let Some(king) = self.board.king_square(self.color).map(Square::index) else {
+            return Phased::default();
+        };
+        let mut score = Phased::default();
+
+        // pawns in front of a king still on its back ranks
+        if self.relative_rank(king) <= 1 {
+            let shield = front_span(king, self.color) & self.pieces(Piece::Pawn);
+            for square in shield {
+                if self.relative_rank(square) <= self.relative_rank(king) + 2 {
+                    score += PAWN_SHIELD;
+                }
+            }
+        }
+        // files next to the king without a pawn of this side
+        let near = adjacent_files(king) | Bitboard::file_of(king);
+        for file in (0..8).map(Bitboard::file_of) {
+            if !(near & file).is_empty() && (self.pieces(Piece::Pawn) & file).is_empty() {
+                score += OPEN_FILE_NEAR_KING;
+            }
+        }
+        // enemy pieces that reach the squares around the king
+        let zone = attacks::king(king) | Bitboard::from_square(king);
+        for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+            for square in self.enemy_pieces(piece) {
+                if !(self.attacks(piece, square) & zone).is_empty() {
+                    score += king_attacker(piece);
+                }
+            }
+        }
+        score
+    }
+
+    fn mobility(&self) -> Phased {
+        let enemy_pawns = self.enemy_pieces(Piece::Pawn);
+        let enemy_pawn_attacks = match self.color {
+            Color::White => enemy_pawns.shift(Direction::SouthEast) | enemy_pawns.shift(Direction::SouthWest),
+            Color::Black => enemy_pawns.shift(Direction::NorthEast) | enemy_pawns.shift(Direction::NorthWest),
+        };
+        let mut score = Phased::default();
+        for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+            let (baseline, weight) = mobility_weight(piece);
+            for square in self.pieces(piece) {
+                let reach = self.attacks(piece, square) & !self.own & !enemy_pawn_attacks;
+                score += weight * (reach.popcount() as i32 - baseline);
+            }
+        }
+        score
+    }
+
+    fn attacks(&self, piece: Piece, square: u8) -> Bitboard {
+        attacks::piece(piece, self.color, square, self.board.occupied)
+    }
+}
+
+const DOUBLED_PAWN: Phased = Phased(-10, -20);
+const ISOLATED_PAWN: Phased = Phased(-10, -15);
+/// Bonus of a passed pawn by its rank, counted from its own side
+const PASSED_PAWN: [Phased; 8] = [
+    Phased(0, 0),
+    Phased(5, 10),
+    Phased(10, 15),
+    Phased(15, 25),
+    Phased(25, 45),
+    Phased(40, 75),
+    Phased(60, 120),
+    Phased(0, 0),
+];
+const PAWN_SHIELD: Phased = Phased(12, 0);
+const OPEN_FILE_NEAR_KING: Phased = Phased(-15, 0);
+
+fn king_attacker(piece: Piece) -> Phased {
+    match piece {
+        Piece::Knight | Piece::Bishop => Phased(-20, 0),
+        Piece::Rook => Phased(-30, -5),
+        Piece::Queen => Phased(-50, -10),
+        Piece::Pawn | Piece::King => Phased(0, 0),
+    }
+}
+
+/// Squares a piece typically reaches and the value of each square more or less
+fn mobility_weight(piece: Piece) -> (i32, Phased) {
+    match piece {
+        Piece::Knight => (4, Phased(4, 4)),
+        Piece::Bishop => (7, Phased(5, 5)),
+        Piece::Rook => (7, Phased(2, 4)),
+        Piece::Queen => (14, Phased(1, 2)),
+        Piece::Pawn | Piece::King => (0, Phased(0, 0)),
+    }
+}
+
+/// Middlegame and endgame piece-square tables, from White's side with rank 8
+/// in the first row.
+fn piece_square_tables(piece: Piece) -> (&'static [i32; 64], &'static [i32; 64]) {
+    match piece {
+        Piece::Pawn => (&PAWN_MG, &PAWN_EG),
+        Piece::Knight => (&KNIGHT, &KNIGHT),
+        Piece::Bishop => (&BISHOP, &BISHOP),
+        Piece::Rook => (&ROOK, &ROOK_EG),
+        Piece::Queen => (&QUEEN, &QUEEN),
+        Piece::King => (&KING_MG, &KING_EG),
+    }
+}
+
+#[rustfmt::skip]
+const PAWN_MG: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     10,  10,  20,  30,  30,  20,  10,  10,
+      5,   5,  10,  25,  25,  10,   5,   5,
+      0,   0,   0,  20,  20,   0,   0,   0,
+      5,  -5, -10,   0,   0, -10,  -5,   5,
+      5,  10,  10, -20, -20,  10,  10,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const PAWN_EG: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     80,  80,  80,  80,  80,  80,  80,  80,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     30,  30,  30,  30,  30,  30,  30,  30,
+     15,  15,  15,  15,  15,  15,  15,  15,
+      5,   5,   5,   5,   5,   5,   5,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10,  10,  10,  10,  10,   5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const ROOK_EG: [i32; 64] = [
+      5,   5,   5,   5,   5,   5,   5,   5,
+     10,  10,  10,  10,  10,  10,  10,  10,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+      0,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   5,   5,   5,   5,   5,   0, -10,
+    -10,   0,   5,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const KING_MG: [i32; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+#[rustfmt::skip]
+const KING_EG: [i32; 64] = [
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50,
+];
@@ -1,7 +1,14 @@
-use crate::{board::Board, color::Color, components::Piece, r#move::Move};
+use crate::{
+    board::Board,
+    chess960::back_rank,
+    color::Color,
+    components::{ColoredPiece, Piece},
+    r#move::{Castling, Move},
+    square::{File, Square},
+};
 
 
-const FEN_SQUARE_INDICES: [usize; 64] = [
+const FEN_SQUARE_INDICES: [u8; 64] = [
     56, 57, 58, 59, 60, 61, 62, 63,
     48, 49, 50, 51, 52, 53, 54, 55,
     40, 41, 42, 43, 44, 45, 46, 47,
@@ -27,26 +34,19 @@ pub fn generate(board:&Board, last_move :&Move)->String{
     fen
 }
 
-fn piece_placement(board:&Board) ->String{
-    let occupied =board.occupied.get(); 
+pub(crate) fn piece_placement(board:&Board) ->String{
     let mut piece_placement = String::from("");
     let mut count_empty=0;
     for i in FEN_SQUARE_INDICES{
-        let mask = 1<< i;
-        let v = mask & occupied;
-        
-        if v==0{
-            count_empty +=1;    
-        }else{
+        if let Some(piece) = board.piece_at(Square::new_unchecked(i)){
             if count_empty>0{
                 piece_placement.push_str(count_empty.to_string().as_str());
             }
-            let color = _get_color(board, mask);
-            let piece = _get_piece(board, mask);
-            let piece_char = piece.unwrap().to_char(color);
-            piece_placement.push(piece_char);
+            piece_placement.push(piece.to_fen_char());
             
             count_empty = 0
+        }else{
+            count_empty +=1;    
         }
 
         if (i+1)%8==0{
@@ -86,41 +86,53 @@ fn extract_en_passant(mov :&Move)-> String{
 }
 
 
+/// The castling field of a FEN: `KQkq` letters, or in Chess960 X-FEN, where
+/// a rook other than the outermost one on its side of the king is named by
+/// its file (`HAha` style letters, uppercase for White).
 pub fn extract_castling_rights(board:&Board) -> String {
-    let rights = ['K', 'Q', 'k', 'q'];
+    castling_field(board, false)
+}
+
+/// The castling field of a Shredder-FEN, every right named by the file of
+/// its rook (`HAha` for the standard position).
+pub fn extract_shredder_castling_rights(board:&Board) -> String {
+    castling_field(board, true)
+}
+
+fn castling_field(board:&Board, shredder:bool) -> String {
     let mut castling_fragment = String::new();
 
-    for (bit, right) in (0..4).rev().zip(rights.iter()) {
-        if (board.castling_rights & (1 << bit)) != 0 {
-            castling_fragment.push(*right);
+    for color in [Color::White, Color::Black] {
+        for (side, letter) in [(Castling::King, 'k'), (Castling::Queen, 'q')] {
+            if board.castling_rights & side.right(color) == 0 {
+                continue;
+            }
+            let rook_file = board.castling_files.rook(side);
+            let letter = if shredder || (board.chess960 && !is_outermost_rook(board, color, side)) {
+                rook_file.to_char()
+            } else {
+                letter
+            };
+            castling_fragment.push(if color == Color::White { letter.to_ascii_uppercase() } else { letter });
         }
     }
 
-    castling_fragment.push(if castling_fragment.is_empty() { '-' } else { '\0' });
+    if castling_fragment.is_empty() {
+        castling_fragment.push('-');
+    }
     castling_fragment
 }
 
-fn _get_color(board:&Board, mask:u64) ->Color{
-    let white = board.by_color.white.get() & mask;
-    
-    if white==0{
-        Color::Black
-    }else{
-        Color::White
-    }
-
+/// Whether no other rook of `color` stands beyond the castling rook on `side`,
+/// so X-FEN can write the right as `K` or `Q`.
+fn is_outermost_rook(board:&Board, color:Color, side:Castling) -> bool {
+    let rook_file = board.castling_files.rook(side);
+    let rook = ColoredPiece::new(color, Piece::Rook);
+    !File::ALL
+        .into_iter()
+        .filter(|&file| match side {
+            Castling::King => file > rook_file,
+            Castling::Queen => file < rook_file,
+        })
+        .any(|file| board.piece_at(Square::from_file_rank(file, back_rank(color))) == Some(rook))
 }
-
-fn _get_piece(board:&Board, mask:u64) ->Result<Piece, &str>{
-    for piece in Piece::get_all(){
-        let piece_board = board.by_piece.get(piece);
-        if (piece_board.get() & mask) !=0{
-            return Ok(piece);
-        } 
-    }
-
-    Err("Value not found")
-    
-}
-
-
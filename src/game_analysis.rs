@@ -0,0 +1,190 @@
+//! Engine analysis of complete games.
+//!
+//! This module drives an [`EngineBackend`] through the positions of a game and
+//! collects the results per ply. It does not care which backend it talks to,
+//! so the same pipeline works with Stockfish, the mock backend used in tests
+//! or an evaluator built into the crate.
+
+use crate::{
+    board::Board,
+    engine::{
+        analysis::Analysis,
+        backend::{EngineBackend, SearchLimits},
+        position::UciPosition,
+    },
+    error::ChessError,
+    pgn::Pgn,
+    polyglot::PolyglotBook,
+    r#move::Move,
+    syzygy::{Tablebase, TablebaseProbe},
+};
+
+/// Engine analysis of the position reached after one move of a game.
+#[derive(Debug, Clone)]
+pub struct PlyAnalysis {
+    /// Ply index of the move, counted from White's first move of the game:
+    /// its parity gives the mover, even for White. A game from a `FEN` tag
+    /// starts at the tag's move number.
+    pub ply: usize,
+    /// The move played, as written in the PGN
+    pub san: String,
+    /// The move played, in UCI notation
+    pub uci: String,
+    /// Clock time left after the move, if the PGN recorded it
+    pub clock: Option<String>,
+    /// Position after the move
+    pub position: UciPosition,
+    /// Engine analysis of the position the move was played in
+    pub before: Analysis,
+    /// Engine analysis of the position after the move
+    pub analysis: Analysis,
+    /// Whether the move was in the opening book. Positions between two book
+    /// moves are not searched, so their analyses are empty.
+    pub book: bool,
+    /// Tablebase verdict on the position after the move, from the point of
+    /// view of the side that made it; `None` outside the tables
+    pub tablebase: Option<TablebaseProbe>,
+}
+
+/// What is consulted besides the engine.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lookups<'a> {
+    /// Moves found in the book are marked as book moves, and positions that
+    /// only book moves lead into and out of are not searched
+    pub book: Option<&'a PolyglotBook>,
+    /// Positions the tables cover get their verdict
+    pub tablebase: Option<&'a Tablebase>,
+}
+
+/// Analyses the position after every move of a game.
+///
+/// Tells the backend a new game starts, then searches the starting position and
+/// each position after it within `limits`, so every ply carries the analysis of
+/// the position before and after the move. Replay stops at the first move that
+/// cannot be resolved on the board.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::engine::analysis::Analysis;
+/// use analyzer::engine::backend::SearchLimits;
+/// use analyzer::engine::mock::MockBackend;
+/// use analyzer::game_analysis::analyse_game;
+/// use analyzer::pgn::Pgn;
+///
+/// let mut backend = MockBackend::new(vec![
+///     Analysis::from_uci_lines(["bestmove e2e4"]),
+///     Analysis::from_uci_lines(["bestmove e7e5"]),
+/// ]);
+///
+/// let mut pgn = Pgn::new("1. e4 e5".to_string());
+/// let plies = analyse_game(&mut backend, &mut pgn, &SearchLimits::depth(10)).unwrap();
+/// assert_eq!(plies.len(), 2);
+/// assert_eq!(plies[1].uci, "e7e5");
+/// assert_eq!(plies[0].before.best_move, "e2e4");
+/// assert_eq!(plies[0].analysis.best_move, "e7e5");
+/// ```
+pub fn analyse_game(
+    backend: &mut dyn EngineBackend,
+    pgn: &mut Pgn,
+    limits: &SearchLimits,
+) -> Result<Vec<PlyAnalysis>, ChessError> {
+    analyse_game_with_lookups(backend, pgn, limits, Lookups::default())
+}
+
+/// Like [`analyse_game`], but also consults an opening book and endgame
+/// tablebase, see [`Lookups`].
+pub fn analyse_game_with_lookups(
+    backend: &mut dyn EngineBackend,
+    pgn: &mut Pgn,
+    limits: &SearchLimits,
+    lookups: Lookups,
+) -> Result<Vec<PlyAnalysis>, ChessError> {
+    backend.set_chess960(pgn.is_chess960())?;
+    backend.new_game()?;
+    analyse_game_with(pgn, lookups, |position| backend.analyse(position, limits))
+}
+
+/// Replays a game and analyses the position after every move with `analyse`.
+///
+/// Shared by [`analyse_game`] and the engine pool, which wraps each search in
+/// its own retry logic.
+pub(crate) fn analyse_game_with<F>(
+    pgn: &mut Pgn,
+    lookups: Lookups,
+    mut analyse: F,
+) -> Result<Vec<PlyAnalysis>, ChessError>
+where
+    F: FnMut(&UciPosition) -> Result<Analysis, ChessError>,
+{
+    let positions = pgn.uci_positions();
+    let mut plies = Vec::with_capacity(positions.len());
+    let Some(last) = positions.last() else {
+        return Ok(plies);
+    };
+
+    // a game from a FEN tag starts there, and its first move need not be White's
+    let start = pgn.start_setup()?;
+    let (start_board, first_index) = (start.board(), start.ply_index());
+    let in_book = lookups
+        .book
+        .map(|book| book.book_plies(start_board, first_index, &last.moves))
+        .unwrap_or_default();
+    let verdicts = match lookups.tablebase {
+        Some(tablebase) => tablebase_verdicts(tablebase, start_board, first_index, &last.moves),
+        None => Vec::new(),
+    };
+    let is_book = |ply: usize| in_book.get(ply).copied().unwrap_or(false);
+    // the position after `plies` moves is needed unless book moves lead both into and out of it
+    let total = positions.len();
+    let mut analyse_after = |plies: usize, position: &UciPosition| {
+        let book_before = plies == 0 || is_book(plies - 1);
+        let book_after = plies == total || is_book(plies);
+        if book_before && book_after {
+            Ok(Analysis::default())
+        } else {
+            analyse(position)
+        }
+    };
+
+    let mut before = analyse_after(0, &pgn.start_position())?;
+    for (ply, position) in positions.into_iter().enumerate() {
+        let analysis = analyse_after(ply + 1, &position)?;
+        plies.push(PlyAnalysis {
+            ply: first_index as usize + ply,
+            san: pgn.moves[ply].san.clone(),
+            uci: position.moves[ply].clone(),
+            clock: pgn.clocks.get(ply).cloned().flatten(),
+            position,
+            before: std::mem::replace(&mut before, analysis.clone()),
+            analysis,
+            book: is_book(ply),
+            tablebase: verdicts.get(ply).copied().flatten(),
+        });
+    }
+    Ok(plies)
+}
+
+/// Replays UCI moves from `start`, the first at ply `first_index`, and probes
+/// the position after each, returning the verdicts from the point of view of
+/// the mover.
+///
+/// The tablebase only saves engine work, so a table that cannot be read is
+/// reported and its positions are left to the engine.
+fn tablebase_verdicts(tablebase: &Tablebase, start: &Board, first_index: u16, moves: &[String]) -> Vec<Option<TablebaseProbe>> {
+    let mut board = start.clone();
+    let mut verdicts = Vec::with_capacity(moves.len());
+    for (index, uci) in (first_index..).zip(moves) {
+        let Ok(played) = Move::validate_uci(&board, uci, index) else {
+            break;
+        };
+        board.apply_move(&played);
+        let side_to_move = played.color().get_opposite();
+        let verdict = tablebase.probe(&board, side_to_move).unwrap_or_else(|error| {
+            eprintln!("[Chess Analyzer] Warning: Tablebase probe after {} failed, using the engine: {}", uci, error);
+            None
+        });
+        verdicts.push(verdict.map(|probe| -probe));
+    }
+    verdicts
+}
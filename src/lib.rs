@@ -1,8 +1,12 @@
+pub mod attacks;
 pub mod bitboard;
+pub mod square;
 pub mod fen;
 pub mod pgn;
 pub mod pgn_header;
 pub mod board;
+pub mod setup;
+pub mod chess960;
 pub mod role;
 pub mod color;
 pub mod components;
@@ -11,7 +15,26 @@ pub mod r#move;
 pub mod error;
 pub mod constants;
 pub mod config;
+pub mod game_analysis;
+pub mod classification;
+pub mod summary;
+pub mod annotate;
+pub mod report;
+pub mod eco;
+pub mod polyglot;
+pub mod book_builder;
+pub mod syzygy;
+pub mod evaluation;
 
 pub mod engine {
+    pub mod analysis;
+    pub mod backend;
+    pub mod cache;
     pub mod engine;
+    pub mod mock;
+    pub mod options;
+    pub mod pool;
+    pub mod position;
+    pub mod search;
+    pub mod uci_server;
 }
@@ -1,7 +1,25 @@
 use analyzer::*;
-use analyzer::{board::Board, pgn::Pgn, engine::engine::Engine, constants::defaults, r#move::Move};
-use std::{fs, io::{self, Write}};
-use clap::{Parser, Subcommand};
+use analyzer::{board::Board, pgn::Pgn, engine::engine::Engine, config::EngineConfig, constants::defaults, r#move::Move};
+use analyzer::annotate::annotate_game;
+use analyzer::classification::{review_game, ClassificationThresholds, MoveReview};
+use analyzer::eco::{EcoTable, OpeningMatch};
+use analyzer::pgn_header::PgnHeaders;
+use analyzer::report::{ReportFormat, ReportWriter};
+use analyzer::summary::{GameSummary, PlayerTotals};
+use analyzer::engine::backend::SearchLimits;
+use analyzer::game_analysis::PlyAnalysis;
+use analyzer::engine::cache::AnalysisCache;
+use analyzer::engine::pool::EnginePool;
+use analyzer::engine::backend::EngineBackend;
+use analyzer::engine::search::{NativeEngine, DEFAULT_HASH_MB};
+use analyzer::engine::uci_server::UciServer;
+use analyzer::polyglot::PolyglotBook;
+use analyzer::syzygy::Tablebase;
+use analyzer::book_builder::{BookBuilder, BookFilter, DEFAULT_BOOK_PLIES};
+use analyzer::color::Color;
+use analyzer::evaluation::evaluate;
+use std::{fs, io::{self, Write}, sync::{Arc, Mutex}};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "analyzer")]
@@ -18,6 +36,29 @@ enum Commands {
         /// Path to PGN file
         #[arg(default_value_t = defaults::DEFAULT_PGN_PATH.to_string())]
         pgn_path: String,
+        #[command(flatten)]
+        engine: EngineArgs,
+        #[command(flatten)]
+        cache: CacheArgs,
+        #[command(flatten)]
+        thresholds: ThresholdArgs,
+        /// Also write the games with evaluations, error marks and engine lines to this PGN file
+        #[arg(long, value_name = "OUT_PGN")]
+        annotate: Option<String>,
+        /// Output format; json, ndjson and csv follow the schema of the report module
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        #[command(flatten)]
+        lookups: LookupArgs,
+    },
+    /// Show the built-in static evaluation of a position of a game, term by term
+    Eval {
+        /// Path to PGN file
+        #[arg(default_value_t = defaults::DEFAULT_PGN_PATH.to_string())]
+        pgn_path: String,
+        /// Evaluate the position after this many plies instead of the final one
+        #[arg(long)]
+        ply: Option<usize>,
     },
     /// Navigate through game interactively
     Navigate {
@@ -25,6 +66,258 @@ enum Commands {
         #[arg(default_value_t = defaults::DEFAULT_PGN_PATH.to_string())]
         pgn_path: String,
     },
+    /// Inspect the UCI engine
+    Engine {
+        #[command(subcommand)]
+        command: EngineCommands,
+    },
+    /// Inspect or invalidate the analysis cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+    /// Work with Polyglot opening books
+    Book {
+        #[command(subcommand)]
+        command: BookCommands,
+    },
+    /// Run the built-in search as a UCI engine on stdin and stdout
+    Uci,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Backend {
+    /// A UCI engine executable such as Stockfish
+    Uci,
+    /// The alpha-beta search built into the analyzer; needs no engine installed
+    Native,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable move list and summaries
+    Text,
+    /// One JSON document
+    Json,
+    /// One JSON record per line
+    Ndjson,
+    /// One CSV table
+    Csv,
+}
+
+impl OutputFormat {
+    fn report_format(self) -> Option<ReportFormat> {
+        match self {
+            OutputFormat::Text => None,
+            OutputFormat::Json => Some(ReportFormat::Json),
+            OutputFormat::Ndjson => Some(ReportFormat::Ndjson),
+            OutputFormat::Csv => Some(ReportFormat::Csv),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum EngineCommands {
+    /// Print the engine's name, author and supported options
+    Info {
+        #[command(flatten)]
+        engine: EngineArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheCommands {
+    /// Print the number of cached analyses
+    Stats {
+        /// Path to the cache file
+        #[arg(long, default_value_t = defaults::DEFAULT_CACHE_PATH.to_string())]
+        path: String,
+    },
+    /// Remove cached analyses
+    Clear {
+        /// Path to the cache file
+        #[arg(long, default_value_t = defaults::DEFAULT_CACHE_PATH.to_string())]
+        path: String,
+        /// Only remove analyses by this engine (name as reported by `engine info`)
+        #[arg(long)]
+        engine: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum BookCommands {
+    /// Build a Polyglot book from the games of a PGN file
+    Build {
+        /// Path to PGN file
+        pgn_path: String,
+        /// Path of the book to write
+        #[arg(long, short)]
+        output: String,
+        /// Plies of each game that go into the book
+        #[arg(long, default_value_t = DEFAULT_BOOK_PLIES)]
+        depth: usize,
+        /// Only games of this player, and only the moves they made
+        #[arg(long)]
+        player: Option<String>,
+        /// Only moves of this side
+        #[arg(long, value_enum)]
+        color: Option<Side>,
+        /// Leave out moves played in fewer games from their position
+        #[arg(long, default_value_t = 1)]
+        min_games: u32,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Side {
+    White,
+    Black,
+}
+
+impl From<Side> for Color {
+    fn from(side: Side) -> Color {
+        match side {
+            Side::White => Color::White,
+            Side::Black => Color::Black,
+        }
+    }
+}
+
+/// Options controlling the analysis cache.
+#[derive(Args)]
+struct CacheArgs {
+    /// Path to the analysis cache file
+    #[arg(long = "cache", default_value_t = defaults::DEFAULT_CACHE_PATH.to_string())]
+    cache_path: String,
+    /// Search every position, neither reading nor writing the cache
+    #[arg(long)]
+    no_cache: bool,
+}
+
+impl CacheArgs {
+    fn open(&self) -> Result<Option<Arc<Mutex<AnalysisCache>>>, String> {
+        if self.no_cache {
+            return Ok(None);
+        }
+        let cache = AnalysisCache::open(&self.cache_path).map_err(|e| e.to_string())?;
+        Ok(Some(Arc::new(Mutex::new(cache))))
+    }
+}
+
+/// Opening book and endgame tablebase consulted besides the engine.
+#[derive(Args)]
+struct LookupArgs {
+    /// Polyglot opening book; moves found in it are marked as book moves and not analysed
+    #[arg(long, value_name = "BOOK_BIN")]
+    book: Option<String>,
+    /// Directory of Syzygy tables (.rtbw/.rtbz); endgame moves are labelled with their verdict
+    #[arg(long, value_name = "DIR")]
+    syzygy: Option<String>,
+}
+
+impl LookupArgs {
+    /// Opens the book and tables and hands them to the pool.
+    fn apply(&self, mut pool: EnginePool) -> Result<EnginePool, String> {
+        if let Some(path) = &self.book {
+            let book = PolyglotBook::open(path).map_err(|e| e.to_string())?;
+            pool = pool.with_book(Arc::new(book));
+        }
+        if let Some(dir) = &self.syzygy {
+            let tablebase = Tablebase::open(dir).map_err(|e| e.to_string())?;
+            if tablebase.is_empty() {
+                eprintln!("[Chess Analyzer] Tablebase: no Syzygy tables found in '{}'", dir);
+            }
+            pool = pool.with_tablebase(Arc::new(tablebase));
+        }
+        Ok(pool)
+    }
+}
+
+/// Centipawn losses at which moves are classified as good moves, inaccuracies, mistakes and blunders.
+#[derive(Args)]
+struct ThresholdArgs {
+    /// Smallest centipawn loss counted as a good move rather than an excellent one
+    #[arg(long, default_value_t = ClassificationThresholds::default().good)]
+    good: i32,
+    /// Smallest centipawn loss counted as an inaccuracy
+    #[arg(long, default_value_t = ClassificationThresholds::default().inaccuracy)]
+    inaccuracy: i32,
+    /// Smallest centipawn loss counted as a mistake
+    #[arg(long, default_value_t = ClassificationThresholds::default().mistake)]
+    mistake: i32,
+    /// Smallest centipawn loss counted as a blunder
+    #[arg(long, default_value_t = ClassificationThresholds::default().blunder)]
+    blunder: i32,
+}
+
+impl ThresholdArgs {
+    fn to_thresholds(&self) -> ClassificationThresholds {
+        ClassificationThresholds {
+            good: self.good,
+            inaccuracy: self.inaccuracy,
+            mistake: self.mistake,
+            blunder: self.blunder,
+        }
+    }
+}
+
+/// Options selecting and configuring the analysis backend.
+#[derive(Args)]
+struct EngineArgs {
+    /// Backend that searches the positions
+    #[arg(long, value_enum, default_value_t = Backend::Uci)]
+    backend: Backend,
+    /// UCI engine executable (defaults to "stockfish" in PATH)
+    #[arg(long = "engine")]
+    engine_path: Option<String>,
+    /// Search depth per position
+    #[arg(long, default_value_t = defaults::DEFAULT_ENGINE_DEPTH)]
+    depth: u8,
+    /// Timeout for each engine response in milliseconds
+    #[arg(long)]
+    timeout_ms: Option<u32>,
+    /// Number of engines analysing games in parallel
+    #[arg(long, default_value_t = 1)]
+    engines: usize,
+    /// Search threads per engine
+    #[arg(long)]
+    threads: Option<u32>,
+    /// Hash table size per engine in megabytes
+    #[arg(long)]
+    hash: Option<u32>,
+}
+
+impl EngineArgs {
+    fn to_config(&self) -> EngineConfig {
+        EngineConfig {
+            depth: self.depth,
+            timeout_ms: self.timeout_ms,
+            engine_path: self.engine_path.clone(),
+            threads: self.threads,
+            hash_mb: self.hash,
+            ..Default::default()
+        }
+    }
+
+    fn limits(&self) -> SearchLimits {
+        SearchLimits::depth(self.depth)
+    }
+}
+
+/// Builds a pool of the backends selected on the command line.
+fn create_pool(args: &EngineArgs, cache: Option<Arc<Mutex<AnalysisCache>>>) -> EnginePool {
+    match (args.backend, cache) {
+        // the native search is quick to repeat, so it does not use the cache
+        (Backend::Native, _) => {
+            let hash_mb = args.hash.unwrap_or(DEFAULT_HASH_MB);
+            EnginePool::new(
+                move || Ok(Box::new(NativeEngine::with_hash_mb(hash_mb)) as Box<dyn EngineBackend + Send>),
+                args.engines,
+            )
+        }
+        (Backend::Uci, Some(cache)) => EnginePool::uci_with_cache(args.to_config(), args.engines, cache),
+        (Backend::Uci, None) => EnginePool::uci(args.to_config(), args.engines),
+    }
 }
 
 #[derive(Debug)]
@@ -51,8 +344,11 @@ fn main() {
     let cli = Cli::parse();
 
     match &cli.command {
-        Commands::Analyze { pgn_path } => {
-            if let Err(error) = analyze_game(pgn_path) {
+        Commands::Analyze { pgn_path, engine, cache, thresholds, annotate, format, lookups } => {
+            let thresholds = thresholds.to_thresholds();
+            if let Err(error) =
+                analyze_game(pgn_path, engine, cache, &thresholds, annotate.as_deref(), *format, lookups)
+            {
                 eprintln!("{}", error);
                 std::process::exit(1);
             }
@@ -63,23 +359,269 @@ fn main() {
                 std::process::exit(1);
             }
         }
+        Commands::Engine { command: EngineCommands::Info { engine } } if engine.backend == Backend::Native => {
+            println!("Engine: {}", NativeEngine::new().name());
+        }
+        Commands::Engine { command: EngineCommands::Info { engine } } => {
+            match Engine::with_config(&engine.to_config()) {
+                Ok(mut engine) => {
+                    print!("{}", engine.info());
+                    engine.quit();
+                }
+                Err(error) => {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Cache { command } => {
+            if let Err(error) = manage_cache(command) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        Commands::Book { command } => {
+            if let Err(error) = manage_book(command) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        Commands::Eval { pgn_path, ply } => {
+            if let Err(error) = evaluate_position(pgn_path, *ply) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        Commands::Uci => UciServer::new(io::stdout()).run(io::stdin().lock()),
+    }
+}
+
+/// Prints the static evaluation of the first game's position after `ply`
+/// plies, or after its last move.
+fn evaluate_position(pgn_path: &str, ply: Option<usize>) -> Result<(), String> {
+    let contents = fs::read_to_string(pgn_path)
+        .map_err(|e| format!("[Chess Analyzer] File error: Could not read file '{}': {}", pgn_path, e))?;
+    let pgn = Pgn::parse_all(contents)
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("[Chess Analyzer] File error: No game in '{}'", pgn_path))?;
+    let plies = ply.unwrap_or(pgn.moves.len()).min(pgn.moves.len());
+
+    let start = pgn.start_setup().map_err(|e| e.to_string())?;
+    let mut board = start.board().clone();
+    for (index, mov) in (start.ply_index()..).zip(pgn.moves.iter().take(plies)) {
+        let played = mov
+            .to_uci_on(&board)
+            .and_then(|uci| Move::from_uci(&board, &uci, index))
+            .map_err(|e| e.to_string())?;
+        board.apply_move(&played);
+    }
+    let side_to_move = if (start.ply_index() as usize + plies).is_multiple_of(2) { Color::White } else { Color::Black };
+    println!("{}", board);
+    println!("After {} plies, {} to move", plies, if side_to_move == Color::White { "White" } else { "Black" });
+    println!("{}", evaluate(&board));
+    Ok(())
+}
+
+fn manage_book(command: &BookCommands) -> Result<(), String> {
+    match command {
+        BookCommands::Build { pgn_path, output, depth, player, color, min_games } => {
+            let contents = fs::read_to_string(pgn_path)
+                .map_err(|e| format!("[Chess Analyzer] File error: Could not read file '{}': {}", pgn_path, e))?;
+            let mut builder = BookBuilder::new(BookFilter {
+                max_plies: *depth,
+                player: player.clone(),
+                color: color.map(Color::from),
+                min_games: *min_games,
+            });
+            let games = Pgn::parse_all(contents);
+            for game in &games {
+                builder.add_game(game);
+            }
+            let book = builder.build();
+            fs::write(output, book.to_bytes())
+                .map_err(|e| format!("[Chess Analyzer] File error: Could not write file '{}': {}", output, e))?;
+            println!(
+                "{}: {} entries from {} of {} games",
+                output,
+                book.len(),
+                builder.games(),
+                games.len()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn manage_cache(command: &CacheCommands) -> Result<(), String> {
+    match command {
+        CacheCommands::Stats { path } => {
+            let cache = AnalysisCache::open(path).map_err(|e| e.to_string())?;
+            println!("{}: {} cached analyses", path, cache.len());
+        }
+        CacheCommands::Clear { path, engine } => {
+            let mut cache = AnalysisCache::open(path).map_err(|e| e.to_string())?;
+            let removed = match engine {
+                Some(engine) => cache.remove_engine(engine).map_err(|e| e.to_string())?,
+                None => {
+                    let count = cache.len();
+                    cache.clear().map_err(|e| e.to_string())?;
+                    count
+                }
+            };
+            println!("{}: removed {} cached analyses", path, removed);
+        }
     }
+    Ok(())
 }
 
-fn analyze_game(pgn_path: &str) -> Result<(), String> {
+fn analyze_game(
+    pgn_path: &str,
+    engine_args: &EngineArgs,
+    cache_args: &CacheArgs,
+    thresholds: &ClassificationThresholds,
+    annotate_path: Option<&str>,
+    format: OutputFormat,
+    lookups: &LookupArgs,
+) -> Result<(), String> {
     let contents = fs::read_to_string(pgn_path)
         .map_err(|e| format!("[Chess Analyzer] File error: Could not read file '{}': {}", pgn_path, e))?;
-    let mut engine = Engine::new();
-    let fens = Pgn::parse(contents);
+    let cache = cache_args.open()?;
+    let pool = lookups.apply(create_pool(engine_args, cache.clone()))?;
+    let limits = engine_args.limits();
+    let games = Pgn::parse_all(contents);
+    let eco = EcoTable::builtin();
+    let openings: Vec<Option<OpeningMatch>> = games.iter().map(|game| eco.classify(game)).collect();
+    let mut headers: Vec<PgnHeaders> = games.iter().map(|game| game.headers.clone()).collect();
+    for (game_headers, opening) in headers.iter_mut().zip(&openings) {
+        if let Some(opening) = opening {
+            opening.set_tags(game_headers);
+        }
+    }
+    let mut annotated = Vec::new();
+
+    let total = games.len();
+    let mut failures = 0;
+    let mut totals = PlayerTotals::default();
+    let mut report = format
+        .report_format()
+        .map(|format| ReportWriter::new(io::stdout().lock(), format, *thresholds));
+    let mut write_error = None;
+    pool.analyse_games_with(games, &limits, |game_index, result| {
+        let game_headers = &headers[game_index];
+        let opening = openings[game_index].as_ref();
+        if let Some(report) = report.as_mut() {
+            let written = match &result {
+                Ok(plies) => report.write_game(game_headers, opening, Ok(plies)),
+                Err(error) => report.write_game(game_headers, opening, Err(error.to_string())),
+            };
+            if let Err(e) = written {
+                write_error.get_or_insert(e);
+            }
+        } else {
+            if game_index > 0 {
+                println!();
+            }
+            println!("Game {}", game_index + 1);
+            if let Some(opening) = opening {
+                print_opening(opening);
+            }
+        }
+
+        match result {
+            Ok(plies) => {
+                let (white, black) = (game_headers.white(), game_headers.black());
+                let summary = GameSummary::from_reviews(&review_game(&plies, thresholds));
+                if report.is_none() {
+                    print_game(game_headers, &plies, &summary, thresholds);
+                }
+                totals.add_game(white, black, &summary);
+
+                if annotate_path.is_some() {
+                    annotated.push(annotate_game(game_headers, &plies, thresholds));
+                }
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                failures += 1;
+            }
+        }
+    });
 
-    for (i, f) in fens.iter().enumerate() {
-        let best_move = engine.process_fen(&f);
-        println!("{}. Best move: {}", i + 1, best_move);
+    if let Some(path) = annotate_path {
+        fs::write(path, annotated.join("\n"))
+            .map_err(|e| format!("[Chess Analyzer] File error: Could not write file '{}': {}", path, e))?;
+    }
+    let write_error = match report {
+        Some(report) => write_error.map_or_else(|| report.finish(&totals).err(), Some),
+        None => None,
+    };
+    if let Some(e) = write_error {
+        return Err(format!("[Chess Analyzer] Output error: Could not write report: {}", e));
+    }
+    if format == OutputFormat::Text && total > 1 && !totals.is_empty() {
+        println!();
+        println!("Players");
+        for (name, stats) in totals.iter() {
+            println!("{} ({} games): {}", name, stats.games, stats);
+        }
+    }
+    if let Some(cache) = cache {
+        eprintln!("[Chess Analyzer] Cache: {}", cache.lock().expect("analysis cache poisoned").stats());
+    }
+    if failures > 0 {
+        return Err(format!("[Chess Analyzer] Engine error: Analysis failed for {} of {} games", failures, total));
     }
-    engine.quit();
     Ok(())
 }
 
+/// Prints the opening a game was classified as and where it left the book.
+fn print_opening(opening: &OpeningMatch) {
+    let name = format!("{} {}", opening.opening.eco, opening.opening.name);
+    match opening.out_of_book_move() {
+        Some(mov) => println!("Opening: {}, out of book at move {}", name, mov),
+        None => println!("Opening: {}", name),
+    }
+}
+
+/// Prints every move's classification and both players' summaries.
+fn print_game(headers: &PgnHeaders, plies: &[PlyAnalysis], summary: &GameSummary, thresholds: &ClassificationThresholds) {
+    // replays the game to write the engine's moves in SAN; raw UCI once that fails
+    let mut board = headers.start_setup().ok().map(|setup| setup.board().clone());
+    for ply in plies {
+        let index = ply.ply as u16;
+        let best_move = board
+            .as_ref()
+            .and_then(|board| Move::from_uci(board, &ply.before.best_move, index).ok())
+            .map_or_else(|| ply.before.best_move.clone(), |best| best.san);
+        let line = match MoveReview::from_ply(ply, thresholds) {
+            _ if ply.book => format!("{}. {} (book)", ply.ply + 1, ply.san),
+            Some(review) => format!(
+                "{}. {} ({}): {} -> {}, loss {} cp, best move: {}",
+                ply.ply + 1,
+                review.san,
+                review.class,
+                review.eval_before,
+                review.eval_after,
+                review.cp_loss,
+                best_move
+            ),
+            None => format!("{}. {}: best move: {}", ply.ply + 1, ply.san, best_move),
+        };
+        match ply.tablebase {
+            Some(probe) => println!("{}, tablebase: {}", line, probe),
+            None => println!("{}", line),
+        }
+        board = board.and_then(|mut board| {
+            let played = Move::from_uci(&board, &ply.uci, index).ok()?;
+            board.apply_move(&played);
+            Some(board)
+        });
+    }
+    println!("White ({}): {}", headers.white().unwrap_or(PlayerTotals::UNKNOWN_PLAYER), summary.white);
+    println!("Black ({}): {}", headers.black().unwrap_or(PlayerTotals::UNKNOWN_PLAYER), summary.black);
+}
+
 /// Generates and displays the current board state with position information.
 fn display_game_state(moves: &[Move], current_position: usize) {
     // Clear screen
@@ -167,6 +709,7 @@ fn navigate_game(pgn_path: &str) -> Result<(), String> {
     let mut pgn = Pgn{ 
         headers: pgn_header::PgnHeaders::new(), 
         moves: Vec::new(), 
+        clocks: Vec::new(),
         _move_counter: 0 
     };
     
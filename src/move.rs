@@ -1,4 +1,14 @@
-use crate::{components::{Piece,Rank, File}, utils::{file_rank_to_index, is_piece}, color::Color, constants::castling};
+use crate::{
+    attacks,
+    bitboard::Bitboard,
+    board::Board,
+    components::{ColoredPiece, Piece},
+    utils::is_piece,
+    color::Color,
+    constants::castling,
+    error::ChessError,
+    square::{File, Rank, Square},
+};
 
 /// Represents a chess move parsed from Standard Algebraic Notation (SAN).
 /// 
@@ -155,18 +165,354 @@ impl Move {
         (source, target)
     }
 
-    pub fn get_target_index(&self) -> Option<u8> {
-        let (file, rank) = &self.target;
-        if file.is_none() || rank.is_none() {
-            return None;
-        }
-        // Use the safe file_rank_to_index function, but fall back to None on error
-        // This maintains the existing API while adding safety
-        file_rank_to_index(file.unwrap(), rank.unwrap()).ok()
+    /// The square the piece moves to, once both its file and rank are known.
+    pub fn get_target_square(&self) -> Option<Square> {
+        Some(Square::from_file_rank(self.target.0?, self.target.1?))
+    }
+
+    /// The square the piece comes from, once both its file and rank are
+    /// known (SAN usually leaves them out).
+    pub fn get_source_square(&self) -> Option<Square> {
+        Some(Square::from_file_rank(self.source.0?, self.source.1?))
     }
     
     
 
+    /// Returns the move in UCI long algebraic notation (`e2e4`, `e7e8q`, `e1g1`).
+    ///
+    /// Castling is written as the king moving two squares from the standard
+    /// start squares; see [`Move::to_uci_on`] for Chess960. Other moves need
+    /// their source square resolved, which happens when the move is replayed on
+    /// a board (see [`Pgn::parse_moves`](crate::pgn::Pgn::parse_moves)); `None`
+    /// is returned while it is still unknown.
+    pub fn to_uci(&self) -> Option<String> {
+        if let Some(castling) = self.castling {
+            let ((king_source, king_target), _) = castling.compute_squares(self.color());
+            return Some(format!("{}{}", king_source, king_target));
+        }
+
+        let (Some(source_file), Some(source_rank)) = self.source else {
+            return None;
+        };
+        let (Some(target_file), Some(target_rank)) = self.target else {
+            return None;
+        };
+
+        let mut uci = String::with_capacity(5);
+        uci.push(source_file.to_char());
+        uci.push(source_rank.to_char());
+        uci.push(target_file.to_char());
+        uci.push(target_rank.to_char());
+        if let Some(promotion) = self.promotion {
+            uci.push(promotion.to_char(Color::Black));
+        }
+        Some(uci)
+    }
+
+    /// Parses a move in UCI long algebraic notation (`e2e4`, `e7e8q`) and
+    /// checks that it is legal on `board` for the side to move at `index`.
+    ///
+    /// Castling may be written as the king moving two squares (`e1g1`) or, as
+    /// in Chess960, as the king taking its own rook (`e1h1`); on a Chess960
+    /// board only the latter is castling. The returned move
+    /// has its source square resolved and its SAN filled in, so it can be
+    /// applied with [`Board::apply_move`] and compared with parsed PGN moves.
+    ///
+    /// # Errors
+    ///
+    /// [`ChessError::InvalidMove`] if `uci` is not a UCI move and
+    /// [`ChessError::IllegalMove`] if it cannot be played on `board`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use analyzer::board::Board;
+    /// use analyzer::r#move::Move;
+    ///
+    /// let board = Board::init();
+    /// let knight = Move::from_uci(&board, "g1f3", 0).unwrap();
+    /// assert_eq!(knight.san, "Nf3");
+    /// assert_eq!(knight.to_uci().as_deref(), Some("g1f3"));
+    ///
+    /// // pawns move forward only
+    /// assert!(Move::from_uci(&board, "e2d3", 0).is_err());
+    /// ```
+    pub fn from_uci(board: &Board, uci: &str, index: u16) -> Result<Move, ChessError> {
+        let mut mov = Self::validate_uci(board, uci, index)?;
+        mov.san = mov.to_san(board);
+        Ok(mov)
+    }
+
+    /// [`Move::from_uci`] without the SAN, which needs the legal moves of the
+    /// position and is left empty.
+    pub(crate) fn validate_uci(board: &Board, uci: &str, index: u16) -> Result<Move, ChessError> {
+        let invalid = || ChessError::InvalidMove { move_str: uci.to_string() };
+        let illegal = |reason: &str| ChessError::IllegalMove {
+            move_str: uci.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let chars: Vec<char> = uci.chars().collect();
+        if chars.len() != 4 && chars.len() != 5 {
+            return Err(invalid());
+        }
+        let source = (File::from_char(chars[0]).ok_or_else(invalid)?, Rank::from_char(chars[1]).ok_or_else(invalid)?);
+        let target = (File::from_char(chars[2]).ok_or_else(invalid)?, Rank::from_char(chars[3]).ok_or_else(invalid)?);
+        let promotion = match chars.get(4).map(|&c| Piece::from_char(c)) {
+            None => None,
+            Some(Some(piece)) if piece != Piece::Pawn && piece != Piece::King => Some(piece),
+            Some(_) => return Err(invalid()),
+        };
+        let source_square = Square::from_file_rank(source.0, source.1);
+        let target_square = Square::from_file_rank(target.0, target.1);
+
+        let color = if index.is_multiple_of(2) { Color::White } else { Color::Black };
+        let Some(ColoredPiece { color: owner, role: piece }) = board.piece_at(source_square) else {
+            return Err(illegal("no piece on the source square"));
+        };
+        if owner != color {
+            return Err(illegal("the piece on the source square belongs to the opponent"));
+        }
+
+        if piece == Piece::King {
+            if let Some(side) = Self::castling_from_squares(board, color, source_square, target_square) {
+                if promotion.is_some() {
+                    return Err(invalid());
+                }
+                Self::check_castling(board, color, side).map_err(illegal)?;
+                let mov = Move {
+                    san: String::new(),
+                    index,
+                    piece,
+                    is_capture: false,
+                    castling: Some(side),
+                    target: (None, None),
+                    source: (None, None),
+                    promotion: None,
+                };
+                // in Chess960 the rook may have shielded the king's target square
+                let mut after = board.clone();
+                after.apply_move(&mov);
+                if after.is_in_check(color) {
+                    return Err(illegal("it leaves the king in check"));
+                }
+                return Ok(mov);
+            }
+        }
+
+        if board.color_at(target_square) == Some(color) {
+            return Err(illegal("the target square holds a piece of the same color"));
+        }
+        let is_en_passant = piece == Piece::Pawn && board.en_passant == Some(target_square) && source.0 - target.0 == 1;
+        let is_capture = board.occupied.contains(target_square.index()) || is_en_passant;
+        if !Self::reaches(board, piece, color, source_square, target_square, is_capture) {
+            return Err(illegal("the piece cannot move there"));
+        }
+
+        let last_rank = if color == Color::White { Rank::Eighth } else { Rank::First };
+        let promotes = piece == Piece::Pawn && target.1 == last_rank;
+        if promotes && promotion.is_none() {
+            return Err(illegal("a pawn reaching the last rank must promote"));
+        }
+        if !promotes && promotion.is_some() {
+            return Err(illegal("only pawns reaching the last rank promote"));
+        }
+
+        let mov = Move {
+            san: String::new(),
+            index,
+            piece,
+            is_capture,
+            castling: None,
+            target: (Some(target.0), Some(target.1)),
+            source: (Some(source.0), Some(source.1)),
+            promotion,
+        };
+        let mut after = board.clone();
+        after.apply_move(&mov);
+        if after.is_in_check(color) {
+            return Err(illegal("it leaves the king in check"));
+        }
+        Ok(mov)
+    }
+
+    /// Recognises castling written as the king capturing its own castling
+    /// rook or, outside Chess960 where that could be a plain king move, as
+    /// the king moving two squares along its home rank.
+    fn castling_from_squares(board: &Board, color: Color, source: Square, target: Square) -> Option<Castling> {
+        let own_rook = board.piece_at(target) == Some(ColoredPiece::new(color, Piece::Rook));
+        [Castling::King, Castling::Queen].into_iter().find(|&side| {
+            let ((king_source, king_target), (rook_source, _)) = board.castling_squares(color, side);
+            source == king_source
+                && ((target == rook_source && own_rook) || (!board.chess960 && target == king_target))
+        })
+    }
+
+    /// Checks the castling right, that the squares king and rook pass and
+    /// land on are empty but for themselves and that the king neither is in
+    /// check nor passes an attacked square.
+    fn check_castling(board: &Board, color: Color, side: Castling) -> Result<(), &'static str> {
+        let right = side.right(color);
+        let ((king_source, king_target), (rook_source, rook_target)) = board.castling_squares(color, side);
+        let king_in_place = board.piece_at(king_source) == Some(ColoredPiece::new(color, Piece::King));
+        let rook_in_place = board.piece_at(rook_source) == Some(ColoredPiece::new(color, Piece::Rook));
+        if board.castling_rights & right == 0 || !king_in_place || !rook_in_place {
+            return Err("castling is no longer allowed on that side");
+        }
+
+        let paths = Bitboard::between(king_source.index(), king_target.index())
+            | king_target.bitboard()
+            | Bitboard::between(rook_source.index(), rook_target.index())
+            | rook_target.bitboard();
+        let others = board.occupied & !king_source.bitboard() & !rook_source.bitboard();
+        if !(paths & others).is_empty() {
+            return Err("pieces stand between king and rook");
+        }
+
+        let king_path = Bitboard::between(king_source.index(), king_target.index())
+            | king_source.bitboard()
+            | king_target.bitboard();
+        if king_path.squares().any(|square| board.is_attacked(Square::new_unchecked(square), color.get_opposite())) {
+            return Err("the king is in check or passes an attacked square");
+        }
+        Ok(())
+    }
+
+    /// Whether `piece` can go from `source` to `target` by the way it moves,
+    /// ignoring pins.
+    fn reaches(board: &Board, piece: Piece, color: Color, source: Square, target: Square, is_capture: bool) -> bool {
+        if piece != Piece::Pawn {
+            return attacks::piece(piece, color, target.index(), board.occupied).contains(source.index());
+        }
+
+        let forward: i8 = if color == Color::White { 1 } else { -1 };
+        let rank_step = target.rank() as i8 - source.rank() as i8;
+        if is_capture {
+            return rank_step == forward && source.file() - target.file() == 1;
+        }
+
+        let start_rank = if color == Color::White { Rank::Second } else { Rank::Seventh };
+        let skipped = source.offset(0, forward);
+        source.file() == target.file()
+            && !board.occupied.contains(target.index())
+            && (rank_step == forward
+                || (rank_step == 2 * forward
+                    && source.rank() == start_rank
+                    && skipped.is_some_and(|skipped| !board.occupied.contains(skipped.index()))))
+    }
+
+    /// Writes the move in SAN as played on `board`, the position before it.
+    ///
+    /// Pieces are disambiguated by file, then rank, then both, counting only
+    /// the other pieces that could legally go to the same square. The `+` or
+    /// `#` suffix comes from the position after the move. Moves whose source
+    /// square is unknown keep their current SAN.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use analyzer::board::Board;
+    /// use analyzer::r#move::Move;
+    ///
+    /// let mut board = Board::init();
+    /// for (index, uci) in ["f2f3", "e7e5", "g2g4"].iter().enumerate() {
+    ///     let mov = Move::from_uci(&board, uci, index as u16).unwrap();
+    ///     board.apply_move(&mov);
+    /// }
+    /// let mate = Move::from_uci(&board, "d8h4", 3).unwrap();
+    /// assert_eq!(mate.to_san(&board), "Qh4#");
+    /// ```
+    pub fn to_san(&self, board: &Board) -> String {
+        let mut san = match self.castling {
+            Some(Castling::King) => "O-O".to_string(),
+            Some(Castling::Queen) => "O-O-O".to_string(),
+            None => match self.describe(board) {
+                Some(san) => san,
+                None => return self.san.clone(),
+            },
+        };
+
+        let mut after = board.clone();
+        after.apply_move(self);
+        let opponent = self.color().get_opposite();
+        if after.is_in_check(opponent) {
+            san.push(if after.has_legal_moves(self.index + 1) { '+' } else { '#' });
+        }
+        san
+    }
+
+    /// SAN of a non-castling move without the check suffix.
+    fn describe(&self, board: &Board) -> Option<String> {
+        let (Some(source_file), Some(source_rank)) = self.source else {
+            return None;
+        };
+        let (Some(target_file), Some(target_rank)) = self.target else {
+            return None;
+        };
+
+        // pawns name their file when capturing; pieces only when another one could go there too
+        let mut disambiguation = String::new();
+        if self.piece == Piece::Pawn {
+            if self.is_capture {
+                disambiguation.push(source_file.to_char());
+            }
+        } else {
+            let rivals: Vec<Move> = board
+                .legal_moves_unnamed(self.index)
+                .into_iter()
+                .filter(|other| other.piece == self.piece && other.target == self.target && other.source != self.source)
+                .collect();
+            if !rivals.is_empty() {
+                let same_file = rivals.iter().any(|other| other.source.0 == Some(source_file));
+                let same_rank = rivals.iter().any(|other| other.source.1 == Some(source_rank));
+                if !same_file {
+                    disambiguation.push(source_file.to_char());
+                } else if !same_rank {
+                    disambiguation.push(source_rank.to_char());
+                } else {
+                    disambiguation.push(source_file.to_char());
+                    disambiguation.push(source_rank.to_char());
+                }
+            }
+        }
+
+        let mut san = String::new();
+        if self.piece != Piece::Pawn {
+            san.push(self.piece.to_char(Color::White));
+        }
+        san.push_str(&disambiguation);
+        if self.is_capture {
+            san.push('x');
+        }
+        san.push(target_file.to_char());
+        san.push(target_rank.to_char());
+        if let Some(promotion) = self.promotion {
+            san.push('=');
+            san.push(promotion.to_char(Color::White));
+        }
+        Some(san)
+    }
+
+    /// Returns the move in UCI notation as played on `board`, the position
+    /// before it. Unlike [`Move::to_uci`] this also works for moves parsed from
+    /// SAN whose source square has not been resolved yet, and writes castling
+    /// in Chess960 as the king taking its rook.
+    pub fn to_uci_on(&self, board: &Board) -> Result<String, ChessError> {
+        if let Some(castling) = self.castling {
+            return Ok(board.castling_uci(self.color(), castling));
+        }
+        if let Some(uci) = self.to_uci() {
+            return Ok(uci);
+        }
+        let source = board.get_source_square(self).map_err(|reason| ChessError::IllegalMove {
+            move_str: self.san.clone(),
+            reason,
+        })?;
+        let mut resolved = self.clone();
+        resolved.source = (Some(source.file()), Some(source.rank()));
+        resolved.to_uci().ok_or_else(|| ChessError::InvalidMove { move_str: self.san.clone() })
+    }
+
     pub fn color(&self)->Color{
         if &self.index%2==0{
             Color::White
@@ -211,7 +557,10 @@ impl Castling {
         }
     }
 
-    pub fn compute_squares(&self, color: Color) -> ((u8, u8), (u8, u8)) {
+    /// King and rook squares `((king_from, king_to), (rook_from, rook_to))` of
+    /// castling in standard chess; [`Board::castling_squares`] gives those of
+    /// the game on a board, Chess960 included.
+    pub fn compute_squares(&self, color: Color) -> ((Square, Square), (Square, Square)) {
         match (self, color) {
             (Castling::King, Color::White) => castling::WHITE_KINGSIDE,
             (Castling::Queen, Color::White) => castling::WHITE_QUEENSIDE,
@@ -220,6 +569,16 @@ impl Castling {
         }
     }
 
+    /// The bit of this castling right for `color` in `Board::castling_rights`.
+    pub fn right(&self, color: Color) -> u8 {
+        match (self, color) {
+            (Castling::King, Color::White) => 0b_1000,
+            (Castling::Queen, Color::White) => 0b_0100,
+            (Castling::King, Color::Black) => 0b_0010,
+            (Castling::Queen, Color::Black) => 0b_0001,
+        }
+    }
+
     
 
 }
@@ -1,8 +1,6 @@
 
 #![allow(dead_code)]
-use regex::Regex;
-
-use crate::{pgn_header::PgnHeaders, board::Board, r#move::Move, utils::{index_to_file_rank, get_header_regex}};
+use crate::{pgn_header::PgnHeaders, board::Board, r#move::Move, engine::position::UciPosition, error::ChessError, setup::Setup, utils::get_header_regex};
 
 /// Represents a chess game in Portable Game Notation (PGN) format.
 /// 
@@ -33,6 +31,7 @@ use crate::{pgn_header::PgnHeaders, board::Board, r#move::Move, utils::{index_to
 /// let mut pgn = Pgn {
 ///     headers: PgnHeaders::new(),
 ///     moves: Vec::new(),
+///     clocks: Vec::new(),
 ///     _move_counter: 0,
 /// };
 /// # let pgn_content = "1. e4 e5".to_string();
@@ -45,6 +44,8 @@ pub struct Pgn{
     pub headers: PgnHeaders,
     /// Sequence of moves in the game
     pub moves: Vec<Move>,
+    /// Clock time left after each move, from `[%clk]` comments
+    pub clocks: Vec<Option<String>>,
     /// Internal move counter for processing
     pub _move_counter: u16
 }
@@ -55,6 +56,7 @@ impl Pgn{
         let mut pgn = Pgn {
             headers: PgnHeaders::new(),
             moves: Vec::new(),
+            clocks: Vec::new(),
             _move_counter: 0,
         };
         pgn.extract_headers(contents.clone());
@@ -94,23 +96,19 @@ impl Pgn{
     }
     
     pub fn parse_moves(&mut self) -> Vec<String>{
-        let mut board = Board::init();
+        let mut board = match self.start_board() {
+            Ok(board) => board,
+            Err(error) => {
+                eprintln!("{}", error);
+                return Vec::new();
+            }
+        };
         let mut fens:Vec<String> =Vec::new();
         for mov in &mut self.moves{
             let source = board.apply_move(&mov);
 
-            if source.is_some() {
-                // Use the safe index_to_file_rank function
-                match index_to_file_rank(source.unwrap()) {
-                    Ok((file, rank)) => {
-                        mov.source = (Some(file), Some(rank));
-                    }
-                    Err(_) => {
-                        // Log error but continue processing - keeps existing behavior
-                        eprintln!("[Chess Analyzer] Warning: Invalid source square index {}", source.unwrap());
-                        mov.source = (None, None);
-                    }
-                }
+            if let Some(source) = source {
+                mov.source = (Some(source.file()), Some(source.rank()));
             }
 
 
@@ -123,6 +121,47 @@ impl Pgn{
 
     }
 
+    /// Whether the `Variant` tag names Chess960 (also written Fischerandom).
+    pub fn is_chess960(&self) -> bool {
+        self.headers.is_chess960()
+    }
+
+    /// The position the game starts from: the `FEN` tag if there is one,
+    /// read as Chess960 when the `Variant` tag says so, or else the standard
+    /// starting position.
+    ///
+    /// # Errors
+    ///
+    /// [`ChessError::InvalidFen`] if the `FEN` tag cannot be read and
+    /// [`ChessError::InvalidPosition`] if it holds an impossible position.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use analyzer::pgn::Pgn;
+    ///
+    /// let pgn = Pgn::new(String::from(
+    ///     "[Variant \"Chess960\"]\n[FEN \"nrbkqbrn/pppppppp/8/8/8/8/PPPPPPPP/NRBKQBRN w KQkq - 0 1\"]\n\n1. O-O-O",
+    /// ));
+    /// assert!(pgn.start_setup().unwrap().board().chess960);
+    /// ```
+    pub fn start_setup(&self) -> Result<Setup, ChessError> {
+        self.headers.start_setup()
+    }
+
+    /// The position the game starts from, as sent to a UCI engine.
+    pub fn start_position(&self) -> UciPosition {
+        match self.headers.tag("FEN") {
+            Some(fen) => UciPosition::from_fen(fen),
+            None => UciPosition::startpos(),
+        }
+    }
+
+    /// The board of [`Pgn::start_setup`].
+    pub fn start_board(&self) -> Result<Board, ChessError> {
+        self.start_setup().map(|setup| setup.board().clone())
+    }
+
     /// **Navigation Mode**: Extracts and parses PGN headers for interactive game replay.
     /// 
     /// This method is part of the navigation workflow. It parses PGN header tags
@@ -147,56 +186,122 @@ impl Pgn{
     /// Use this in combination with `extract_headers()` when you need to navigate
     /// through a game move by move in interactive mode.
     pub fn extract_moves(&mut self, contents:String){
-        let header_pattern = get_header_regex();
-        let move_list = header_pattern.replace_all(&contents, "");
-        let move_list = move_list.trim();
-        let move_list = move_list.replace("\n", "");
-        
-        let alt_moves_pattern = Regex::new(r"(\(.*\))").unwrap();
-        let move_list = alt_moves_pattern.replace_all(&move_list, "").to_string();    
-        for ( _,token) in move_list.split(" ").enumerate() {
-            if token.len()==0 { // has length
+        for token in tokenize_movetext(&contents) {
+            let token = match token {
+                MovetextToken::Word(word) => word,
+                MovetextToken::Comment(comment) => {
+                    if let (Some(clock), Some(last)) = (clock_from_comment(&comment), self.clocks.last_mut()) {
+                        *last = Some(clock);
+                    }
+                    continue;
+                }
+            };
+            let token = token.as_str();
+            if token == "1-0" || token == "0-1" || token == "1/2-1/2" || token == "0-0" || token == "*" { //end
                 continue;
             }
-            if token.get(token.len()-1..) == Some("."){ //is a counter
+            if token.starts_with('$') { // numeric annotation glyph
                 continue;
             }
-            if token == "1-0" || token == "0-1" || token =="0-0" || token=="*"{ //end
-                continue;        
+            // move numbers may be glued to the move that follows them ("12.e4", "12...Nf6")
+            let token = token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+            if token.is_empty() { //is a counter
+                continue;
             }
             self.moves.push(
                 Move::new(String::from(token), self._move_counter)
             );
+            self.clocks.push(None);
             self._move_counter+=1;
         }
     }
 
+    /// Splits the contents of a PGN file holding several games into one string per game.
+    ///
+    /// A new game starts at the first header line that follows movetext.
+    /// Files holding a single game are returned unchanged as a one-element vector.
+    pub fn split_games(contents: &str) -> Vec<String> {
+        let mut games = Vec::new();
+        let mut current = String::new();
+        let mut in_movetext = false;
+
+        for line in contents.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') && in_movetext {
+                games.push(std::mem::take(&mut current));
+                in_movetext = false;
+            } else if !trimmed.is_empty() && !trimmed.starts_with('[') {
+                in_movetext = true;
+            }
+            current.push_str(line);
+            current.push('\n');
+        }
+
+        if !current.trim().is_empty() {
+            games.push(current);
+        }
+        games
+    }
+
+    /// Parses every game of a (possibly multi-game) PGN file.
+    pub fn parse_all(contents: String) -> Vec<Pgn> {
+        Self::split_games(&contents)
+            .into_iter()
+            .map(Pgn::new)
+            .collect()
+    }
+
+    /// Replays the game and returns the position after each move as the
+    /// starting position plus the moves played so far, ready to be sent to a
+    /// UCI engine.
+    ///
+    /// Replay stops at the first move whose source square could not be resolved.
+    pub fn uci_positions(&mut self) -> Vec<UciPosition> {
+        let fens = self.parse_moves();
+        let Ok(start) = self.start_board() else {
+            return Vec::new();
+        };
+
+        let mut position = self.start_position();
+        let mut positions = Vec::with_capacity(self.moves.len());
+        for (mov, fen) in self.moves.iter().zip(&fens) {
+            // castling squares stay those of the start position all game
+            let uci = match mov.castling {
+                Some(castling) => Some(start.castling_uci(mov.color(), castling)),
+                None => mov.to_uci(),
+            };
+            match uci {
+                Some(uci) => {
+                    position.push(&uci);
+                    position.set_fen(fen);
+                }
+                None => {
+                    eprintln!("[Chess Analyzer] Warning: Could not convert move {}.{} to UCI, stopping replay", mov.index, mov.san);
+                    break;
+                }
+            }
+            positions.push(position.clone());
+        }
+        positions
+    }
+
     
     
     fn _extract_meta_from_line(&mut self, line:&str){
-        let mut key  = String::from("");
-        let mut val  = String::from("");
-        
-        let mut is_key_found = false;
-        for (_, c) in line.chars().enumerate() {
-            
-            if c!=' '{
-                if is_key_found {
-                    val.push(c) 
-                } else{
-                    key.push(c);    
-                } 
-                    
-            }else{
-                is_key_found = true;
-            }
+        // [Name "value"]: the value is quoted and may contain spaces and \" escapes
+        let (key, val) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        if key.starts_with('%') {
+            // a command embedded in a comment, such as [%eval 0.25] or [%clk 0:01:00]
+            return;
         }
-        
-        self.set_header(key, val.trim_matches('"').to_owned());
+        let val = val.trim().trim_matches('"').replace("\\\"", "\"").replace("\\\\", "\\");
+
+        self.set_header(key.to_string(), val);
     }
 
     fn set_header(&mut self, key:String, val:String){
-        
+        self.headers.set_tag(key.clone(), val.clone());
+
         match key.to_lowercase().as_str() {
             "event" => self.headers.set_event(val),
             "site" => self.headers.set_site(val),
@@ -204,10 +309,74 @@ impl Pgn{
             "white" => self.headers.set_white(val),
             "black" => self.headers.set_black(val),
             "result" => self.headers.set_result(val),
+            // moves are numbered from the position the game starts in
+            "fen" => {
+                if let Ok(setup) = Setup::from_fen(&val) {
+                    self._move_counter = setup.ply_index();
+                }
+            }
             &_ => ()
         }
     }
+}
+
+/// A main-line element of movetext.
+enum MovetextToken {
+    /// A move, move number, NAG or game result
+    Word(String),
+    /// The text of a brace or semicolon comment
+    Comment(String),
+}
+
+/// Splits movetext into main-line words and comments, skipping tag pairs and
+/// (possibly nested) variations.
+fn tokenize_movetext(movetext: &str) -> Vec<MovetextToken> {
+    fn flush(word: &mut String, tokens: &mut Vec<MovetextToken>) {
+        if !word.is_empty() {
+            tokens.push(MovetextToken::Word(std::mem::take(word)));
+        }
+    }
 
-    
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut variation_depth: usize = 0;
+    let mut chars = movetext.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | ';' => {
+                flush(&mut word, &mut tokens);
+                let end = if c == '{' { '}' } else { '\n' };
+                let comment: String = chars.by_ref().take_while(|&c| c != end).collect();
+                if variation_depth == 0 {
+                    tokens.push(MovetextToken::Comment(comment));
+                }
+            }
+            '[' if variation_depth == 0 => {
+                // tag pair
+                flush(&mut word, &mut tokens);
+                chars.by_ref().take_while(|&c| c != ']').for_each(drop);
+            }
+            '(' => {
+                flush(&mut word, &mut tokens);
+                variation_depth += 1;
+            }
+            ')' => {
+                flush(&mut word, &mut tokens);
+                variation_depth = variation_depth.saturating_sub(1);
+            }
+            c if c.is_whitespace() => flush(&mut word, &mut tokens),
+            c if variation_depth == 0 => word.push(c),
+            _ => {}
+        }
+    }
+    flush(&mut word, &mut tokens);
+    tokens
+}
 
+/// Extracts the clock time from a comment holding a `[%clk 0:01:59]` command.
+fn clock_from_comment(comment: &str) -> Option<String> {
+    let start = comment.find("[%clk")? + "[%clk".len();
+    let end = start + comment[start..].find(']')?;
+    Some(comment[start..end].trim().to_string())
 }
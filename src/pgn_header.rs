@@ -1,5 +1,10 @@
 #![allow(dead_code)]
+use crate::{board::Board, color::Color, error::ChessError, setup::Setup};
+
+#[derive(Debug, Clone, Default)]
 pub struct PgnHeaders {
+    /// Every tag pair of the game in file order, including the ones below
+    tags: Vec<(String, String)>,
     event: Option<String>,
     site: Option<String>,
     date: Option<String>,
@@ -11,6 +16,7 @@ pub struct PgnHeaders {
 impl PgnHeaders{
     pub fn new() -> PgnHeaders {
         PgnHeaders {
+            tags: Vec::new(),
             event: None,
             site: None,
             date: None,
@@ -44,4 +50,70 @@ impl PgnHeaders{
         self.result = Some(result);
     }
 
+    /// Sets a tag pair, replacing an existing tag of the same name (case-insensitive)
+    /// or appending a new one.
+    pub fn set_tag(&mut self, name: String, value: String) {
+        match self.tags.iter_mut().find(|(existing, _)| existing.eq_ignore_ascii_case(&name)) {
+            Some(tag) => tag.1 = value,
+            None => self.tags.push((name, value)),
+        }
+    }
+
+    /// Value of the tag with the given name (case-insensitive).
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// All tag pairs in file order.
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    pub fn event(&self) -> Option<&str> {
+        self.event.as_deref()
+    }
+
+    pub fn site(&self) -> Option<&str> {
+        self.site.as_deref()
+    }
+
+    pub fn date(&self) -> Option<&str> {
+        self.date.as_deref()
+    }
+
+    pub fn white(&self) -> Option<&str> {
+        self.white.as_deref()
+    }
+
+    pub fn black(&self) -> Option<&str> {
+        self.black.as_deref()
+    }
+
+    pub fn result(&self) -> Option<&str> {
+        self.result.as_deref()
+    }
+
+    /// Whether the `Variant` tag names Chess960 (also written Fischerandom).
+    pub fn is_chess960(&self) -> bool {
+        self.tag("Variant").is_some_and(|variant| {
+            let variant: String = variant.chars().filter(char::is_ascii_alphanumeric).collect();
+            ["chess960", "fischerandom", "fischerrandom", "960"].contains(&variant.to_lowercase().as_str())
+        })
+    }
+
+    /// The position the game starts from, see
+    /// [`Pgn::start_setup`](crate::pgn::Pgn::start_setup).
+    pub fn start_setup(&self) -> Result<Setup, ChessError> {
+        let setup = match self.tag("FEN") {
+            Some(fen) => Setup::from_fen(fen)?,
+            None => Setup::from_board(Board::init(), Color::White),
+        };
+        let setup = if self.is_chess960() { setup.with_chess960(true) } else { setup };
+        setup.validate()?;
+        Ok(setup)
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,525 @@
+//! Machine-readable analysis output.
+//!
+//! [`ReportWriter`] writes analysed games as JSON, NDJSON or CSV. All three
+//! formats carry the same records; field names and meanings below are stable
+//! within a [`SCHEMA_VERSION`].
+//!
+//! # Ply records
+//!
+//! One per analysed move:
+//!
+//! | field | type | meaning |
+//! |---|---|---|
+//! | `game` | integer | 1-based index of the game in the input file |
+//! | `ply` | integer | 1-based ply number (1 = White's first move) |
+//! | `move_number` | integer | full move number as written in the PGN |
+//! | `side` | string | `"white"` or `"black"`, the side that moved |
+//! | `san` | string | the move as written in the PGN |
+//! | `uci` | string | the move in UCI notation |
+//! | `fen` | string or null | position after the move |
+//! | `eval_before` | eval or null | evaluation before the move, mover's point of view |
+//! | `eval_after` | eval or null | evaluation after the move, mover's point of view |
+//! | `cp_loss` | integer or null | centipawns lost against the best move |
+//! | `best_move` | string | engine's best move before the move, UCI |
+//! | `pv` | array of strings | engine's best line before the move, UCI |
+//! | `classification` | string or null | `book`, `brilliant`, `best`, `excellent`, `good`, `inaccuracy`, `mistake` or `blunder` |
+//! | `wdl` | string or null | tablebase verdict after the move, mover's point of view: `win`, `cursed-win`, `draw`, `blessed-loss` or `loss` |
+//! | `dtz` | integer or null | tablebase distance to zeroing the 50-move counter after the move, in plies, mover's point of view |
+//! | `clock` | string or null | clock time left after the move, from `[%clk]` |
+//!
+//! An eval is `{"cp": 35}` or `{"mate": -3}`; mates count moves, negative
+//! when the mover gets mated. In CSV an eval is written as `35` or `#-3`.
+//! Book moves (see [`crate::polyglot`]) are classified `book` and have no
+//! evaluations or centipawn loss. `wdl` and `dtz` are only set for positions
+//! covered by the configured tablebase (see [`crate::syzygy`]).
+//!
+//! # Game records
+//!
+//! One per game: `game`, the `event`, `white`, `black` and `result` tags (null
+//! when missing), the `eco` code and `opening` name matched from the game's
+//! positions (see [`crate::eco`]), `out_of_book`, the `ply` of the first move
+//! after the game left the opening table (null while in book or without a
+//! match), `error` (null unless the analysis failed) and `white_stats` /
+//! `black_stats`. Stats records hold `games`, `moves`, `acpl`, `accuracy` (in
+//! percent) and a count per classification (`brilliant` … `blunder`).
+//!
+//! # Player records
+//!
+//! One per player name over all games: `name` plus the stats fields.
+//!
+//! # Layout per format
+//!
+//! * `json`: one document `{"schema_version", "games": [game record + "plies": [...]], "players": [...]}`.
+//! * `ndjson`: one object per line, tagged with `"type": "ply" | "game" | "player"`.
+//!   The ply lines of a game come right before its game line; player lines come last.
+//! * `csv`: one table with a `type` column; columns that do not apply to a row are empty.
+//!   Games get one row per side, with `side` set and the side's stats, or a
+//!   single row when the analysis failed.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{
+    classification::{ClassificationThresholds, MoveReview},
+    eco::OpeningMatch,
+    engine::analysis::Score,
+    game_analysis::PlyAnalysis,
+    pgn_header::PgnHeaders,
+    summary::{GameSummary, PlayerStats, PlayerTotals},
+};
+
+/// Version of the record layout described in the module documentation
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Classification of moves played from the opening book
+const BOOK_CLASSIFICATION: &str = "book";
+
+/// Columns of the CSV output, in order
+const CSV_COLUMNS: [&str; 37] = [
+    "type", "game", "ply", "move_number", "side", "san", "uci", "fen", "eval_before", "eval_after",
+    "cp_loss", "best_move", "pv", "classification", "wdl", "dtz", "clock", "name", "event", "white", "black",
+    "result", "eco", "opening", "out_of_book", "error", "games", "moves", "acpl", "accuracy", "brilliant", "best", "excellent",
+    "good", "inaccuracy", "mistake", "blunder",
+];
+
+/// Output format of [`ReportWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Ndjson,
+    Csv,
+}
+
+/// An evaluation as written in reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EvalRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mate: Option<i32>,
+}
+
+impl From<Score> for EvalRecord {
+    fn from(score: Score) -> Self {
+        match score {
+            Score::Centipawns(cp) => EvalRecord { cp: Some(cp), mate: None },
+            Score::Mate(moves) => EvalRecord { cp: None, mate: Some(moves) },
+        }
+    }
+}
+
+impl EvalRecord {
+    fn to_csv(self) -> String {
+        match (self.cp, self.mate) {
+            (Some(cp), _) => cp.to_string(),
+            (_, Some(moves)) => format!("#{}", moves),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Analysis of one move.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlyRecord {
+    pub game: usize,
+    pub ply: usize,
+    pub move_number: usize,
+    pub side: &'static str,
+    pub san: String,
+    pub uci: String,
+    pub fen: Option<String>,
+    pub eval_before: Option<EvalRecord>,
+    pub eval_after: Option<EvalRecord>,
+    pub cp_loss: Option<i32>,
+    pub best_move: String,
+    pub pv: Vec<String>,
+    pub classification: Option<&'static str>,
+    pub wdl: Option<&'static str>,
+    pub dtz: Option<i32>,
+    pub clock: Option<String>,
+}
+
+impl PlyRecord {
+    /// Builds the record of a ply of game number `game` (1-based).
+    pub fn new(game: usize, ply: &PlyAnalysis, thresholds: &ClassificationThresholds) -> PlyRecord {
+        let review = MoveReview::from_ply(ply, thresholds);
+        PlyRecord {
+            game,
+            ply: ply.ply + 1,
+            move_number: ply.ply / 2 + 1,
+            side: if ply.ply.is_multiple_of(2) { "white" } else { "black" },
+            san: ply.san.clone(),
+            uci: ply.uci.clone(),
+            fen: ply.position.fen.clone(),
+            eval_before: review.as_ref().map(|review| review.eval_before.into()),
+            eval_after: review.as_ref().map(|review| review.eval_after.into()),
+            cp_loss: review.as_ref().map(|review| review.cp_loss),
+            best_move: ply.before.best_move.clone(),
+            pv: ply.before.best_line().map(|line| line.pv.clone()).unwrap_or_default(),
+            classification: if ply.book {
+                Some(BOOK_CLASSIFICATION)
+            } else {
+                review.as_ref().map(|review| review.class.name())
+            },
+            wdl: ply.tablebase.map(|probe| probe.wdl.name()),
+            dtz: ply.tablebase.and_then(|probe| probe.dtz),
+            clock: ply.clock.clone(),
+        }
+    }
+}
+
+/// Statistics of a player, over one game or many.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatsRecord {
+    pub games: usize,
+    pub moves: usize,
+    pub acpl: f64,
+    pub accuracy: f64,
+    pub brilliant: usize,
+    pub best: usize,
+    pub excellent: usize,
+    pub good: usize,
+    pub inaccuracy: usize,
+    pub mistake: usize,
+    pub blunder: usize,
+}
+
+impl From<&PlayerStats> for StatsRecord {
+    fn from(stats: &PlayerStats) -> Self {
+        use crate::classification::MoveClass::*;
+        StatsRecord {
+            games: stats.games,
+            moves: stats.moves,
+            acpl: round2(stats.acpl()),
+            accuracy: round2(stats.accuracy()),
+            brilliant: stats.count(Brilliant),
+            best: stats.count(Best),
+            excellent: stats.count(Excellent),
+            good: stats.count(Good),
+            inaccuracy: stats.count(Inaccuracy),
+            mistake: stats.count(Mistake),
+            blunder: stats.count(Blunder),
+        }
+    }
+}
+
+/// Tags, outcome and per-side statistics of one game.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameRecord {
+    pub game: usize,
+    pub event: Option<String>,
+    pub white: Option<String>,
+    pub black: Option<String>,
+    pub result: Option<String>,
+    pub eco: Option<String>,
+    pub opening: Option<String>,
+    pub out_of_book: Option<usize>,
+    pub error: Option<String>,
+    pub white_stats: Option<StatsRecord>,
+    pub black_stats: Option<StatsRecord>,
+}
+
+impl GameRecord {
+    /// Builds the record of game number `game` (1-based).
+    pub fn new(
+        game: usize,
+        headers: &PgnHeaders,
+        opening: Option<&OpeningMatch>,
+        summary: Option<&GameSummary>,
+        error: Option<String>,
+    ) -> GameRecord {
+        GameRecord {
+            game,
+            event: headers.event().map(str::to_string),
+            white: headers.white().map(str::to_string),
+            black: headers.black().map(str::to_string),
+            result: headers.result().map(str::to_string),
+            eco: opening.map(|opening| opening.opening.eco.clone()),
+            opening: opening.map(|opening| opening.opening.name.clone()),
+            out_of_book: opening.and_then(|opening| opening.out_of_book).map(|ply| ply + 1),
+            error,
+            white_stats: summary.map(|summary| (&summary.white).into()),
+            black_stats: summary.map(|summary| (&summary.black).into()),
+        }
+    }
+}
+
+/// Statistics of one player over all games.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlayerRecord {
+    pub name: String,
+    #[serde(flatten)]
+    pub stats: StatsRecord,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum NdjsonRecord<'a> {
+    Ply(&'a PlyRecord),
+    Game(&'a GameRecord),
+    Player(&'a PlayerRecord),
+}
+
+#[derive(Serialize)]
+struct JsonGame {
+    #[serde(flatten)]
+    game: GameRecord,
+    plies: Vec<PlyRecord>,
+}
+
+#[derive(Serialize)]
+struct JsonDocument<'a> {
+    schema_version: u32,
+    games: &'a [JsonGame],
+    players: Vec<PlayerRecord>,
+}
+
+/// Writes analysed games in one of the [`ReportFormat`]s.
+///
+/// NDJSON and CSV are streamed game by game; JSON is written as one document
+/// by [`ReportWriter::finish`].
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::classification::ClassificationThresholds;
+/// use analyzer::engine::analysis::Analysis;
+/// use analyzer::engine::backend::SearchLimits;
+/// use analyzer::engine::mock::MockBackend;
+/// use analyzer::game_analysis::analyse_game;
+/// use analyzer::pgn::Pgn;
+/// use analyzer::report::{ReportFormat, ReportWriter};
+/// use analyzer::summary::PlayerTotals;
+///
+/// let mut backend = MockBackend::new(vec![Analysis::from_uci_lines([
+///     "info depth 10 score cp 20 pv e2e4",
+///     "bestmove e2e4",
+/// ])]);
+/// let mut pgn = Pgn::new("1. e4 *".to_string());
+/// let plies = analyse_game(&mut backend, &mut pgn, &SearchLimits::depth(10)).unwrap();
+///
+/// let mut output = Vec::new();
+/// let mut writer = ReportWriter::new(&mut output, ReportFormat::Ndjson, ClassificationThresholds::default());
+/// writer.write_game(&pgn.headers, None, Ok(&plies)).unwrap();
+/// writer.finish(&PlayerTotals::default()).unwrap();
+///
+/// let output = String::from_utf8(output).unwrap();
+/// assert!(output.starts_with(r#"{"type":"ply","game":1,"ply":1,"move_number":1,"side":"white","san":"e4""#));
+/// ```
+pub struct ReportWriter<W: Write> {
+    out: W,
+    format: ReportFormat,
+    thresholds: ClassificationThresholds,
+    games: Vec<JsonGame>,
+    wrote_header: bool,
+}
+
+impl<W: Write> ReportWriter<W> {
+    pub fn new(out: W, format: ReportFormat, thresholds: ClassificationThresholds) -> Self {
+        Self {
+            out,
+            format,
+            thresholds,
+            games: Vec::new(),
+            wrote_header: false,
+        }
+    }
+
+    /// Writes the next game: its opening, plies and summary, or the error that
+    /// stopped its analysis.
+    pub fn write_game(
+        &mut self,
+        headers: &PgnHeaders,
+        opening: Option<&OpeningMatch>,
+        result: Result<&[PlyAnalysis], String>,
+    ) -> io::Result<()> {
+        let number = self.games.len() + 1;
+        let (plies, game) = match result {
+            Ok(plies) => {
+                let reviews: Vec<MoveReview> = plies
+                    .iter()
+                    .filter_map(|ply| MoveReview::from_ply(ply, &self.thresholds))
+                    .collect();
+                let summary = GameSummary::from_reviews(&reviews);
+                let records = plies
+                    .iter()
+                    .map(|ply| PlyRecord::new(number, ply, &self.thresholds))
+                    .collect();
+                (records, GameRecord::new(number, headers, opening, Some(&summary), None))
+            }
+            Err(error) => (Vec::new(), GameRecord::new(number, headers, opening, None, Some(error))),
+        };
+
+        match self.format {
+            ReportFormat::Json => {}
+            ReportFormat::Ndjson => {
+                for ply in &plies {
+                    self.write_ndjson(&NdjsonRecord::Ply(ply))?;
+                }
+                self.write_ndjson(&NdjsonRecord::Game(&game))?;
+            }
+            ReportFormat::Csv => {
+                for ply in &plies {
+                    self.write_csv_row(&ply_row(ply))?;
+                }
+                for row in game_rows(&game) {
+                    self.write_csv_row(&row)?;
+                }
+            }
+        }
+        // JSON needs every game at the end; the other formats only the count
+        let plies = if self.format == ReportFormat::Json { plies } else { Vec::new() };
+        self.games.push(JsonGame { game, plies });
+        Ok(())
+    }
+
+    /// Writes the per-player totals and completes the output.
+    pub fn finish(mut self, totals: &PlayerTotals) -> io::Result<()> {
+        let players: Vec<PlayerRecord> = totals
+            .iter()
+            .map(|(name, stats)| PlayerRecord { name: name.to_string(), stats: stats.into() })
+            .collect();
+
+        match self.format {
+            ReportFormat::Json => {
+                let document = JsonDocument {
+                    schema_version: SCHEMA_VERSION,
+                    games: &self.games,
+                    players,
+                };
+                serde_json::to_writer_pretty(&mut self.out, &document)?;
+                writeln!(self.out)?;
+            }
+            ReportFormat::Ndjson => {
+                for player in &players {
+                    self.write_ndjson(&NdjsonRecord::Player(player))?;
+                }
+            }
+            ReportFormat::Csv => {
+                for player in &players {
+                    self.write_csv_row(&player_row(player))?;
+                }
+                // an empty report still gets its header
+                if !self.wrote_header {
+                    self.write_csv_row(&[])?;
+                }
+            }
+        }
+        self.out.flush()
+    }
+
+    fn write_ndjson(&mut self, record: &NdjsonRecord) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, record)?;
+        writeln!(self.out)
+    }
+
+    fn write_csv_row(&mut self, fields: &[(&str, String)]) -> io::Result<()> {
+        if !self.wrote_header {
+            writeln!(self.out, "{}", CSV_COLUMNS.join(","))?;
+            self.wrote_header = true;
+            if fields.is_empty() {
+                return Ok(());
+            }
+        }
+        let row: Vec<String> = CSV_COLUMNS
+            .iter()
+            .map(|column| {
+                fields
+                    .iter()
+                    .find(|(name, _)| name == column)
+                    .map(|(_, value)| csv_escape(value))
+                    .unwrap_or_default()
+            })
+            .collect();
+        writeln!(self.out, "{}", row.join(","))
+    }
+}
+
+fn ply_row(ply: &PlyRecord) -> Vec<(&'static str, String)> {
+    vec![
+        ("type", "ply".to_string()),
+        ("game", ply.game.to_string()),
+        ("ply", ply.ply.to_string()),
+        ("move_number", ply.move_number.to_string()),
+        ("side", ply.side.to_string()),
+        ("san", ply.san.clone()),
+        ("uci", ply.uci.clone()),
+        ("fen", ply.fen.clone().unwrap_or_default()),
+        ("eval_before", ply.eval_before.map(EvalRecord::to_csv).unwrap_or_default()),
+        ("eval_after", ply.eval_after.map(EvalRecord::to_csv).unwrap_or_default()),
+        ("cp_loss", ply.cp_loss.map(|loss| loss.to_string()).unwrap_or_default()),
+        ("best_move", ply.best_move.clone()),
+        ("pv", ply.pv.join(" ")),
+        ("classification", ply.classification.unwrap_or_default().to_string()),
+        ("wdl", ply.wdl.unwrap_or_default().to_string()),
+        ("dtz", ply.dtz.map(|dtz| dtz.to_string()).unwrap_or_default()),
+        ("clock", ply.clock.clone().unwrap_or_default()),
+    ]
+}
+
+fn game_rows(game: &GameRecord) -> Vec<Vec<(&'static str, String)>> {
+    let row = vec![
+        ("type", "game".to_string()),
+        ("game", game.game.to_string()),
+        ("event", game.event.clone().unwrap_or_default()),
+        ("white", game.white.clone().unwrap_or_default()),
+        ("black", game.black.clone().unwrap_or_default()),
+        ("result", game.result.clone().unwrap_or_default()),
+        ("eco", game.eco.clone().unwrap_or_default()),
+        ("opening", game.opening.clone().unwrap_or_default()),
+        ("out_of_book", game.out_of_book.map(|ply| ply.to_string()).unwrap_or_default()),
+        ("error", game.error.clone().unwrap_or_default()),
+    ];
+    let sides: Vec<(&str, &StatsRecord)> = [("white", &game.white_stats), ("black", &game.black_stats)]
+        .into_iter()
+        .filter_map(|(side, stats)| stats.as_ref().map(|stats| (side, stats)))
+        .collect();
+    if sides.is_empty() {
+        return vec![row];
+    }
+    sides
+        .into_iter()
+        .map(|(side, stats)| {
+            let mut row = row.clone();
+            row.push(("side", side.to_string()));
+            row.extend(stats_fields(stats));
+            row
+        })
+        .collect()
+}
+
+fn player_row(player: &PlayerRecord) -> Vec<(&'static str, String)> {
+    let mut row = vec![("type", "player".to_string()), ("name", player.name.clone())];
+    row.extend(stats_fields(&player.stats));
+    row
+}
+
+fn stats_fields(stats: &StatsRecord) -> Vec<(&'static str, String)> {
+    vec![
+        ("games", stats.games.to_string()),
+        ("moves", stats.moves.to_string()),
+        ("acpl", stats.acpl.to_string()),
+        ("accuracy", stats.accuracy.to_string()),
+        ("brilliant", stats.brilliant.to_string()),
+        ("best", stats.best.to_string()),
+        ("excellent", stats.excellent.to_string()),
+        ("good", stats.good.to_string()),
+        ("inaccuracy", stats.inaccuracy.to_string()),
+        ("mistake", stats.mistake.to_string()),
+        ("blunder", stats.blunder.to_string()),
+    ]
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
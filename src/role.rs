@@ -22,6 +22,17 @@ impl ByPiece {
         }
     }
 
+    pub fn empty()->ByPiece{
+        ByPiece {
+            pawn: Bitboard::EMPTY,
+            knight: Bitboard::EMPTY,
+            bishop: Bitboard::EMPTY,
+            rook: Bitboard::EMPTY,
+            queen: Bitboard::EMPTY,
+            king: Bitboard::EMPTY,
+        }
+    }
+
     pub fn get(&self, piece:Piece) ->Bitboard{
         match piece {
             Piece::Pawn => self.pawn,
@@ -0,0 +1,418 @@
+//! Setting up positions piece by piece.
+//!
+//! A [`Setup`] starts from an empty board (or any board) and takes pieces,
+//! the side to move, castling rights and an en passant square, for puzzles
+//! and study positions that do not come from a game. [`Setup::validate`]
+//! checks the result could arise in a game and reports every problem it
+//! finds at once, so a position editor can show them all.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use analyzer::color::Color;
+//! use analyzer::components::{ColoredPiece, Piece};
+//! use analyzer::error::ChessError;
+//! use analyzer::r#move::Castling;
+//! use analyzer::setup::Setup;
+//! use analyzer::square::Square;
+//!
+//! let setup = Setup::empty()
+//!     .put(Square::E1, ColoredPiece::new(Color::White, Piece::King))
+//!     .put(Square::H1, ColoredPiece::new(Color::White, Piece::Rook))
+//!     .put(Square::E8, ColoredPiece::new(Color::Black, Piece::King))
+//!     .with_castling(Color::White, Castling::King)
+//!     .with_side_to_move(Color::Black);
+//! assert_eq!(setup.fen(), "4k3/8/8/8/8/8/8/4K2R b K - 0 1");
+//! assert!(setup.validate().is_ok());
+//!
+//! // Black in check with White to move, and a castling right without its rook
+//! let broken = setup
+//!     .with_side_to_move(Color::White)
+//!     .put(Square::E2, ColoredPiece::new(Color::White, Piece::Rook))
+//!     .remove(Square::H1);
+//! match broken.validate() {
+//!     Err(ChessError::InvalidPosition { problems }) => assert_eq!(problems.len(), 2),
+//!     _ => panic!("expected two problems"),
+//! }
+//! ```
+
+use crate::{
+    bitboard::Bitboard,
+    board::Board,
+    chess960::{back_rank, CastlingFiles},
+    color::Color,
+    components::{ColoredPiece, Piece},
+    error::ChessError,
+    fen::{extract_castling_rights, piece_placement},
+    r#move::Castling,
+    square::{File, Rank, Square},
+};
+
+/// Most pieces a side can have, the sixteen it starts with
+const MAX_PIECES: u32 = 16;
+
+/// Most pawns a side can have
+const MAX_PAWNS: u32 = 8;
+
+/// A position being set up: a board and the side to move.
+#[derive(Clone)]
+pub struct Setup {
+    board: Board,
+    side_to_move: Color,
+}
+
+impl Setup {
+    /// An empty board with White to move and no castling rights.
+    pub fn empty() -> Self {
+        Setup::from_board(Board::empty(), Color::White)
+    }
+
+    /// Reads a position in Forsyth-Edwards Notation. The castling field may
+    /// use `KQkq`, Shredder-FEN rook files (`HAha`) or a mix as in X-FEN;
+    /// rook files or a king off the e-file mark the game as Chess960. The
+    /// move counters may be left out.
+    ///
+    /// The position is not validated, see [`Setup::validate`].
+    ///
+    /// # Errors
+    ///
+    /// [`ChessError::InvalidFen`] if a field cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use analyzer::color::Color;
+    /// use analyzer::setup::Setup;
+    ///
+    /// let setup = Setup::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9").unwrap();
+    /// assert!(setup.board().chess960);
+    /// assert_eq!(setup.side_to_move(), Color::White);
+    /// assert_eq!(setup.fen(), "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9");
+    /// assert!(setup.validate().is_ok());
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Self, ChessError> {
+        let invalid = |reason: &str| ChessError::InvalidFen { fen: fen.to_string(), reason: reason.to_string() };
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let (placement, side, castling, en_passant) = match fields[..] {
+            [placement, side, castling, en_passant, ..] if fields.len() <= 6 => (placement, side, castling, en_passant),
+            _ => return Err(invalid("expected four to six fields")),
+        };
+
+        let mut setup = Setup::empty();
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(invalid("piece placement needs eight ranks"));
+        }
+        for (rank, row) in Rank::ALL.into_iter().rev().zip(ranks) {
+            let mut file = 0;
+            for ch in row.chars() {
+                if let Some(empty) = ch.to_digit(10).filter(|empty| (1..=8).contains(empty)) {
+                    file += empty as u8;
+                    continue;
+                }
+                let piece = ColoredPiece::from_fen_char(ch).ok_or_else(|| invalid("unknown piece letter"))?;
+                let file_of = File::new(file).ok_or_else(|| invalid("a rank holds more than eight squares"))?;
+                setup = setup.put(Square::from_file_rank(file_of, rank), piece);
+                file += 1;
+            }
+            if file != 8 {
+                return Err(invalid("a rank does not hold eight squares"));
+            }
+        }
+
+        setup.side_to_move = match side {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(invalid("side to move must be w or b")),
+        };
+        setup = setup.read_castling(castling).map_err(invalid)?;
+        if en_passant != "-" {
+            let square = en_passant.parse().map_err(|_| invalid("bad en passant square"))?;
+            setup = setup.with_en_passant(Some(square));
+        }
+        let half_move_count = fields.get(4).map_or(Ok(0), |count| count.parse()).map_err(|_| invalid("bad half-move clock"))?;
+        let full_move_count = fields.get(5).map_or(Ok(1), |count| count.parse()).map_err(|_| invalid("bad full move number"))?;
+        Ok(setup.with_move_counters(half_move_count, full_move_count))
+    }
+
+    /// Takes the castling rights and the files they castle from out of a
+    /// FEN castling field.
+    fn read_castling(mut self, field: &str) -> Result<Self, &'static str> {
+        if field == "-" {
+            return Ok(self);
+        }
+        let mut files = CastlingFiles::STANDARD;
+        let mut chess960 = false;
+        for ch in field.chars() {
+            let color = if ch.is_ascii_uppercase() { Color::White } else { Color::Black };
+            let rank = back_rank(color);
+            let king = File::ALL
+                .into_iter()
+                .find(|&file| self.board.piece_at(Square::from_file_rank(file, rank)) == Some(ColoredPiece::new(color, Piece::King)));
+            let rooks: Vec<File> = File::ALL
+                .into_iter()
+                .filter(|&file| self.board.piece_at(Square::from_file_rank(file, rank)) == Some(ColoredPiece::new(color, Piece::Rook)))
+                .collect();
+            let (side, rook) = match (ch.to_ascii_lowercase(), king) {
+                // X-FEN: the outermost rook on that side, where one stands
+                ('k', _) => (Castling::King, king.and_then(|king| rooks.iter().copied().rfind(|&rook| rook > king))),
+                ('q', _) => (Castling::Queen, king.and_then(|king| rooks.iter().copied().find(|&rook| rook < king))),
+                (letter @ 'a'..='h', Some(king)) => {
+                    let rook = File::from_char(letter).expect("a file letter");
+                    let side = if rook > king { Castling::King } else { Castling::Queen };
+                    chess960 = true;
+                    (side, Some(rook))
+                }
+                ('a'..='h', None) => return Err("castling right named by file without a king on the back rank"),
+                _ => return Err("unknown castling letter"),
+            };
+            if let Some(king) = king {
+                files.king = king;
+            }
+            match (side, rook) {
+                (Castling::King, Some(rook)) => files.king_rook = rook,
+                (Castling::Queen, Some(rook)) => files.queen_rook = rook,
+                // left for validation to report
+                (_, None) => {}
+            }
+            self.board.castling_rights |= side.right(color);
+        }
+        chess960 |= files != CastlingFiles::STANDARD;
+        Ok(self.with_castling_files(files).with_chess960(chess960))
+    }
+
+    /// Starts from `board` with `side_to_move` to play.
+    pub fn from_board(board: Board, side_to_move: Color) -> Self {
+        Setup { board, side_to_move }
+    }
+
+    /// Puts `piece` on `square`, replacing what stood there.
+    pub fn put(mut self, square: Square, piece: ColoredPiece) -> Self {
+        self.board.put_piece(square, piece);
+        self
+    }
+
+    /// Empties `square`.
+    pub fn remove(mut self, square: Square) -> Self {
+        self.board.remove_piece(square);
+        self
+    }
+
+    pub fn with_side_to_move(mut self, color: Color) -> Self {
+        self.side_to_move = color;
+        self
+    }
+
+    /// Grants `color` the right to castle on `side`.
+    pub fn with_castling(mut self, color: Color, side: Castling) -> Self {
+        self.board.castling_rights |= side.right(color);
+        self
+    }
+
+    /// Withdraws every castling right.
+    pub fn without_castling(mut self) -> Self {
+        self.board.castling_rights = 0;
+        self
+    }
+
+    /// Sets the files king and rooks castle from, for Chess960.
+    pub fn with_castling_files(mut self, files: CastlingFiles) -> Self {
+        self.board.castling_files = files;
+        self
+    }
+
+    /// Marks the game as Chess960, which changes how castling is written in
+    /// UCI and FEN.
+    pub fn with_chess960(mut self, chess960: bool) -> Self {
+        self.board.chess960 = chess960;
+        self
+    }
+
+    /// Sets the square a pawn skipped with a double step on the last move.
+    pub fn with_en_passant(mut self, square: Option<Square>) -> Self {
+        self.board.en_passant = square;
+        self
+    }
+
+    /// Sets the half-move clock for the fifty-move rule and the full move number.
+    pub fn with_move_counters(mut self, half_move_count: u8, full_move_count: u16) -> Self {
+        self.board.half_move_count = half_move_count;
+        self.board.full_move_count = full_move_count;
+        self
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn side_to_move(&self) -> Color {
+        self.side_to_move
+    }
+
+    /// Ply index of the next move, whose parity gives the side to move as in
+    /// [`Move::new`](crate::r#move::Move::new).
+    pub fn ply_index(&self) -> u16 {
+        2 * self.board.full_move_count.saturating_sub(1) + u16::from(self.side_to_move == Color::Black)
+    }
+
+    /// The position in Forsyth-Edwards Notation.
+    pub fn fen(&self) -> String {
+        let en_passant = self.board.en_passant.map_or_else(|| String::from("-"), |square| square.to_string());
+        format!(
+            "{} {} {} {} {} {}",
+            piece_placement(&self.board),
+            self.side_to_move,
+            extract_castling_rights(&self.board),
+            en_passant,
+            self.board.half_move_count,
+            self.board.full_move_count
+        )
+    }
+
+    /// Checks the position could arise in a game.
+    ///
+    /// # Errors
+    ///
+    /// [`ChessError::InvalidPosition`] listing every problem: a side without
+    /// exactly one king, pawns on the first or last rank, more pieces or
+    /// pawns than a side starts with, the side not to move in check,
+    /// castling rights without king and rook on their squares, or an en
+    /// passant square no double step can have left.
+    pub fn validate(&self) -> Result<(), ChessError> {
+        let mut problems = Vec::new();
+        for color in [Color::White, Color::Black] {
+            self.check_material(color, &mut problems);
+            self.check_castling(color, &mut problems);
+        }
+        self.check_pawn_ranks(&mut problems);
+        self.check_opponent_in_check(&mut problems);
+        self.check_en_passant(&mut problems);
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ChessError::InvalidPosition { problems })
+        }
+    }
+
+    /// Validates the position and returns its board.
+    ///
+    /// # Errors
+    ///
+    /// See [`Setup::validate`].
+    pub fn build(self) -> Result<Board, ChessError> {
+        self.validate()?;
+        Ok(self.board)
+    }
+
+    fn count(&self, color: Color, role: Piece) -> u32 {
+        (self.board.by_piece.get(role) & self.board.by_color.get(color)).popcount()
+    }
+
+    fn check_material(&self, color: Color, problems: &mut Vec<String>) {
+        let kings = self.count(color, Piece::King);
+        if kings != 1 {
+            problems.push(format!("{} has {} kings", color_name(color), kings));
+        }
+
+        let pieces = self.board.by_color.get(color).popcount();
+        if pieces > MAX_PIECES {
+            problems.push(format!("{} has {} pieces", color_name(color), pieces));
+        }
+        let pawns = self.count(color, Piece::Pawn);
+        if pawns > MAX_PAWNS {
+            problems.push(format!("{} has {} pawns", color_name(color), pawns));
+        }
+
+        // pieces beyond the starting set must have been pawns once
+        let promoted: u32 = [(Piece::Knight, 2), (Piece::Bishop, 2), (Piece::Rook, 2), (Piece::Queen, 1)]
+            .into_iter()
+            .map(|(role, start)| self.count(color, role).saturating_sub(start))
+            .sum();
+        if pawns <= MAX_PAWNS && promoted > MAX_PAWNS - pawns {
+            problems.push(format!(
+                "{} has {} promoted pieces but only {} missing pawns",
+                color_name(color),
+                promoted,
+                MAX_PAWNS - pawns
+            ));
+        }
+    }
+
+    fn check_pawn_ranks(&self, problems: &mut Vec<String>) {
+        let back_ranks = Bitboard::rank(Rank::First) | Bitboard::rank(Rank::Eighth);
+        for index in self.board.by_piece.pawn & back_ranks {
+            problems.push(format!("pawn on {}", Square::new_unchecked(index)));
+        }
+    }
+
+    fn check_opponent_in_check(&self, problems: &mut Vec<String>) {
+        let waiting = self.side_to_move.get_opposite();
+        let Some(king) = self.board.king_square(waiting) else {
+            return;
+        };
+        if self.board.is_attacked(king, self.side_to_move) {
+            problems.push(format!(
+                "{} is in check with {} to move",
+                color_name(waiting),
+                color_name(self.side_to_move)
+            ));
+        }
+    }
+
+    fn check_castling(&self, color: Color, problems: &mut Vec<String>) {
+        for (side, name) in [(Castling::King, "kingside"), (Castling::Queen, "queenside")] {
+            if self.board.castling_rights & side.right(color) == 0 {
+                continue;
+            }
+            let ((king, _), (rook, _)) = self.board.castling_squares(color, side);
+            let in_place = self.board.piece_at(king) == Some(ColoredPiece::new(color, Piece::King))
+                && self.board.piece_at(rook) == Some(ColoredPiece::new(color, Piece::Rook));
+            if !in_place {
+                problems.push(format!(
+                    "{} may castle {} without king on {} and rook on {}",
+                    color_name(color),
+                    name,
+                    king,
+                    rook
+                ));
+            }
+        }
+    }
+
+    fn check_en_passant(&self, problems: &mut Vec<String>) {
+        let Some(square) = self.board.en_passant else {
+            return;
+        };
+        // the pawn went from `origin` over `square` to `pushed`
+        let (rank, forward) = match self.side_to_move {
+            Color::White => (Rank::Sixth, 1),
+            Color::Black => (Rank::Third, -1),
+        };
+        if square.rank() != rank {
+            problems.push(format!(
+                "en passant square {} is not on the {} rank",
+                square,
+                if rank == Rank::Sixth { "sixth" } else { "third" }
+            ));
+            return;
+        }
+        let (Some(pushed), Some(origin)) = (square.offset(0, -forward), square.offset(0, forward)) else {
+            return;
+        };
+        let mover = self.side_to_move.get_opposite();
+        if self.board.piece_at(pushed) != Some(ColoredPiece::new(mover, Piece::Pawn)) {
+            problems.push(format!("en passant square {} without a {} pawn on {}", square, color_name(mover), pushed));
+        }
+        if self.board.piece_at(square).is_some() || self.board.piece_at(origin).is_some() {
+            problems.push(format!("en passant square {} with {} or {} occupied", square, square, origin));
+        }
+    }
+}
+
+fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "White",
+        Color::Black => "Black",
+    }
+}
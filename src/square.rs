@@ -0,0 +1,378 @@
+//! Board coordinates.
+//!
+//! [`Square`] names one of the 64 squares, [`File`] and [`Rank`] its column
+//! and row. Squares are numbered rank by rank from White's side, a1 = 0,
+//! b1 = 1, ..., h8 = 63, the numbering [`Bitboard`] bits follow. A square is
+//! always on the board: constructing one from an index, coordinates or text
+//! checks it once, so code receiving a `Square` need not.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use analyzer::square::{File, Rank, Square};
+//!
+//! let e4: Square = "e4".parse().unwrap();
+//! assert_eq!(e4, Square::E4);
+//! assert_eq!((e4.file(), e4.rank()), (File::E, Rank::Fourth));
+//! assert_eq!(e4.index(), 28);
+//! assert_eq!(e4.offset(1, 2), Some(Square::F6));
+//! assert_eq!(Square::H8.offset(1, 0), None);
+//! assert_eq!(e4.flip_vertical(), Square::E5);
+//! assert_eq!(e4.distance(Square::A1), 4);
+//! assert_eq!(Square::all().count(), 64);
+//! ```
+
+use std::{fmt, ops::Sub, str::FromStr};
+
+use crate::{bitboard::Bitboard, error::ChessError};
+
+/// One of the 64 squares of the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square(u8);
+
+macro_rules! define_squares {
+    ($($name:ident),*) => {
+        impl Square {
+            define_squares!(@consts 0u8, $($name),*);
+        }
+    };
+    (@consts $index:expr, $name:ident $(, $rest:ident)*) => {
+        pub const $name: Square = Square($index);
+        define_squares!(@consts $index + 1, $($rest),*);
+    };
+    (@consts $index:expr,) => {};
+    (@consts $index:expr) => {};
+}
+
+define_squares!(
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8
+);
+
+impl Square {
+    /// Create a new Square from a raw index with bounds checking.
+    ///
+    /// # Arguments
+    /// * `index` - The square index (must be 0-63)
+    ///
+    /// # Returns
+    /// * `Ok(Square)` if index is valid
+    /// * `Err(ChessError::InvalidSquare)` if index is out of bounds
+    pub fn new(index: u8) -> Result<Self, ChessError> {
+        if index > 63 {
+            return Err(ChessError::InvalidSquare { index });
+        }
+        Ok(Square(index))
+    }
+
+    /// Square of an index known to be on the board, such as a bit of a
+    /// [`Bitboard`].
+    pub(crate) const fn new_unchecked(index: u8) -> Self {
+        debug_assert!(index < 64);
+        Square(index)
+    }
+
+    /// The square on `file` and `rank`.
+    pub const fn from_file_rank(file: File, rank: Rank) -> Self {
+        Square(rank as u8 * 8 + file as u8)
+    }
+
+    /// All squares, from a1 to h8 rank by rank.
+    pub fn all() -> impl DoubleEndedIterator<Item = Square> + ExactSizeIterator {
+        (0..64).map(Square)
+    }
+
+    /// Get the raw square index (0-63).
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+
+    pub const fn file(self) -> File {
+        File::ALL[(self.0 % 8) as usize]
+    }
+
+    pub const fn rank(self) -> Rank {
+        Rank::ALL[(self.0 / 8) as usize]
+    }
+
+    /// The set holding only this square.
+    pub const fn bitboard(self) -> Bitboard {
+        Bitboard::from_square(self.0)
+    }
+
+    /// The square `file_delta` files to the right and `rank_delta` ranks up
+    /// (towards Black), if it is on the board.
+    pub fn offset(self, file_delta: i8, rank_delta: i8) -> Option<Square> {
+        let file = self.file().offset(file_delta)?;
+        let rank = self.rank().offset(rank_delta)?;
+        Some(Square::from_file_rank(file, rank))
+    }
+
+    /// Number of king steps from this square to `other`.
+    pub fn distance(self, other: Square) -> u8 {
+        let files = (self.file() - other.file()) as u8;
+        let ranks = (self.rank() - other.rank()) as u8;
+        files.max(ranks)
+    }
+
+    /// The square on the same file with the rank mirrored, e2 for e7.
+    pub const fn flip_vertical(self) -> Square {
+        Square(self.0 ^ 56)
+    }
+
+    /// The square on the same rank with the file mirrored, g1 for b1.
+    pub const fn flip_horizontal(self) -> Square {
+        Square(self.0 ^ 7)
+    }
+
+    /// Convert to chess notation coordinates.
+    ///
+    /// # Returns
+    /// * `(file_char, rank_char)` like ('e', '4') for e4
+    pub const fn to_notation(self) -> (char, char) {
+        (self.file().to_char(), self.rank().to_char())
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (file, rank) = self.to_notation();
+        write!(f, "{}{}", file, rank)
+    }
+}
+
+impl FromStr for Square {
+    type Err = ChessError;
+
+    /// Parses a square in algebraic notation such as `e4`.
+    fn from_str(text: &str) -> Result<Square, ChessError> {
+        let invalid = || ChessError::InvalidCoordinate { coordinate: text.to_string() };
+        let mut chars = text.chars();
+        let (Some(file), Some(rank), None) = (chars.next(), chars.next(), chars.next()) else {
+            return Err(invalid());
+        };
+        match (File::from_char(file), Rank::from_char(rank)) {
+            (Some(file), Some(rank)) => Ok(Square::from_file_rank(file, rank)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl From<Square> for u8 {
+    fn from(square: Square) -> u8 {
+        square.0
+    }
+}
+
+impl TryFrom<u8> for Square {
+    type Error = ChessError;
+
+    fn try_from(index: u8) -> Result<Square, ChessError> {
+        Square::new(index)
+    }
+}
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub enum File{
+    A=0,B,C,D,E,F,G,H
+}
+
+impl File {
+    pub const ALL: [File; 8] = [File::A, File::B, File::C, File::D, File::E, File::F, File::G, File::H];
+
+    /// The file of index `index`, 0 for the a-file.
+    pub const fn new(index: u8) -> Option<File> {
+        if index < 8 {
+            Some(File::ALL[index as usize])
+        } else {
+            None
+        }
+    }
+
+    pub const fn index(self) -> u8 {
+        self as u8
+    }
+
+    /// The file `delta` files to the right, if there is one.
+    pub const fn offset(self, delta: i8) -> Option<File> {
+        let index = self as i8 + delta;
+        if index < 0 {
+            None
+        } else {
+            File::new(index as u8)
+        }
+    }
+
+    pub fn get_bit_board(&self)->Bitboard{
+        Bitboard::file(*self)
+    }
+
+    pub const fn from_char(ch: char) -> Option<File> {
+        match ch {
+            'A' | 'a' => Some(File::A),
+            'B' | 'b' => Some(File::B),
+            'C' | 'c' => Some(File::C),
+            'D' | 'd' => Some(File::D),
+            'E' | 'e' => Some(File::E),
+            'F' | 'f' => Some(File::F),
+            'G' | 'g' => Some(File::G),
+            'H' | 'h' => Some(File::H),
+            _ => None,
+        }
+    }
+
+    pub const fn to_char(&self) -> char {
+        match self {
+            File::A =>'a',
+            File::B =>'b',
+            File::C =>'c',
+            File::D =>'d',
+            File::E =>'e',
+            File::F =>'f',
+            File::G =>'g',
+            File::H =>'h'
+        }
+    }
+
+}
+
+impl Sub for File {
+    type Output = usize;
+
+    fn sub(self, other: File) -> usize {
+        let self_value = self as usize;
+        let other_value = other as usize;
+        self_value.abs_diff(other_value)
+
+    }
+}
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub enum Rank{
+    First=0,Second,Third,Fourth,Fifth,Sixth,Seventh,Eighth,
+}
+impl Rank {
+    pub const ALL: [Rank; 8] = [
+        Rank::First,
+        Rank::Second,
+        Rank::Third,
+        Rank::Fourth,
+        Rank::Fifth,
+        Rank::Sixth,
+        Rank::Seventh,
+        Rank::Eighth,
+    ];
+
+    /// The rank of index `index`, 0 for the first rank.
+    pub const fn new(index: u8) -> Option<Rank> {
+        if index < 8 {
+            Some(Rank::ALL[index as usize])
+        } else {
+            None
+        }
+    }
+
+    pub const fn index(self) -> u8 {
+        self as u8
+    }
+
+    /// The rank `delta` ranks up (towards Black), if there is one.
+    pub const fn offset(self, delta: i8) -> Option<Rank> {
+        let index = self as i8 + delta;
+        if index < 0 {
+            None
+        } else {
+            Rank::new(index as u8)
+        }
+    }
+
+    pub fn get_bit_board(&self)->Bitboard{
+        Bitboard::rank(*self)
+    }
+
+    pub const fn from_char(ch: char) -> Option<Rank> {
+        match ch {
+            '1' => Some(Rank::First),
+            '2' => Some(Rank::Second),
+            '3' => Some(Rank::Third),
+            '4' => Some(Rank::Fourth),
+            '5' => Some(Rank::Fifth),
+            '6' => Some(Rank::Sixth),
+            '7' => Some(Rank::Seventh),
+            '8' => Some(Rank::Eighth),
+            _ => None,
+        }
+    }
+
+    pub const fn to_char(&self) -> char {
+        (b'1' + *self as u8) as char
+    }
+
+}
+
+impl Sub for Rank {
+    type Output = usize;
+
+    fn sub(self, other: Rank) -> usize {
+        let self_value = self as usize;
+        let other_value = other as usize;
+        self_value.abs_diff(other_value)
+
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_creation() {
+        assert!(Square::new(0).is_ok());
+        assert!(Square::new(63).is_ok());
+        assert!(Square::new(64).is_err());
+    }
+
+    #[test]
+    fn test_square_from_file_rank() {
+        assert_eq!(Square::from_file_rank(File::A, Rank::First), Square::A1);
+        assert_eq!(Square::from_file_rank(File::H, Rank::Eighth), Square::H8);
+        assert!(File::new(7).is_some() && Rank::new(7).is_some());
+        assert!(File::new(8).is_none());
+        assert!(Rank::new(8).is_none());
+    }
+
+    #[test]
+    fn test_square_conversion() {
+        let square = Square::new(28).unwrap(); // e4
+        assert_eq!((square.file(), square.rank()), (File::E, Rank::Fourth));
+        assert_eq!(square.to_notation(), ('e', '4'));
+        assert_eq!(square.to_string(), "e4");
+    }
+
+    #[test]
+    fn test_square_parsing() {
+        assert_eq!("a1".parse::<Square>(), Ok(Square::A1));
+        assert_eq!("H8".parse::<Square>(), Ok(Square::H8));
+        for invalid in ["", "e", "e9", "i4", "e44"] {
+            assert!(invalid.parse::<Square>().is_err(), "{}", invalid);
+        }
+        assert!(Square::all().all(|square| square.to_string().parse::<Square>() == Ok(square)));
+    }
+
+    #[test]
+    fn test_square_geometry() {
+        assert_eq!(Square::B1.flip_horizontal(), Square::G1);
+        assert_eq!(Square::A1.flip_vertical(), Square::A8);
+        assert_eq!(Square::A1.offset(-1, 0), None);
+        assert_eq!(Square::G1.offset(-1, 2), Some(Square::F3));
+        assert_eq!(Square::A1.distance(Square::H8), 7);
+        assert_eq!(Square::E4.distance(Square::E4), 0);
+        assert_eq!(Square::E4.bitboard(), Bitboard::from_square(28));
+    }
+}
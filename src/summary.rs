@@ -0,0 +1,189 @@
+//! End-of-game statistics per player.
+//!
+//! Summaries are built from [`MoveReview`]s: the average centipawn loss, how
+//! many moves fell into each [`MoveClass`], and an accuracy percentage based on
+//! how much winning chance every move gave away. Stats of the same player can
+//! be added up over all games of a PGN file.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::classification::{centipawn_value, MoveClass, MoveReview};
+use crate::color::Color;
+
+/// Largest centipawn loss a single move adds to the average, so one missed
+/// mate does not outweigh a whole game of play.
+const MAX_CP_LOSS: i32 = 1000;
+
+/// Winning chance in percent for the side with the given centipawn advantage.
+///
+/// Uses the logistic curve fitted to rated games by Lichess: about 50% at
+/// equality, 75% at +2.3 and 90% at +6.
+pub fn win_probability(cp: i32) -> f64 {
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * cp as f64).exp()) - 1.0)
+}
+
+/// Accuracy of a single move in percent, from the winning chances of the mover
+/// before and after it. Moves that keep the winning chance score 100.
+pub fn move_accuracy(win_before: f64, win_after: f64) -> f64 {
+    let lost = (win_before - win_after).max(0.0);
+    (103.1668 * (-0.04354 * lost).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+/// Statistics of one player over one or more games.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerStats {
+    /// Games the stats were collected from
+    pub games: usize,
+    /// Reviewed moves
+    pub moves: usize,
+    /// Sum of the (capped) centipawn losses
+    pub total_cp_loss: i64,
+    /// Sum of the per-move accuracies
+    pub total_accuracy: f64,
+    counts: [usize; MoveClass::ALL.len()],
+}
+
+impl PlayerStats {
+    /// Adds one move to the stats.
+    pub fn record(&mut self, review: &MoveReview) {
+        self.moves += 1;
+        self.total_cp_loss += review.cp_loss.min(MAX_CP_LOSS) as i64;
+        self.counts[review.class as usize] += 1;
+
+        self.total_accuracy += if review.cp_loss == 0 {
+            100.0
+        } else {
+            move_accuracy(
+                win_probability(centipawn_value(review.eval_before)),
+                win_probability(centipawn_value(review.eval_after)),
+            )
+        };
+    }
+
+    /// Adds up the stats of another game.
+    pub fn merge(&mut self, other: &PlayerStats) {
+        self.games += other.games;
+        self.moves += other.moves;
+        self.total_cp_loss += other.total_cp_loss;
+        self.total_accuracy += other.total_accuracy;
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            *count += other;
+        }
+    }
+
+    /// Average centipawn loss per move.
+    pub fn acpl(&self) -> f64 {
+        if self.moves == 0 {
+            return 0.0;
+        }
+        self.total_cp_loss as f64 / self.moves as f64
+    }
+
+    /// Average move accuracy in percent.
+    pub fn accuracy(&self) -> f64 {
+        if self.moves == 0 {
+            return 0.0;
+        }
+        self.total_accuracy / self.moves as f64
+    }
+
+    /// Number of moves of the given class.
+    pub fn count(&self, class: MoveClass) -> usize {
+        self.counts[class as usize]
+    }
+}
+
+impl fmt::Display for PlayerStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "accuracy {:.1}%, ACPL {:.0}", self.accuracy(), self.acpl())?;
+        for class in MoveClass::ALL {
+            write!(f, ", {} {}", self.count(class), class)?;
+        }
+        Ok(())
+    }
+}
+
+/// Statistics of both players of one game.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GameSummary {
+    pub white: PlayerStats,
+    pub black: PlayerStats,
+}
+
+impl GameSummary {
+    /// Summarises the reviewed moves of a game, each counted for the side
+    /// its ply index gives.
+    pub fn from_reviews(reviews: &[MoveReview]) -> GameSummary {
+        let mut summary = GameSummary::default();
+        summary.white.games = 1;
+        summary.black.games = 1;
+        for review in reviews {
+            let side = if review.ply % 2 == 0 { Color::White } else { Color::Black };
+            summary.player_mut(side).record(review);
+        }
+        summary
+    }
+
+    pub fn player(&self, color: Color) -> &PlayerStats {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    fn player_mut(&mut self, color: Color) -> &mut PlayerStats {
+        match color {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        }
+    }
+}
+
+/// Statistics per player name, added up over many games.
+///
+/// # Examples
+///
+/// ```rust
+/// use analyzer::summary::{GameSummary, PlayerTotals};
+///
+/// let mut totals = PlayerTotals::default();
+/// let game = GameSummary::from_reviews(&[]);
+/// totals.add_game(Some("Carlsen"), Some("Nepo"), &game);
+/// totals.add_game(Some("Nepo"), Some("Carlsen"), &game);
+/// assert_eq!(totals.get("Carlsen").unwrap().games, 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PlayerTotals {
+    players: BTreeMap<String, PlayerStats>,
+}
+
+impl PlayerTotals {
+    /// Name used for games without a `White` or `Black` header.
+    pub const UNKNOWN_PLAYER: &'static str = "?";
+
+    /// Adds both players' stats of a game under their names.
+    pub fn add_game(&mut self, white: Option<&str>, black: Option<&str>, summary: &GameSummary) {
+        for (name, stats) in [(white, &summary.white), (black, &summary.black)] {
+            let name = name.unwrap_or(Self::UNKNOWN_PLAYER).to_string();
+            self.players.entry(name).or_default().merge(stats);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PlayerStats> {
+        self.players.get(name)
+    }
+
+    /// Players and their stats, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PlayerStats)> {
+        self.players.iter().map(|(name, stats)| (name.as_str(), stats))
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+}
@@ -0,0 +1,1492 @@
+//! Syzygy endgame tablebases.
+//!
+//! [`Tablebase`] probes the `.rtbw` (win/draw/loss) and `.rtbz`
+//! (distance-to-zero) files of a local directory. Tables are named after their
+//! material with the stronger side first, like `KRPvKR`, and hold positions
+//! without castling rights. A table is read into memory the first time a
+//! position needs it and kept for later probes.
+//!
+//! The decoder follows the layout of the files as written by the Syzygy
+//! generator: a header describing the piece order and index groups of every
+//! sub-table, followed by blocks of values compressed with recursive pairing
+//! and a canonical Huffman code.
+
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    ops::Neg,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use crate::{board::Board, color::Color, components::{ColoredPiece, Piece}, error::ChessError, r#move::{Castling, Move}, square::Square};
+
+/// Most pieces, kings included, a Syzygy table holds
+pub const MAX_PIECES: usize = 7;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+/// Flags of a sub-table
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_WIDE: u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+/// Added to the piece codes of table headers (1 pawn to 6 king, see
+/// [`piece_code`]) for the second side
+const BLACK: u8 = 8;
+
+/// Order of piece letters in table names
+const PIECE_ORDER: [(Piece, char); 6] = [
+    (Piece::King, 'K'),
+    (Piece::Queen, 'Q'),
+    (Piece::Rook, 'R'),
+    (Piece::Bishop, 'B'),
+    (Piece::Knight, 'N'),
+    (Piece::Pawn, 'P'),
+];
+
+/// Outcome of a position with perfect play, from the side to move's point of
+/// view. Cursed wins and blessed losses are wins and losses that the 50-move
+/// rule turns into draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Wdl {
+        match value {
+            i32::MIN..=-2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        }
+    }
+
+    fn value(self) -> i32 {
+        self as i32 - 2
+    }
+
+    /// Name as written in reports: `win`, `cursed-win`, `draw`,
+    /// `blessed-loss` or `loss`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Wdl::Loss => "loss",
+            Wdl::BlessedLoss => "blessed-loss",
+            Wdl::Draw => "draw",
+            Wdl::CursedWin => "cursed-win",
+            Wdl::Win => "win",
+        }
+    }
+
+    /// Short description: `winning`, `drawing` or `losing`, with the 50-move
+    /// rule cases spelled out.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Wdl::Loss => "losing",
+            Wdl::BlessedLoss => "drawing, saved by the 50-move rule",
+            Wdl::Draw => "drawing",
+            Wdl::CursedWin => "drawing, the win takes too long for the 50-move rule",
+            Wdl::Win => "winning",
+        }
+    }
+}
+
+/// The outcome for the other side.
+impl Neg for Wdl {
+    type Output = Wdl;
+
+    fn neg(self) -> Wdl {
+        Wdl::from_value(-self.value())
+    }
+}
+
+/// What the tables say about a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TablebaseProbe {
+    pub wdl: Wdl,
+    /// Plies to the next capture or pawn move with best play, positive when
+    /// winning, negative when losing and 0 in draws; `None` without the
+    /// position's DTZ table
+    pub dtz: Option<i32>,
+}
+
+impl fmt::Display for TablebaseProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.dtz {
+            Some(dtz) => write!(f, "{} (DTZ {})", self.wdl.describe(), dtz),
+            None => write!(f, "{}", self.wdl.describe()),
+        }
+    }
+}
+
+/// The same verdict seen from the other side.
+impl Neg for TablebaseProbe {
+    type Output = TablebaseProbe;
+
+    fn neg(self) -> TablebaseProbe {
+        TablebaseProbe {
+            wdl: -self.wdl,
+            dtz: self.dtz.map(|dtz| -dtz),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum TableKind {
+    Wdl,
+    Dtz,
+}
+
+impl TableKind {
+    fn magic(self) -> [u8; 4] {
+        match self {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        }
+    }
+}
+
+/// A directory of Syzygy tables.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use analyzer::board::Board;
+/// use analyzer::color::Color;
+/// use analyzer::syzygy::Tablebase;
+///
+/// let tablebase = Tablebase::open("/usr/share/syzygy").unwrap();
+/// let board = Board::init();
+/// // the starting position has too many pieces for any table
+/// assert!(tablebase.probe(&board, Color::White).unwrap().is_none());
+/// ```
+#[derive(Debug)]
+pub struct Tablebase {
+    /// Table name to file, per kind
+    files: HashMap<(String, TableKind), PathBuf>,
+    max_pieces: usize,
+    loaded: Mutex<HashMap<(String, TableKind), Arc<Table>>>,
+}
+
+impl Tablebase {
+    /// Indexes the tables in `directory`. Files are only read when probed.
+    ///
+    /// # Errors
+    ///
+    /// [`ChessError::FileError`] if the directory cannot be read.
+    pub fn open<P: AsRef<Path>>(directory: P) -> Result<Tablebase, ChessError> {
+        let directory = directory.as_ref();
+        let entries = fs::read_dir(directory).map_err(|e| ChessError::FileError {
+            path: directory.display().to_string(),
+            reason: e.to_string(),
+        })?;
+
+        let mut files = HashMap::new();
+        let mut max_pieces = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let (Some(name), Some(extension)) = (
+                path.file_stem().and_then(|stem| stem.to_str()),
+                path.extension().and_then(|extension| extension.to_str()),
+            ) else {
+                continue;
+            };
+            let kind = match extension {
+                "rtbw" => TableKind::Wdl,
+                "rtbz" => TableKind::Dtz,
+                _ => continue,
+            };
+            let Some(material) = Material::parse(name) else {
+                continue;
+            };
+            max_pieces = max_pieces.max(material.piece_count());
+            files.insert((name.to_string(), kind), path);
+        }
+
+        Ok(Tablebase {
+            files,
+            max_pieces,
+            loaded: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Most pieces of any table in the directory, 0 if there are none.
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    /// Number of table files found.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Win/draw/loss of the position with `side_to_move` to play. Returns
+    /// `None` if no table covers it or the position has castling rights.
+    ///
+    /// # Errors
+    ///
+    /// [`ChessError::FileError`] if a table cannot be read or is corrupt.
+    pub fn probe_wdl(&self, board: &Board, side_to_move: Color) -> Result<Option<Wdl>, ChessError> {
+        if !self.covers(board) {
+            return Ok(None);
+        }
+        Ok(self.search(board, side_to_move, false)?.map(|(wdl, _)| wdl))
+    }
+
+    /// Distance to zeroing in plies of the position with `side_to_move` to
+    /// play, see [`TablebaseProbe::dtz`]. Returns `None` if no table covers
+    /// it or the position has castling rights.
+    ///
+    /// # Errors
+    ///
+    /// [`ChessError::FileError`] if a table cannot be read or is corrupt.
+    pub fn probe_dtz(&self, board: &Board, side_to_move: Color) -> Result<Option<i32>, ChessError> {
+        if !self.covers(board) {
+            return Ok(None);
+        }
+        self.dtz(board, side_to_move)
+    }
+
+    /// Win/draw/loss and, when its DTZ table is present, the distance to
+    /// zeroing of a position. Returns `None` if no WDL table covers it.
+    pub fn probe(&self, board: &Board, side_to_move: Color) -> Result<Option<TablebaseProbe>, ChessError> {
+        let Some(wdl) = self.probe_wdl(board, side_to_move)? else {
+            return Ok(None);
+        };
+        let dtz = self.dtz(board, side_to_move)?;
+        Ok(Some(TablebaseProbe { wdl, dtz }))
+    }
+
+    /// Whether the position is small enough and free of castling rights.
+    fn covers(&self, board: &Board) -> bool {
+        (board.occupied.popcount() as usize) <= self.max_pieces && !can_castle(board)
+    }
+
+    /// Resolves captures (and, with `zeroing`, pawn moves) before trusting
+    /// the table: tables hold no positions with en passant rights and may
+    /// store any value where a capture is best. Returns the value and whether
+    /// a zeroing move achieves it.
+    fn search(&self, board: &Board, side: Color, zeroing: bool) -> Result<Option<(Wdl, bool)>, ChessError> {
+        let moves = board.legal_moves_unnamed(ply_index(side));
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+        for mov in &moves {
+            if !(mov.is_capture || zeroing && mov.piece == Piece::Pawn) {
+                continue;
+            }
+            searched += 1;
+            let Some((value, _)) = self.search(&after(board, mov), side.get_opposite(), false)? else {
+                return Ok(None);
+            };
+            let value = -value;
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Ok(Some((value, true)));
+                }
+            }
+        }
+
+        let no_more_moves = searched > 0 && searched == moves.len();
+        let value = if no_more_moves {
+            best
+        } else {
+            match self.probe_table(board, side, TableKind::Wdl, Wdl::Draw)? {
+                Some(Probed::Value(value)) => Wdl::from_value(value),
+                _ => return Ok(None),
+            }
+        };
+
+        if best >= value {
+            return Ok(Some((best, best > Wdl::Draw || no_more_moves)));
+        }
+        Ok(Some((value, false)))
+    }
+
+    /// Distance to zeroing as in [`Tablebase::probe_dtz`], without the
+    /// coverage check.
+    fn dtz(&self, board: &Board, side: Color) -> Result<Option<i32>, ChessError> {
+        let Some((wdl, zeroing_best)) = self.search(board, side, true)? else {
+            return Ok(None);
+        };
+        if wdl == Wdl::Draw {
+            return Ok(Some(0));
+        }
+        if zeroing_best {
+            return Ok(Some(dtz_before_zeroing(wdl)));
+        }
+
+        match self.probe_table(board, side, TableKind::Dtz, wdl)? {
+            None => return Ok(None),
+            Some(Probed::Value(dtz)) => {
+                let cursed = matches!(wdl, Wdl::BlessedLoss | Wdl::CursedWin);
+                return Ok(Some((dtz + if cursed { 100 } else { 0 }) * wdl.value().signum()));
+            }
+            Some(Probed::OtherSide) => {}
+        }
+
+        // the table only holds the other side to move: look one ply ahead
+        let index = ply_index(side);
+        let moves = board.legal_moves_unnamed(index);
+        let mut min_dtz = i32::MAX;
+        for mov in &moves {
+            let zeroing = mov.is_capture || mov.piece == Piece::Pawn;
+            let next = after(board, mov);
+            let other = side.get_opposite();
+            let dtz = if zeroing {
+                match self.search(&next, other, false)? {
+                    Some((value, _)) => -dtz_before_zeroing(value),
+                    None => return Ok(None),
+                }
+            } else {
+                match self.dtz(&next, other)? {
+                    Some(dtz) => -dtz,
+                    None => return Ok(None),
+                }
+            };
+            // a mating move
+            if dtz == 1 && next.is_in_check(other) && !next.has_legal_moves(index + 1) {
+                min_dtz = 1;
+            }
+            let dtz = if zeroing { dtz } else { dtz + dtz.signum() };
+            if dtz < min_dtz && dtz.signum() == wdl.value().signum() {
+                min_dtz = dtz;
+            }
+        }
+        Ok(Some(if min_dtz == i32::MAX { -1 } else { min_dtz }))
+    }
+
+    /// Looks a position up in the table of its material. Returns `None` if the
+    /// table is missing.
+    fn probe_table(&self, board: &Board, side: Color, kind: TableKind, wdl: Wdl) -> Result<Option<Probed>, ChessError> {
+        let material = Material::of(board);
+        if kind == TableKind::Wdl && material.piece_count() == 2 {
+            return Ok(Some(Probed::Value(0)));
+        }
+
+        let (white, black) = (material.side_name(0), material.side_name(1));
+        let straight = format!("{}v{}", white, black);
+        let (name, black_stronger) = if self.files.contains_key(&(straight.clone(), kind)) {
+            (straight, false)
+        } else {
+            (format!("{}v{}", black, white), true)
+        };
+        let Some(table) = self.table(&name, kind)? else {
+            return Ok(None);
+        };
+        table.probe(board, side, black_stronger, wdl)
+    }
+
+    /// Loads a table on first use.
+    fn table(&self, name: &str, kind: TableKind) -> Result<Option<Arc<Table>>, ChessError> {
+        let key = (name.to_string(), kind);
+        let Some(path) = self.files.get(&key) else {
+            return Ok(None);
+        };
+        let mut loaded = self.loaded.lock().expect("tablebase cache poisoned");
+        if let Some(table) = loaded.get(&key) {
+            return Ok(Some(Arc::clone(table)));
+        }
+        let material = Material::parse(name).expect("table names are checked when indexing");
+        let table = Arc::new(Table::read(path, kind, &material)?);
+        loaded.insert(key, Arc::clone(&table));
+        Ok(Some(table))
+    }
+}
+
+/// Result of a table lookup: the stored value, or for DTZ tables holding only
+/// one side to move, the information that the other side is stored.
+enum Probed {
+    Value(i32),
+    OtherSide,
+}
+
+/// DTZ of a winning or losing position whose best move is zeroing.
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+    }
+}
+
+/// A ply index whose parity gives `side` the move, as the board's move
+/// generation expects.
+fn ply_index(side: Color) -> u16 {
+    match side {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+fn after(board: &Board, mov: &Move) -> Board {
+    let mut next = board.clone();
+    next.apply_move(mov);
+    next
+}
+
+/// Whether a castling right is left with its king and rook still in place,
+/// on the castling files of the board (Chess960 ones included).
+fn can_castle(board: &Board) -> bool {
+    [
+        (0b1000, Color::White, Castling::King),
+        (0b0100, Color::White, Castling::Queen),
+        (0b0010, Color::Black, Castling::King),
+        (0b0001, Color::Black, Castling::Queen),
+    ]
+        .into_iter()
+        .any(|(bit, color, side)| {
+            let ((king, _), (rook, _)) = board.castling_squares(color, side);
+            board.castling_rights & bit != 0
+                && board.piece_at(king) == Some(ColoredPiece::new(color, Piece::King))
+                && board.piece_at(rook) == Some(ColoredPiece::new(color, Piece::Rook))
+        })
+}
+
+/// Pieces of both sides, counted per type in table name order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Material {
+    /// Counts of kings, queens, rooks, bishops, knights and pawns, for the
+    /// first side of a table name (White on a board) and the second
+    sides: [[u8; 6]; 2],
+}
+
+impl Material {
+    fn of(board: &Board) -> Material {
+        let mut sides = [[0u8; 6]; 2];
+        for square in Square::all() {
+            if let Some(piece) = board.piece_at(square) {
+                sides[usize::from(piece.color == Color::Black)][name_index(piece.role)] += 1;
+            }
+        }
+        Material { sides }
+    }
+
+    /// Reads a table name like `KRPvKR`.
+    fn parse(name: &str) -> Option<Material> {
+        let (first, second) = name.split_once('v')?;
+        let mut sides = [[0u8; 6]; 2];
+        for (counts, letters) in sides.iter_mut().zip([first, second]) {
+            for letter in letters.chars() {
+                let index = PIECE_ORDER.iter().position(|&(_, c)| c == letter)?;
+                counts[index] += 1;
+            }
+            if counts[0] != 1 {
+                return None;
+            }
+        }
+        let material = Material { sides };
+        (material.piece_count() <= MAX_PIECES).then_some(material)
+    }
+
+    /// Letters of one side, like `KRP`.
+    fn side_name(&self, side: usize) -> String {
+        PIECE_ORDER
+            .iter()
+            .zip(self.sides[side])
+            .flat_map(|(&(_, letter), count)| std::iter::repeat_n(letter, count as usize))
+            .collect()
+    }
+
+    fn piece_count(&self) -> usize {
+        self.sides.iter().flatten().map(|&count| count as usize).sum()
+    }
+
+    fn pawns(&self, side: usize) -> usize {
+        self.sides[side][5] as usize
+    }
+}
+
+/// Position of a piece type in table names, king first.
+fn name_index(piece: Piece) -> usize {
+    PIECE_ORDER
+        .iter()
+        .position(|&(candidate, _)| candidate == piece)
+        .expect("every piece has a letter")
+}
+
+/// Code of a piece type in table headers.
+fn piece_code(piece: Piece) -> u8 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight => 2,
+        Piece::Bishop => 3,
+        Piece::Rook => 4,
+        Piece::Queen => 5,
+        Piece::King => 6,
+    }
+}
+
+/// Index tables shared by all tables.
+struct Indexing {
+    binomial: [[u64; 64]; MAX_PIECES],
+    map_pawns: [u64; 64],
+    lead_pawn_idx: [[u64; 64]; MAX_PIECES],
+    lead_pawns_size: [[u64; 4]; MAX_PIECES],
+    map_b1h1h7: [u64; 64],
+    map_a1d1d4: [u64; 64],
+    map_kk: [[u64; 64]; 10],
+}
+
+/// Rank minus file: 0 on the a1-h8 diagonal, negative below it.
+fn off_diagonal(square: u8) -> i32 {
+    (square / 8) as i32 - (square % 8) as i32
+}
+
+fn king_distance(a: u8, b: u8) -> u8 {
+    ((a % 8) as i8 - (b % 8) as i8).unsigned_abs().max(((a / 8) as i8 - (b / 8) as i8).unsigned_abs())
+}
+
+fn indexing() -> &'static Indexing {
+    static INDEXING: OnceLock<Indexing> = OnceLock::new();
+    INDEXING.get_or_init(|| {
+        let mut ix = Indexing {
+            binomial: [[0; 64]; MAX_PIECES],
+            map_pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; MAX_PIECES],
+            lead_pawns_size: [[0; 4]; MAX_PIECES],
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+        };
+
+        let mut code = 0;
+        for square in 0..64u8 {
+            if off_diagonal(square) < 0 {
+                ix.map_b1h1h7[square as usize] = code;
+                code += 1;
+            }
+        }
+
+        // the a1-d1-d4 triangle, diagonal squares last
+        let mut code = 0;
+        let mut diagonal = Vec::new();
+        for square in 0..=27u8 {
+            if square % 8 > 3 {
+                continue;
+            }
+            if off_diagonal(square) < 0 {
+                ix.map_a1d1d4[square as usize] = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            ix.map_a1d1d4[square as usize] = code;
+            code += 1;
+        }
+
+        // the 462 placements of two kings with the first in the triangle
+        let mut code = 0;
+        let mut both_on_diagonal = Vec::new();
+        for idx in 0..10u64 {
+            for first in 0..=27u8 {
+                if first % 8 > 3 || ix.map_a1d1d4[first as usize] != idx || (idx == 0 && first != 1) {
+                    continue;
+                }
+                for second in 0..64u8 {
+                    if king_distance(first, second) <= 1 {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) > 0 {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((idx, second));
+                    } else {
+                        ix.map_kk[idx as usize][second as usize] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, second) in both_on_diagonal {
+            ix.map_kk[idx as usize][second as usize] = code;
+            code += 1;
+        }
+
+        ix.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                ix.binomial[k][n] = if k > 0 { ix.binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { ix.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        // pawn squares a2-h7, edge files and low ranks last
+        let mut assigned = 0;
+        for lead_pawns in 1..=5 {
+            for file in 0..4u8 {
+                let mut idx = 0;
+                for rank in 1..7u8 {
+                    let square = (rank * 8 + file) as usize;
+                    if lead_pawns == 1 {
+                        ix.map_pawns[square] = 47 - assigned;
+                        ix.map_pawns[square ^ 7] = 46 - assigned;
+                        assigned += 2;
+                    }
+                    ix.lead_pawn_idx[lead_pawns][square] = idx;
+                    idx += ix.binomial[lead_pawns - 1][ix.map_pawns[square] as usize];
+                }
+                ix.lead_pawns_size[lead_pawns][file as usize] = idx;
+            }
+        }
+        ix
+    })
+}
+
+/// Decoding data of one sub-table: one side to move and, with pawns, one
+/// file of the leading pawn.
+#[derive(Debug, Clone, Default)]
+struct PairsData {
+    flags: u8,
+    /// Piece codes in the order the index encodes them
+    pieces: [u8; MAX_PIECES],
+    /// Sizes of the index groups, zero-terminated
+    group_len: [usize; MAX_PIECES + 1],
+    /// Multiplier of every group in the index; after the last group, the size
+    group_idx: [u64; MAX_PIECES + 1],
+    block_size: u64,
+    span: u64,
+    sparse_index: usize,
+    sparse_index_size: u64,
+    block_length: usize,
+    block_length_size: u64,
+    blocks: u64,
+    data: usize,
+    min_sym_len: u8,
+    lowest_sym: usize,
+    base64: Vec<u64>,
+    symlen: Vec<u32>,
+    btree: usize,
+    /// Offsets of the value maps of DTZ tables, one per WDL outcome
+    map_idx: [usize; 4],
+}
+
+/// A table file read into memory.
+#[derive(Debug)]
+struct Table {
+    path: String,
+    bytes: Vec<u8>,
+    kind: TableKind,
+    symmetric: bool,
+    has_pawns: bool,
+    unique_pieces: bool,
+    piece_count: usize,
+    /// Pawns of the leading side and of the other side
+    pawn_count: [usize; 2],
+    sides: usize,
+    /// Sub-tables by file of the leading pawn, then side to move
+    pairs: Vec<PairsData>,
+    /// Offset of the DTZ value maps
+    map: usize,
+}
+
+impl Table {
+    fn read(path: &Path, kind: TableKind, material: &Material) -> Result<Table, ChessError> {
+        let bytes = fs::read(path).map_err(|e| ChessError::FileError {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        })?;
+
+        let symmetric = material.sides[0] == material.sides[1];
+        // the side with fewer pawns leads, the first on a tie
+        let first_leads = material.pawns(1) == 0 || (material.pawns(0) > 0 && material.pawns(1) >= material.pawns(0));
+        let pawn_count = if first_leads {
+            [material.pawns(0), material.pawns(1)]
+        } else {
+            [material.pawns(1), material.pawns(0)]
+        };
+        let unique_pieces = material
+            .sides
+            .iter()
+            .any(|counts| counts[1..].contains(&1));
+
+        let mut table = Table {
+            path: path.display().to_string(),
+            bytes,
+            kind,
+            symmetric,
+            has_pawns: pawn_count[0] > 0,
+            unique_pieces,
+            piece_count: material.piece_count(),
+            pawn_count,
+            sides: if kind == TableKind::Wdl && !symmetric { 2 } else { 1 },
+            pairs: Vec::new(),
+            map: 0,
+        };
+        table.parse_header()?;
+        Ok(table)
+    }
+
+    fn corrupt(&self, reason: &str) -> ChessError {
+        ChessError::FileError {
+            path: self.path.clone(),
+            reason: format!("corrupt tablebase: {}", reason),
+        }
+    }
+
+    fn byte(&self, offset: usize) -> Result<u8, ChessError> {
+        self.bytes.get(offset).copied().ok_or_else(|| self.corrupt("unexpected end of file"))
+    }
+
+    fn u16_le(&self, offset: usize) -> Result<u16, ChessError> {
+        Ok(u16::from_le_bytes([self.byte(offset)?, self.byte(offset + 1)?]))
+    }
+
+    fn u32_le(&self, offset: usize) -> Result<u32, ChessError> {
+        Ok(u32::from_le_bytes([self.byte(offset)?, self.byte(offset + 1)?, self.byte(offset + 2)?, self.byte(offset + 3)?]))
+    }
+
+    /// Big-endian bytes of the compressed data; bytes past the end read as 0.
+    fn be(&self, offset: usize, len: usize) -> u64 {
+        (0..len).fold(0, |value, i| (value << 8) | u64::from(self.bytes.get(offset + i).copied().unwrap_or(0)))
+    }
+
+    fn files(&self) -> usize {
+        if self.has_pawns {
+            4
+        } else {
+            1
+        }
+    }
+
+    fn pairs(&self, side: usize, file: usize) -> &PairsData {
+        let file = if self.has_pawns { file } else { 0 };
+        &self.pairs[file * self.sides + side % self.sides]
+    }
+
+    fn parse_header(&mut self) -> Result<(), ChessError> {
+        if self.bytes.get(..4) != Some(&self.kind.magic()[..]) {
+            return Err(self.corrupt("wrong magic number"));
+        }
+        let flags = self.byte(4)?;
+        if (flags & 2 != 0) != self.has_pawns {
+            return Err(self.corrupt("header does not match the table name"));
+        }
+
+        let mut pos = 5;
+        let both_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut pairs = Vec::new();
+        for file in 0..self.files() {
+            let first = self.byte(pos)?;
+            let second = if both_pawns { self.byte(pos + 1)? } else { 0xff };
+            let order = [[first & 0xf, second & 0xf], [first >> 4, second >> 4]];
+            pos += 1 + usize::from(both_pawns);
+
+            let mut sides = vec![PairsData::default(); self.sides];
+            for k in 0..self.piece_count {
+                let byte = self.byte(pos + k)?;
+                for (side, data) in sides.iter_mut().enumerate() {
+                    data.pieces[k] = if side == 0 { byte & 0xf } else { byte >> 4 };
+                }
+            }
+            pos += self.piece_count;
+            for (side, mut data) in sides.into_iter().enumerate() {
+                self.set_groups(&mut data, order[side], file);
+                pairs.push(data);
+            }
+        }
+        pos += pos & 1;
+
+        for data in &mut pairs {
+            pos = self.set_sizes(data, pos)?;
+        }
+
+        if self.kind == TableKind::Dtz {
+            self.map = pos;
+            for file in 0..self.files() {
+                let data = &mut pairs[file * self.sides];
+                if data.flags & FLAG_MAPPED == 0 {
+                    continue;
+                }
+                if data.flags & FLAG_WIDE != 0 {
+                    pos += pos & 1;
+                    for i in 0..4 {
+                        data.map_idx[i] = (pos - self.map) / 2 + 1;
+                        pos += 2 * self.u16_le(pos)? as usize + 2;
+                    }
+                } else {
+                    for i in 0..4 {
+                        data.map_idx[i] = pos - self.map + 1;
+                        pos += self.byte(pos)? as usize + 1;
+                    }
+                }
+            }
+            pos += pos & 1;
+        }
+
+        for data in &mut pairs {
+            data.sparse_index = pos;
+            pos += data.sparse_index_size as usize * 6;
+        }
+        for data in &mut pairs {
+            data.block_length = pos;
+            pos += data.block_length_size as usize * 2;
+        }
+        for data in &mut pairs {
+            pos = (pos + 0x3f) & !0x3f;
+            data.data = pos;
+            pos += (data.blocks * data.block_size) as usize;
+        }
+        if pos > self.bytes.len() {
+            return Err(self.corrupt("file is truncated"));
+        }
+
+        self.pairs = pairs;
+        Ok(())
+    }
+
+    /// Splits the pieces into the groups the index is made of and computes
+    /// each group's multiplier.
+    fn set_groups(&self, data: &mut PairsData, order: [u8; 2], file: usize) {
+        let ix = indexing();
+        let mut n = 0;
+        let mut first_len: i32 = if self.has_pawns {
+            0
+        } else if self.unique_pieces {
+            3
+        } else {
+            2
+        };
+        data.group_len[0] = 1;
+        for i in 1..self.piece_count {
+            first_len -= 1;
+            // the first group takes the leading pieces, later ones equal pieces
+            if first_len > 0 || data.pieces[i] == data.pieces[i - 1] {
+                data.group_len[n] += 1;
+            } else {
+                n += 1;
+                data.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        data.group_len[n] = 0;
+
+        let both_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = if both_pawns { 2 } else { 1 };
+        let mut free_squares = 64 - data.group_len[0] - if both_pawns { data.group_len[1] } else { 0 };
+        let mut idx = 1u64;
+        let mut k = 0;
+        while next < n || k == order[0] as usize || k == order[1] as usize {
+            if k == order[0] as usize {
+                data.group_idx[0] = idx;
+                idx *= if self.has_pawns {
+                    ix.lead_pawns_size[data.group_len[0]][file]
+                } else if self.unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] as usize {
+                data.group_idx[1] = idx;
+                idx *= ix.binomial[data.group_len[1]][48 - data.group_len[0]];
+            } else {
+                data.group_idx[next] = idx;
+                idx *= ix.binomial[data.group_len[next]][free_squares];
+                free_squares -= data.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        data.group_idx[n] = idx;
+    }
+
+    /// Reads the block layout and Huffman code of a sub-table.
+    fn set_sizes(&self, data: &mut PairsData, mut pos: usize) -> Result<usize, ChessError> {
+        data.flags = self.byte(pos)?;
+        pos += 1;
+        if data.flags & FLAG_SINGLE_VALUE != 0 {
+            // the single value is kept in min_sym_len
+            data.min_sym_len = self.byte(pos)?;
+            return Ok(pos + 1);
+        }
+
+        let groups = data.group_len.iter().position(|&len| len == 0).unwrap_or(MAX_PIECES);
+        let size = data.group_idx[groups];
+        data.block_size = 1 << self.byte(pos)?;
+        data.span = 1 << self.byte(pos + 1)?;
+        data.sparse_index_size = size.div_ceil(data.span);
+        let padding = self.byte(pos + 2)?;
+        data.blocks = u64::from(self.u32_le(pos + 3)?);
+        data.block_length_size = data.blocks + u64::from(padding);
+        let max_sym_len = self.byte(pos + 7)?;
+        data.min_sym_len = self.byte(pos + 8)?;
+        pos += 9;
+        data.lowest_sym = pos;
+        if max_sym_len < data.min_sym_len || data.min_sym_len == 0 {
+            return Err(self.corrupt("invalid symbol lengths"));
+        }
+
+        // canonical Huffman code: base64[i] is the smallest 64-bit padded
+        // code of length min_sym_len + i
+        let lengths = (max_sym_len - data.min_sym_len) as usize + 1;
+        data.base64 = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            let lowest = u64::from(self.u16_le(data.lowest_sym + 2 * i)?);
+            let next_lowest = u64::from(self.u16_le(data.lowest_sym + 2 * (i + 1))?);
+            data.base64[i] = data.base64[i + 1].wrapping_add(lowest).wrapping_sub(next_lowest) / 2;
+        }
+        for (i, base) in data.base64.iter_mut().enumerate() {
+            *base = base.checked_shl(64 - i as u32 - u32::from(data.min_sym_len)).unwrap_or(0);
+        }
+        pos += lengths * 2;
+
+        let symbols = self.u16_le(pos)? as usize;
+        pos += 2;
+        data.btree = pos;
+        data.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for symbol in 0..symbols {
+            if !visited[symbol] {
+                data.symlen[symbol] = self.symlen(data, symbol, &mut visited)?;
+            }
+        }
+        Ok(pos + symbols * 3 + (symbols & 1))
+    }
+
+    /// Number of values a symbol expands to, minus one.
+    fn symlen(&self, data: &mut PairsData, symbol: usize, visited: &mut [bool]) -> Result<u32, ChessError> {
+        visited[symbol] = true;
+        let (left, right) = self.pair(data, symbol)?;
+        if right == 0xfff {
+            return Ok(0);
+        }
+        for child in [left, right] {
+            if child >= visited.len() {
+                return Err(self.corrupt("symbol out of range"));
+            }
+            if !visited[child] {
+                data.symlen[child] = self.symlen(data, child, visited)?;
+            }
+        }
+        Ok(data.symlen[left] + data.symlen[right] + 1)
+    }
+
+    /// The two symbols a symbol stands for; for a literal, the value and 0xfff.
+    fn pair(&self, data: &PairsData, symbol: usize) -> Result<(usize, usize), ChessError> {
+        let offset = data.btree + 3 * symbol;
+        let [a, b, c] = [self.byte(offset)?, self.byte(offset + 1)?, self.byte(offset + 2)?];
+        let left = (usize::from(b & 0xf) << 8) | usize::from(a);
+        let right = (usize::from(c) << 4) | usize::from(b >> 4);
+        Ok((left, right))
+    }
+
+    /// The value stored at `idx` of a sub-table.
+    fn decompress(&self, data: &PairsData, idx: u64) -> Result<i32, ChessError> {
+        if data.flags & FLAG_SINGLE_VALUE != 0 {
+            return Ok(i32::from(data.min_sym_len));
+        }
+
+        // the sparse index points at the block holding the middle of every span
+        let k = idx / data.span;
+        if k >= data.sparse_index_size {
+            return Err(self.corrupt("index out of range"));
+        }
+        let entry = data.sparse_index + 6 * k as usize;
+        let mut block = u64::from(self.u32_le(entry)?);
+        let mut offset = i64::from(self.u16_le(entry + 4)?);
+        offset += (idx % data.span) as i64 - (data.span / 2) as i64;
+
+        let block_length = |block: u64| -> Result<i64, ChessError> {
+            if block >= data.block_length_size {
+                return Err(self.corrupt("block out of range"));
+            }
+            Ok(i64::from(self.u16_le(data.block_length + 2 * block as usize)?))
+        };
+        while offset < 0 {
+            block = block.checked_sub(1).ok_or_else(|| self.corrupt("block out of range"))?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        // walk the Huffman-coded symbols of the block up to the one covering the offset
+        let mut pos = data.data + (block * data.block_size) as usize;
+        let mut buffer = self.be(pos, 8);
+        pos += 8;
+        let mut buffer_bits = 64;
+        let mut symbol;
+        loop {
+            let mut len = 0;
+            while len + 1 < data.base64.len() && buffer < data.base64[len] {
+                len += 1;
+            }
+            let shift = 64 - len as u32 - u32::from(data.min_sym_len);
+            symbol = (buffer.wrapping_sub(data.base64[len]) >> shift) as usize;
+            symbol += self.u16_le(data.lowest_sym + 2 * len)? as usize;
+            let expands = i64::from(*data.symlen.get(symbol).ok_or_else(|| self.corrupt("symbol out of range"))?) + 1;
+            if offset < expands {
+                break;
+            }
+            offset -= expands;
+            let bits = len + data.min_sym_len as usize;
+            buffer <<= bits;
+            buffer_bits -= bits;
+            if buffer_bits <= 32 {
+                buffer_bits += 32;
+                buffer |= self.be(pos, 4) << (64 - buffer_bits);
+                pos += 4;
+            }
+        }
+
+        // descend the pairs to the literal at the offset
+        while data.symlen[symbol] != 0 {
+            let (left, right) = self.pair(data, symbol)?;
+            let left_expands = i64::from(data.symlen[left]) + 1;
+            if offset < left_expands {
+                symbol = left;
+            } else {
+                offset -= left_expands;
+                symbol = right;
+            }
+        }
+        Ok(self.pair(data, symbol)?.0 as i32)
+    }
+
+    /// Looks up a position. `black_stronger` tells the board has the table's
+    /// first side as Black; `wdl` is the outcome a DTZ value belongs to.
+    fn probe(&self, board: &Board, side: Color, black_stronger: bool, wdl: Wdl) -> Result<Option<Probed>, ChessError> {
+        let ix = indexing();
+        // the table stores the first side as White and, if symmetric, only White to move
+        let flip = black_stronger || (self.symmetric && side == Color::Black);
+        let (flip_color, flip_squares) = if flip { (BLACK, 56) } else { (0, 0) };
+        let stm = usize::from(flip) ^ usize::from(side == Color::Black);
+
+        let mut squares = Vec::with_capacity(self.piece_count);
+        let mut pieces = Vec::with_capacity(self.piece_count);
+        for square in Square::all() {
+            let Some(piece) = board.piece_at(square) else {
+                continue;
+            };
+            let code = piece_code(piece.role) | if piece.color == Color::Black { BLACK } else { 0 };
+            squares.push(square.index() ^ flip_squares);
+            pieces.push(code ^ flip_color);
+        }
+        if pieces.len() != self.piece_count {
+            return Err(self.corrupt("position does not match the table"));
+        }
+
+        // with pawns, the leading pawns come first, the one nearest the edge in front
+        let mut lead_pawns = 0;
+        let mut file = 0;
+        if self.has_pawns {
+            let lead = self.pairs(0, 0).pieces[0];
+            let mut order: Vec<usize> = (0..pieces.len()).filter(|&i| pieces[i] == lead).collect();
+            lead_pawns = order.len();
+            order.extend((0..pieces.len()).filter(|&i| pieces[i] != lead));
+            squares = order.iter().map(|&i| squares[i]).collect();
+            pieces = order.iter().map(|&i| pieces[i]).collect();
+            let leader = (0..lead_pawns)
+                .max_by_key(|&i| (ix.map_pawns[squares[i] as usize], std::cmp::Reverse(i)))
+                .expect("the leading side has pawns");
+            squares.swap(0, leader);
+            let f = squares[0] % 8;
+            file = f.min(7 - f) as usize;
+        }
+
+        if self.kind == TableKind::Dtz {
+            let stored = usize::from(self.pairs(0, file).flags & FLAG_STM);
+            if stored != stm && (self.has_pawns || !self.symmetric) {
+                return Ok(Some(Probed::OtherSide));
+            }
+        }
+
+        let data = self.pairs(stm, file);
+        // order the pieces like the table
+        for i in lead_pawns..pieces.len().saturating_sub(1) {
+            if let Some(j) = (i + 1..pieces.len()).find(|&j| pieces[j] == data.pieces[i]) {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        // mirror so the leading piece is on files a-d
+        if squares[0] % 8 > 3 {
+            for square in &mut squares {
+                *square ^= 7;
+            }
+        }
+
+        let mut idx;
+        if self.has_pawns {
+            idx = ix.lead_pawn_idx[lead_pawns][squares[0] as usize];
+            squares[1..lead_pawns].sort_by_key(|&square| ix.map_pawns[square as usize]);
+            for (i, &square) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                idx += ix.binomial[i][ix.map_pawns[square as usize] as usize];
+            }
+        } else {
+            // and on ranks 1-4, below the a1-h8 diagonal
+            if squares[0] / 8 > 3 {
+                for square in &mut squares {
+                    *square ^= 56;
+                }
+            }
+            if let Some(i) = (0..data.group_len[0]).find(|&i| off_diagonal(squares[i]) != 0) {
+                if off_diagonal(squares[i]) > 0 {
+                    for square in &mut squares[i..] {
+                        *square = ((*square >> 3) | (*square << 3)) & 63;
+                    }
+                }
+            }
+            idx = if self.unique_pieces {
+                self.encode_unique(&squares)
+            } else {
+                ix.map_kk[ix.map_a1d1d4[squares[0] as usize] as usize][squares[1] as usize]
+            };
+        }
+
+        idx *= data.group_idx[0];
+        let mut start = data.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = 1;
+        while data.group_len[next] != 0 {
+            let end = start + data.group_len[next];
+            squares[start..end].sort_unstable();
+            let mut n = 0;
+            for i in start..end {
+                let adjust = squares[..start].iter().filter(|&&square| squares[i] > square).count();
+                let square = squares[i] as usize - adjust - if remaining_pawns { 8 } else { 0 };
+                n += ix.binomial[i - start + 1][square];
+            }
+            remaining_pawns = false;
+            idx += n * data.group_idx[next];
+            start = end;
+            next += 1;
+        }
+
+        let value = self.decompress(data, idx)?;
+        Ok(Some(Probed::Value(match self.kind {
+            TableKind::Wdl => value - 2,
+            TableKind::Dtz => self.map_dtz(data, value, wdl)?,
+        })))
+    }
+
+    /// Index of the three leading pieces of a pawnless table with a unique piece.
+    fn encode_unique(&self, squares: &[u8]) -> u64 {
+        let ix = indexing();
+        let [s0, s1, s2] = [squares[0], squares[1], squares[2]];
+        let adjust1 = u64::from(s1 > s0);
+        let adjust2 = u64::from(s2 > s0) + u64::from(s2 > s1);
+        let rank = |square: u8| u64::from(square / 8);
+        if off_diagonal(s0) != 0 {
+            (ix.map_a1d1d4[s0 as usize] * 63 + (u64::from(s1) - adjust1)) * 62 + u64::from(s2) - adjust2
+        } else if off_diagonal(s1) != 0 {
+            (6 * 63 + rank(s0) * 28 + ix.map_b1h1h7[s1 as usize]) * 62 + u64::from(s2) - adjust2
+        } else if off_diagonal(s2) != 0 {
+            6 * 63 * 62 + 4 * 28 * 62 + rank(s0) * 7 * 28 + (rank(s1) - adjust1) * 28 + ix.map_b1h1h7[s2 as usize]
+        } else {
+            6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank(s0) * 7 * 6 + (rank(s1) - adjust1) * 6 + (rank(s2) - adjust2)
+        }
+    }
+
+    /// Turns a stored DTZ value into plies.
+    fn map_dtz(&self, data: &PairsData, value: i32, wdl: Wdl) -> Result<i32, ChessError> {
+        let mut value = value;
+        if data.flags & FLAG_MAPPED != 0 {
+            let map = match wdl {
+                Wdl::Loss => 1,
+                Wdl::BlessedLoss => 3,
+                Wdl::Draw | Wdl::Win => 0,
+                Wdl::CursedWin => 2,
+            };
+            let index = data.map_idx[map] + value as usize;
+            value = if data.flags & FLAG_WIDE != 0 {
+                i32::from(self.u16_le(self.map + 2 * index)?)
+            } else {
+                i32::from(self.byte(self.map + index)?)
+            };
+        }
+        let in_moves = match wdl {
+            Wdl::Win => data.flags & FLAG_WIN_PLIES == 0,
+            Wdl::Loss => data.flags & FLAG_LOSS_PLIES == 0,
+            Wdl::CursedWin | Wdl::BlessedLoss => true,
+            Wdl::Draw => false,
+        };
+        Ok(if in_moves { value * 2 + 1 } else { value + 1 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Bitboard;
+
+    #[test]
+    fn test_king_placements() {
+        let ix = indexing();
+        let mut codes: Vec<u64> = Vec::new();
+        for first in 0..=27u8 {
+            if first % 8 > 3 || off_diagonal(first) > 0 {
+                continue;
+            }
+            for second in 0..64u8 {
+                if king_distance(first, second) > 1 && !(off_diagonal(first) == 0 && off_diagonal(second) > 0) {
+                    codes.push(ix.map_kk[ix.map_a1d1d4[first as usize] as usize][second as usize]);
+                }
+            }
+        }
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), 462);
+        assert_eq!(codes.last(), Some(&461));
+    }
+
+    #[test]
+    fn test_pawn_tables() {
+        let ix = indexing();
+        // a2 leads, h7 trails
+        assert_eq!(ix.map_pawns[8], 47);
+        assert_eq!(ix.map_pawns[15], 46);
+        assert_eq!(ix.map_pawns[51], 1);
+        assert_eq!(ix.map_pawns[52], 0);
+        assert_eq!(ix.lead_pawns_size[1], [6; 4]);
+        assert_eq!(ix.binomial[2][5], 10);
+    }
+
+    #[test]
+    fn test_table_names() {
+        let material = Material::parse("KRPvKR").unwrap();
+        assert_eq!(material.side_name(0), "KRP");
+        assert_eq!(material.side_name(1), "KR");
+        assert_eq!(material.piece_count(), 5);
+        assert!(Material::parse("KRvR").is_none());
+        assert!(Material::parse("KXvK").is_none());
+    }
+
+    #[test]
+    fn test_castling_rights_on_chess960_files() {
+        let board = |fen: &str| crate::setup::Setup::from_fen(fen).unwrap().build().unwrap();
+        assert!(can_castle(&board("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1")));
+        // king on b1 and rook on a1, as Chess960 allows
+        assert!(can_castle(&board("1k6/8/8/8/8/8/8/RK6 w Q - 0 1")));
+        assert!(!can_castle(&board("1k6/8/8/8/8/8/8/RK6 w - - 0 1")));
+    }
+
+    /// A board holding only the given pieces, without castling rights.
+    fn board_with(pieces: &[(u8, Piece, Color)]) -> Board {
+        let mut board = Board::init();
+        for piece in Piece::get_all() {
+            *board.by_piece.get_mut(piece) = Bitboard::EMPTY;
+        }
+        board.by_color.white = Bitboard::EMPTY;
+        board.by_color.black = Bitboard::EMPTY;
+        board.occupied = Bitboard::EMPTY;
+        board.castling_rights = 0;
+        for &(square, piece, color) in pieces {
+            board.by_piece.get_mut(piece).set_bit(square);
+            board.by_color.get_mut(color).set_bit(square);
+            board.occupied.set_bit(square);
+        }
+        board.rebuild_piece_lookup();
+        board
+    }
+
+    /// A pawnless KQvK table whose sub-tables each hold a single value.
+    fn single_value_table(magic: [u8; 4], values: &[[u8; 2]]) -> Vec<u8> {
+        let mut bytes = magic.to_vec();
+        // flags, piece order, then the pieces K Q k for both sides to move
+        bytes.extend([0x01, 0x00, 0x66, 0x55, 0xee, 0x00]);
+        for value in values {
+            bytes.extend(value);
+        }
+        bytes.resize(64, 0);
+        bytes
+    }
+
+    fn tablebase(name: &str, dtz: bool) -> (PathBuf, Tablebase) {
+        let dir = std::env::temp_dir().join(format!("analyzer-syzygy-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        // White to move wins, Black to move loses
+        fs::write(dir.join("KQvK.rtbw"), single_value_table(WDL_MAGIC, &[[FLAG_SINGLE_VALUE, 4], [FLAG_SINGLE_VALUE, 0]]))
+            .unwrap();
+        if dtz {
+            // stored for White to move: a win 5 moves from zeroing
+            fs::write(dir.join("KQvK.rtbz"), single_value_table(DTZ_MAGIC, &[[FLAG_SINGLE_VALUE, 5]])).unwrap();
+        }
+        let tablebase = Tablebase::open(&dir).unwrap();
+        (dir, tablebase)
+    }
+
+    #[test]
+    fn test_probe_single_value_tables() {
+        let (dir, tablebase) = tablebase("probe", true);
+        assert_eq!((tablebase.len(), tablebase.max_pieces()), (2, 3));
+
+        // Kc3 Qb6 against Kh8
+        let board = board_with(&[(18, Piece::King, Color::White), (41, Piece::Queen, Color::White), (63, Piece::King, Color::Black)]);
+        let white = tablebase.probe(&board, Color::White).unwrap().unwrap();
+        assert_eq!(white, TablebaseProbe { wdl: Wdl::Win, dtz: Some(11) });
+        assert_eq!(white.to_string(), "winning (DTZ 11)");
+        // the table only holds White to move: Black's DTZ comes from a search
+        let black = tablebase.probe(&board, Color::Black).unwrap().unwrap();
+        assert_eq!(black, TablebaseProbe { wdl: Wdl::Loss, dtz: Some(-12) });
+
+        // the hanging queen on g7 can only be taken
+        let board = board_with(&[(18, Piece::King, Color::White), (54, Piece::Queen, Color::White), (63, Piece::King, Color::Black)]);
+        assert_eq!(tablebase.probe_wdl(&board, Color::Black).unwrap(), Some(Wdl::Draw));
+        assert_eq!(tablebase.probe_dtz(&board, Color::Black).unwrap(), Some(0));
+
+        // the same material with colors swapped reads the table flipped
+        let board = board_with(&[(18, Piece::King, Color::Black), (41, Piece::Queen, Color::Black), (63, Piece::King, Color::White)]);
+        assert_eq!(tablebase.probe_wdl(&board, Color::Black).unwrap(), Some(Wdl::Win));
+        assert_eq!(tablebase.probe_wdl(&board, Color::White).unwrap(), Some(Wdl::Loss));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// A pawnless table whose sub-tables hold `values`, one per index, with
+    /// fixed-length Huffman codes: every symbol is a literal value. `pieces`
+    /// are the header piece codes, the same for both sides to move.
+    fn compressed_table(kind: TableKind, pieces: &[u8], sub_tables: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        const BLOCK_SIZE_LOG: u8 = 6;
+        const SPAN_LOG: u8 = 8;
+        let span = 1usize << SPAN_LOG;
+
+        let mut bytes = kind.magic().to_vec();
+        bytes.push(u8::from(sub_tables.len() == 2));
+        bytes.push(0x00);
+        bytes.extend(pieces.iter().map(|&code| code | (code << 4)));
+        bytes.resize(bytes.len() + (bytes.len() & 1), 0);
+
+        let mut layouts = Vec::new();
+        for (flags, values) in sub_tables {
+            let mut symbols: Vec<u8> = values.clone();
+            symbols.sort_unstable();
+            symbols.dedup();
+            let bits = (usize::BITS - (symbols.len() - 1).leading_zeros()).max(1) as usize;
+            let per_block = (8 << BLOCK_SIZE_LOG) / bits;
+            let blocks = values.len().div_ceil(per_block);
+
+            bytes.extend([*flags, BLOCK_SIZE_LOG, SPAN_LOG, 0]);
+            bytes.extend((blocks as u32).to_le_bytes());
+            bytes.extend([bits as u8, bits as u8, 0, 0]);
+            bytes.extend((symbols.len() as u16).to_le_bytes());
+            for &value in &symbols {
+                bytes.extend([value, 0xf0, 0xff]);
+            }
+            bytes.resize(bytes.len() + (symbols.len() & 1), 0);
+            layouts.push((symbols, bits, per_block, blocks));
+        }
+        if kind == TableKind::Dtz {
+            bytes.resize(bytes.len() + (bytes.len() & 1), 0);
+        }
+
+        // the sparse index points at the middle of every span
+        for ((_, values), &(_, _, per_block, blocks)) in sub_tables.iter().zip(&layouts) {
+            for k in 0..values.len().div_ceil(span) {
+                let middle = k * span + span / 2;
+                let block = (middle / per_block).min(blocks - 1);
+                bytes.extend((block as u32).to_le_bytes());
+                bytes.extend(((middle - block * per_block) as u16).to_le_bytes());
+            }
+        }
+        for ((_, values), &(_, _, per_block, blocks)) in sub_tables.iter().zip(&layouts) {
+            for block in 0..blocks {
+                let len = per_block.min(values.len() - block * per_block);
+                bytes.extend(((len - 1) as u16).to_le_bytes());
+            }
+        }
+        for ((_, values), (symbols, bits, per_block, _)) in sub_tables.iter().zip(&layouts) {
+            bytes.resize((bytes.len() + 0x3f) & !0x3f, 0);
+            for chunk in values.chunks(*per_block) {
+                let mut block = vec![0u8; 1 << BLOCK_SIZE_LOG];
+                for (i, value) in chunk.iter().enumerate() {
+                    let code = symbols.binary_search(value).unwrap();
+                    for bit in 0..*bits {
+                        if code >> (bits - 1 - bit) & 1 != 0 {
+                            let at = i * bits + bit;
+                            block[at / 8] |= 0x80 >> (at % 8);
+                        }
+                    }
+                }
+                bytes.extend(block);
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_probe_compressed_tables() {
+        // K, R and k form the first index group (31332 placements), the
+        // knight the second (61 squares)
+        let size = 31332 * 61;
+        let (k, r, n) = (piece_code(Piece::King), piece_code(Piece::Rook), piece_code(Piece::Knight));
+        let pieces = [k, r, k | BLACK, n | BLACK];
+        // Kb1 Rd3 against Kh7 Na6: Kb1 leads the triangle as code 0, Rd3 and
+        // Kh7 follow as 19 - 1 and 55 - 2, so the first group is
+        // (0 * 63 + 18) * 62 + 53; the knight's a6 comes after two of them
+        let planted = 18 * 62 + 53 + 31332 * (40 - 2);
+        let sub_table = |default: u8, value: u8| {
+            let mut values = vec![default; size];
+            values[planted] = value;
+            values
+        };
+
+        let dir = std::env::temp_dir().join(format!("analyzer-syzygy-{}-compressed", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // White to move wins, Black to move loses, except where planted
+        let wdl = compressed_table(TableKind::Wdl, &pieces, &[(0, sub_table(4, 3)), (0, sub_table(0, 1))]);
+        fs::write(dir.join("KRvKN.rtbw"), wdl).unwrap();
+        let dtz = compressed_table(TableKind::Dtz, &pieces, &[(0, sub_table(10, 20))]);
+        fs::write(dir.join("KRvKN.rtbz"), dtz).unwrap();
+        let tablebase = Tablebase::open(&dir).unwrap();
+
+        let planted = board_with(&[
+            (1, Piece::King, Color::White),
+            (19, Piece::Rook, Color::White),
+            (40, Piece::Knight, Color::Black),
+            (55, Piece::King, Color::Black),
+        ]);
+        assert_eq!(
+            tablebase.probe(&planted, Color::White).unwrap(),
+            Some(TablebaseProbe { wdl: Wdl::CursedWin, dtz: Some(141) })
+        );
+        assert_eq!(tablebase.probe_wdl(&planted, Color::Black).unwrap(), Some(Wdl::BlessedLoss));
+
+        // the knight on a5 instead
+        let other = board_with(&[
+            (1, Piece::King, Color::White),
+            (19, Piece::Rook, Color::White),
+            (32, Piece::Knight, Color::Black),
+            (55, Piece::King, Color::Black),
+        ]);
+        assert_eq!(
+            tablebase.probe(&other, Color::White).unwrap(),
+            Some(TablebaseProbe { wdl: Wdl::Win, dtz: Some(21) })
+        );
+        assert_eq!(tablebase.probe_wdl(&other, Color::Black).unwrap(), Some(Wdl::Loss));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_tables() {
+        let (dir, tablebase) = tablebase("missing", false);
+        let board = board_with(&[(18, Piece::King, Color::White), (41, Piece::Queen, Color::White), (63, Piece::King, Color::Black)]);
+        assert_eq!(tablebase.probe(&board, Color::White).unwrap(), Some(TablebaseProbe { wdl: Wdl::Win, dtz: None }));
+
+        // KRvK has no table, and the starting position has too many pieces
+        let board = board_with(&[(18, Piece::King, Color::White), (41, Piece::Rook, Color::White), (63, Piece::King, Color::Black)]);
+        assert_eq!(tablebase.probe(&board, Color::White).unwrap(), None);
+        assert_eq!(tablebase.probe(&Board::init(), Color::White).unwrap(), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
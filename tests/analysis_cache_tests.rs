@@ -0,0 +1,179 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use analyzer::config::EngineConfig;
+use analyzer::engine::analysis::{Analysis, Score};
+use analyzer::engine::backend::SearchLimits;
+use analyzer::engine::cache::{normalize_fen, AnalysisCache, CacheKey};
+use analyzer::engine::engine::Engine;
+
+const AFTER_E4: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+
+const SCRIPT: &str = "
+id name MockEngine 1.0
+option name Hash type spin default 16 min 1 max 1024
+option name MultiPV type spin default 1 min 1 max 500
+[go]
+info depth 12 multipv 1 score cp -30 pv e7e5 g1f3
+info depth 12 multipv 2 score cp -35 pv c7c5
+bestmove e7e5 ponder g1f3
+";
+
+fn temp_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("analyzer-cache-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn sample_analysis() -> Analysis {
+    Analysis::from_uci_lines([
+        "info depth 18 multipv 1 score cp -30 pv e7e5 g1f3",
+        "info depth 18 multipv 2 score mate 4 pv c7c5",
+        "bestmove e7e5 ponder g1f3",
+    ])
+}
+
+#[test]
+fn test_entries_survive_reopening() {
+    let path = temp_path("reopen.tsv");
+    let key = CacheKey::new("Stockfish 16", &SearchLimits::depth(18), AFTER_E4);
+    {
+        let mut cache = AnalysisCache::open(&path).unwrap();
+        cache.insert(key.clone(), sample_analysis()).unwrap();
+    }
+
+    let mut cache = AnalysisCache::open(&path).unwrap();
+    assert_eq!(cache.len(), 1);
+    let analysis = cache.get(&key).unwrap();
+    assert_eq!(analysis, sample_analysis());
+    assert_eq!(analysis.lines[1].score, Score::Mate(4));
+}
+
+#[test]
+fn test_truncated_entries_are_skipped() {
+    let path = temp_path("truncated.tsv");
+    let key = CacheKey::new("Stockfish 16", &SearchLimits::depth(18), AFTER_E4);
+    fs::write(&path, "Stockfish 16\tgo depth 18\tsome fen\tinfo depth 18 multipv 1 sco").unwrap();
+
+    let mut cache = AnalysisCache::open(&path).unwrap();
+    assert!(cache.is_empty());
+    cache.insert(key, sample_analysis()).unwrap();
+    assert_eq!(AnalysisCache::open(&path).unwrap().len(), 1);
+}
+
+#[test]
+fn test_keys_ignore_move_clocks() {
+    assert_eq!(
+        normalize_fen(AFTER_E4),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3"
+    );
+
+    let limits = SearchLimits::depth(18);
+    let mut cache = AnalysisCache::in_memory();
+    cache.insert(CacheKey::new("Stockfish 16", &limits, AFTER_E4), sample_analysis()).unwrap();
+
+    let later = AFTER_E4.replace(" 0 1", " 4 12");
+    assert!(cache.get(&CacheKey::new("Stockfish 16", &limits, &later)).is_some());
+}
+
+#[test]
+fn test_keys_separate_engines_and_limits() {
+    let mut cache = AnalysisCache::in_memory();
+    cache.insert(CacheKey::new("Stockfish 16", &SearchLimits::depth(18), AFTER_E4), sample_analysis()).unwrap();
+
+    assert!(cache.get(&CacheKey::new("Stockfish 17", &SearchLimits::depth(18), AFTER_E4)).is_none());
+    assert!(cache.get(&CacheKey::new("Stockfish 16", &SearchLimits::depth(20), AFTER_E4)).is_none());
+    assert!(cache.get(&CacheKey::new("Stockfish 16", &SearchLimits::depth(18), AFTER_E4)).is_some());
+
+    let stats = cache.stats();
+    assert_eq!((stats.entries, stats.hits, stats.misses), (1, 1, 2));
+}
+
+#[test]
+fn test_keys_separate_engine_options() {
+    let key = || CacheKey::new("Stockfish 16", &SearchLimits::depth(18), AFTER_E4);
+    let mut cache = AnalysisCache::in_memory();
+    cache.insert(key().with_options([("Hash", "256"), ("MultiPV", "3")]), sample_analysis()).unwrap();
+
+    assert!(cache.get(&key().with_options([("MultiPV", "3"), ("Hash", "256")])).is_some());
+    assert!(cache.get(&key().with_options([("MultiPV", "1"), ("Hash", "256")])).is_none());
+    assert!(cache.get(&key().with_options([("MultiPV", "3"), ("Hash", "256"), ("Threads", "4")])).is_none());
+    assert!(cache.get(&key()).is_none());
+}
+
+#[test]
+fn test_invalidation() {
+    let path = temp_path("invalidate.tsv");
+    let limits = SearchLimits::depth(18);
+    let mut cache = AnalysisCache::open(&path).unwrap();
+    cache.insert(CacheKey::new("Stockfish 16", &limits, AFTER_E4), sample_analysis()).unwrap();
+    cache.insert(CacheKey::new("Stockfish 17", &limits, AFTER_E4), sample_analysis()).unwrap();
+
+    assert_eq!(cache.remove_engine("Stockfish 16").unwrap(), 1);
+    assert_eq!(AnalysisCache::open(&path).unwrap().len(), 1);
+
+    cache.clear().unwrap();
+    assert!(AnalysisCache::open(&path).unwrap().is_empty());
+}
+
+/// A mock engine config running [`SCRIPT`], and the path of its command log.
+fn mock_engine(name: &str) -> (EngineConfig, PathBuf) {
+    let dir = temp_path(name);
+    fs::create_dir_all(&dir).unwrap();
+    let script_path = dir.join("engine.uci");
+    let log_path = dir.join("engine.log");
+    fs::write(&script_path, SCRIPT).unwrap();
+
+    let config = EngineConfig {
+        timeout_ms: Some(2000),
+        engine_path: Some(env!("CARGO_BIN_EXE_mock_uci").to_string()),
+        engine_args: vec![
+            script_path.to_string_lossy().into_owned(),
+            log_path.to_string_lossy().into_owned(),
+        ],
+        ..Default::default()
+    };
+    (config, log_path)
+}
+
+#[test]
+fn test_engine_skips_cached_positions() {
+    let (config, log_path) = mock_engine("engine");
+    let cache = Arc::new(Mutex::new(AnalysisCache::in_memory()));
+    let mut engine = Engine::with_config(&config).unwrap();
+    engine.set_cache(Arc::clone(&cache));
+
+    let first = engine.process_fen(AFTER_E4).unwrap();
+    let second = engine.process_fen(&AFTER_E4.replace(" 0 1", " 0 7")).unwrap();
+    engine.quit();
+
+    assert_eq!(first, second);
+    assert_eq!(first.best_move, "e7e5");
+    let searches = fs::read_to_string(&log_path)
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with("go"))
+        .count();
+    assert_eq!(searches, 1);
+
+    let stats = cache.lock().unwrap().stats();
+    assert_eq!((stats.entries, stats.hits, stats.misses), (1, 1, 1));
+}
+
+#[test]
+fn test_engines_configured_differently_do_not_share_entries() {
+    let cache = Arc::new(Mutex::new(AnalysisCache::in_memory()));
+    for hash_mb in [None, Some(64), Some(64)] {
+        let (config, _) = mock_engine("options");
+        let mut engine = Engine::with_config(&EngineConfig { hash_mb, ..config }).unwrap();
+        engine.set_cache(Arc::clone(&cache));
+        engine.process_fen(AFTER_E4).unwrap();
+        engine.quit();
+    }
+
+    let stats = cache.lock().unwrap().stats();
+    assert_eq!((stats.entries, stats.hits, stats.misses), (2, 1, 2));
+}
@@ -0,0 +1,127 @@
+use analyzer::annotate::annotate_game;
+use analyzer::classification::ClassificationThresholds;
+use analyzer::engine::analysis::Analysis;
+use analyzer::engine::backend::SearchLimits;
+use analyzer::engine::mock::MockBackend;
+use analyzer::game_analysis::analyse_game;
+use analyzer::pgn::Pgn;
+
+fn analysis(score: &str, pv: &str) -> Analysis {
+    Analysis::from_uci_lines([
+        format!("info depth 14 score {} pv {}", score, pv),
+        format!("bestmove {}", pv.split_whitespace().next().unwrap()),
+    ])
+}
+
+/// Analyses `movetext` with canned analyses, one per position starting with
+/// the initial one, and returns the annotated PGN.
+fn annotate(movetext: &str, analyses: Vec<Analysis>) -> String {
+    let mut backend = MockBackend::new(analyses);
+    let mut pgn = Pgn::new(movetext.to_string());
+    let plies = analyse_game(&mut backend, &mut pgn, &SearchLimits::depth(14)).unwrap();
+    annotate_game(&pgn.headers, &plies, &ClassificationThresholds::default())
+}
+
+/// Movetext with line breaks replaced by spaces.
+fn unwrapped(pgn: &str) -> String {
+    pgn.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+const SCHOLARS_MATE: &str = "[Event \"Club night\"]\n[White \"Alice\"]\n[Black \"Bob\"]\n[Result \"1-0\"]\n\n\
+    1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6?? 4. Qxf7# 1-0";
+
+fn scholars_mate_analyses() -> Vec<Analysis> {
+    vec![
+        analysis("cp 30", "e2e4 e7e5"),
+        analysis("cp -30", "e7e5 g1f3"),
+        analysis("cp 30", "g1f3 b8c6"),
+        analysis("cp 0", "b8c6 f1c4"),
+        analysis("cp 20", "f1c4 g7g6"),
+        analysis("cp -20", "g7g6 h5f3 g8f6"),
+        analysis("mate 1", "h5f7"),
+        Analysis::from_uci_lines(["info depth 0 score mate 0", "bestmove (none)"]),
+    ]
+}
+
+#[test]
+fn test_headers_are_kept() {
+    let annotated = annotate(SCHOLARS_MATE, scholars_mate_analyses());
+    assert!(annotated.starts_with(
+        "[Event \"Club night\"]\n[White \"Alice\"]\n[Black \"Bob\"]\n[Result \"1-0\"]\n\n1. e4"
+    ));
+    assert!(annotated.trim_end().ends_with("1-0"));
+}
+
+#[test]
+fn test_eval_comments_are_from_whites_point_of_view() {
+    let annotated = unwrapped(&annotate(SCHOLARS_MATE, scholars_mate_analyses()));
+    assert!(annotated.contains("1. e4 { [%eval 0.30] }"));
+    assert!(annotated.contains("1... e5 { [%eval 0.30] }"));
+    assert!(annotated.contains("2... Nc6 { [%eval 0.20] }"));
+    // no evaluation once the side to move is mated
+    assert!(annotated.contains("4. Qxf7# 1-0"));
+}
+
+#[test]
+fn test_errors_get_nags_and_variations() {
+    let annotated = unwrapped(&annotate(SCHOLARS_MATE, scholars_mate_analyses()));
+    // the original "??" is replaced by the engine's verdict
+    assert!(annotated.contains("3... Nf6 $4 { [%eval #1] } ( 3... g6 4. Qf3 Nf6 )"));
+    assert!(!annotated.contains("Nf6??"));
+    assert!(!annotated.contains("Qh5 $"));
+}
+
+#[test]
+fn test_annotated_game_reads_back() {
+    let annotated = annotate(SCHOLARS_MATE, scholars_mate_analyses());
+    let pgn = Pgn::new(annotated);
+
+    let moves: Vec<&str> = pgn.moves.iter().map(|m| m.san.as_str()).collect();
+    assert_eq!(moves, ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"]);
+    assert_eq!(pgn.headers.white(), Some("Alice"));
+    assert_eq!(pgn.headers.tags().len(), 4);
+}
+
+#[test]
+fn test_variation_notation() {
+    let quiet = analysis("cp 20", "a7a6");
+    let mut analyses = vec![quiet.clone(); 4];
+    // before 3. a3: developing the queen's knight or castling were better
+    analyses.push(analysis("cp 50", "b1d2 c8f5"));
+    analyses.push(analysis("cp 300", "c8f5"));
+    let annotated = unwrapped(&annotate("[Event \"?\"]\n\n1. d4 d5 2. Nf3 Nf6 3. a3 *", analyses));
+    assert!(annotated.contains("3. a3 $4 { [%eval -3.00] } ( 3. Nbd2 Bf5 )"));
+
+    let mut analyses = vec![quiet; 6];
+    analyses.push(analysis("cp 60", "e1g1 g8f6 d2d4 e5d4"));
+    analyses.push(analysis("cp 0", "g8f6"));
+    let annotated = unwrapped(&annotate("[Event \"?\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. a3 *", analyses));
+    assert!(annotated.contains("( 4. O-O Nf6 5. d4 exd4 )"));
+    assert!(annotated.contains("4. a3 $6"));
+}
+
+#[test]
+fn test_lines_are_wrapped() {
+    let annotated = annotate(SCHOLARS_MATE, scholars_mate_analyses());
+    assert!(annotated.lines().all(|line| line.len() < 80));
+}
+
+#[test]
+fn test_games_from_a_fen_tag() {
+    let analyses = vec![
+        analysis("cp -90", "e8d7 e1d2"),
+        analysis("cp 80", "e2e4 d7c6"),
+        analysis("cp -100", "d7c6 e4e5"),
+        analysis("cp 100", "e1d2 c6b5"),
+        analysis("cp 10", "c6d5"),
+    ];
+    let annotated = unwrapped(&annotate(
+        "[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]\n\n1... Kd7 2. e4 Kc6 3. e5 *",
+        analyses,
+    ));
+    assert!(
+        annotated.contains("1... Kd7 { [%eval 0.80] } 2. e4 { [%eval 1.00] } 2... Kc6 { [%eval 1.00] } 3. e5 $2 { [%eval -0.10] } ( 3. Kd2 Kb5 )"),
+        "{}",
+        annotated
+    );
+}
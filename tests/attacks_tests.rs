@@ -0,0 +1,85 @@
+use analyzer::attacks;
+use analyzer::bitboard::Bitboard;
+use analyzer::color::Color;
+use analyzer::components::Piece;
+
+/// Squares a slider reaches stepping by the (file, rank) offsets until the
+/// edge or a blocker, the slow way.
+fn walk(square: u8, occupied: u64, directions: &[(i8, i8)]) -> u64 {
+    let mut attacks = 0;
+    for &(file_step, rank_step) in directions {
+        let (mut file, mut rank) = ((square % 8) as i8, (square / 8) as i8);
+        loop {
+            file += file_step;
+            rank += rank_step;
+            if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+                break;
+            }
+            attacks |= 1 << (rank * 8 + file);
+            if occupied & (1 << (rank * 8 + file)) != 0 {
+                break;
+            }
+        }
+    }
+    attacks
+}
+
+fn bits(squares: &[u8]) -> Bitboard {
+    squares.iter().copied().collect()
+}
+
+#[test]
+fn test_leapers() {
+    // a1, e4, h8
+    assert_eq!(attacks::knight(0), bits(&[10, 17]));
+    assert_eq!(attacks::knight(28), bits(&[11, 13, 18, 22, 34, 38, 43, 45]));
+    assert_eq!(attacks::king(63), bits(&[54, 55, 62]));
+    assert_eq!(attacks::king(28), bits(&[19, 20, 21, 27, 29, 35, 36, 37]));
+
+    // no wrapping around the board edge
+    assert_eq!(attacks::pawn(Color::White, 8), bits(&[17]));
+    assert_eq!(attacks::pawn(Color::White, 15), bits(&[22]));
+    assert_eq!(attacks::pawn(Color::Black, 52), bits(&[43, 45]));
+    assert_eq!(attacks::pawn(Color::White, 60), Bitboard::EMPTY);
+    assert_eq!(attacks::pawn(Color::Black, 3), Bitboard::EMPTY);
+}
+
+#[test]
+fn test_sliders_match_ray_walking() {
+    let bishop = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+    let rook = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+    // xorshift, for reproducible occupancies of varying density
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for round in 0..200 {
+        let occupied = match round % 3 {
+            0 => next() & next() & next(),
+            1 => next() & next(),
+            _ => next(),
+        };
+        for square in 0..64 {
+            assert_eq!(attacks::bishop(square, Bitboard(occupied)).get(), walk(square, occupied, &bishop));
+            assert_eq!(attacks::rook(square, Bitboard(occupied)).get(), walk(square, occupied, &rook));
+            assert_eq!(
+                attacks::queen(square, Bitboard(occupied)).get(),
+                walk(square, occupied, &bishop) | walk(square, occupied, &rook)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_piece_attacks() {
+    let occupied = Bitboard(0xffff_0000_0000_ffff);
+    // the rook on a1 is boxed in by its own pawn and knight
+    assert_eq!(attacks::piece(Piece::Rook, Color::White, 0, occupied), bits(&[1, 8]));
+    assert_eq!(attacks::piece(Piece::Bishop, Color::Black, 58, occupied), bits(&[49, 51]));
+    assert_eq!(attacks::piece(Piece::Pawn, Color::Black, 52, occupied), bits(&[43, 45]));
+    assert_eq!(attacks::piece(Piece::Knight, Color::White, 6, occupied), bits(&[12, 21, 23]));
+}
@@ -0,0 +1,91 @@
+use analyzer::bitboard::{Bitboard, Direction};
+use analyzer::square::{File, Rank};
+
+fn set(squares: &[u8]) -> Bitboard {
+    squares.iter().copied().collect()
+}
+
+#[test]
+fn test_set_operations() {
+    let a = set(&[0, 9, 18]);
+    let b = set(&[9, 27]);
+    assert_eq!(a & b, set(&[9]));
+    assert_eq!(a | b, set(&[0, 9, 18, 27]));
+    assert_eq!(a ^ b, set(&[0, 18, 27]));
+    assert_eq!(!Bitboard::EMPTY, Bitboard::FULL);
+    assert_eq!(a & !b, set(&[0, 18]));
+    assert_eq!(set(&[1]) << 8, set(&[9]));
+    assert_eq!(set(&[9]) >> 9, set(&[0]));
+
+    let mut c = a;
+    c &= b;
+    c |= set(&[63]);
+    c ^= set(&[9]);
+    assert_eq!(c, set(&[63]));
+    assert!(Bitboard::EMPTY.is_empty());
+    assert!(a.contains(18) && !a.contains(19));
+}
+
+#[test]
+fn test_squares() {
+    let mut board = set(&[63, 4, 28]);
+    assert_eq!(board.popcount(), 3);
+    assert_eq!(board.lsb(), Some(4));
+    assert_eq!(board.msb(), Some(63));
+    assert_eq!(board.squares().len(), 3);
+    assert_eq!(board.into_iter().collect::<Vec<_>>(), [4, 28, 63]);
+
+    assert_eq!(board.pop_lsb(), Some(4));
+    assert_eq!(board.pop_lsb(), Some(28));
+    assert_eq!(board.pop_lsb(), Some(63));
+    assert_eq!(board.pop_lsb(), None);
+    assert_eq!(board.lsb(), None);
+    assert_eq!(board.msb(), None);
+}
+
+#[test]
+fn test_shifts_do_not_wrap() {
+    // a4 and h5
+    let board = set(&[24, 39]);
+    assert_eq!(board.shift(Direction::North), set(&[32, 47]));
+    assert_eq!(board.shift(Direction::South), set(&[16, 31]));
+    assert_eq!(board.shift(Direction::East), set(&[25]));
+    assert_eq!(board.shift(Direction::West), set(&[38]));
+    assert_eq!(board.shift(Direction::NorthEast), set(&[33]));
+    assert_eq!(board.shift(Direction::NorthWest), set(&[46]));
+    assert_eq!(board.shift(Direction::SouthEast), set(&[17]));
+    assert_eq!(board.shift(Direction::SouthWest), set(&[30]));
+
+    assert_eq!(Bitboard::rank(Rank::Eighth).shift(Direction::North), Bitboard::EMPTY);
+    // one rank or file drops out, two for diagonal steps
+    let straight = [Direction::North, Direction::South, Direction::East, Direction::West];
+    for direction in Direction::ALL {
+        let expected = if straight.contains(&direction) { 56 } else { 49 };
+        assert_eq!(Bitboard::FULL.shift(direction).popcount(), expected);
+    }
+}
+
+#[test]
+fn test_masks() {
+    assert_eq!(Bitboard::file(File::C), set(&[2, 10, 18, 26, 34, 42, 50, 58]));
+    assert_eq!(Bitboard::rank(Rank::Third), Bitboard(0xff_0000));
+    assert_eq!(Bitboard::file_of(42), Bitboard::file(File::C));
+    assert_eq!(Bitboard::rank_of(42), Bitboard::rank(Rank::Sixth));
+    assert_eq!(File::C.get_bit_board(), Bitboard::file(File::C));
+
+    // through c6: a4-f8 and a8-h1
+    assert_eq!(Bitboard::diagonal(42), set(&[24, 33, 42, 51, 60]));
+    assert_eq!(Bitboard::anti_diagonal(42), set(&[7, 14, 21, 28, 35, 42, 49, 56]));
+    assert_eq!(Bitboard::diagonal(7), set(&[7]));
+
+    assert_eq!(Bitboard::line(0, 18), Bitboard::diagonal(0));
+    assert_eq!(Bitboard::line(12, 60), Bitboard::file(File::E));
+    assert_eq!(Bitboard::line(0, 17), Bitboard::EMPTY);
+    assert_eq!(Bitboard::line(5, 5), Bitboard::EMPTY);
+
+    assert_eq!(Bitboard::between(0, 7), set(&[1, 2, 3, 4, 5, 6]));
+    assert_eq!(Bitboard::between(60, 4), set(&[12, 20, 28, 36, 44, 52]));
+    assert_eq!(Bitboard::between(42, 7), set(&[14, 21, 28, 35]));
+    assert_eq!(Bitboard::between(0, 1), Bitboard::EMPTY);
+    assert_eq!(Bitboard::between(0, 17), Bitboard::EMPTY);
+}
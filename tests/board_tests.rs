@@ -1,5 +1,6 @@
 use analyzer::board::Board;
-use analyzer::components::Piece;
+use analyzer::components::{ColoredPiece, Piece};
+use analyzer::square::Square;
 use analyzer::r#move::Move;
 use analyzer::error::ChessError;
 use analyzer::color::Color;
@@ -9,33 +10,33 @@ fn test_initial_piece_lookup() {
     let board = Board::init();
     
     // Test white pieces on starting squares
-    assert_eq!(board.get_piece_at_index(0).unwrap(), Piece::Rook);   // a1
-    assert_eq!(board.get_piece_at_index(1).unwrap(), Piece::Knight); // b1
-    assert_eq!(board.get_piece_at_index(2).unwrap(), Piece::Bishop); // c1
-    assert_eq!(board.get_piece_at_index(3).unwrap(), Piece::Queen);  // d1
-    assert_eq!(board.get_piece_at_index(4).unwrap(), Piece::King);   // e1
-    assert_eq!(board.get_piece_at_index(5).unwrap(), Piece::Bishop); // f1
-    assert_eq!(board.get_piece_at_index(6).unwrap(), Piece::Knight); // g1
-    assert_eq!(board.get_piece_at_index(7).unwrap(), Piece::Rook);   // h1
+    assert_eq!(board.get_piece_at(Square::A1).unwrap(), Piece::Rook);   // a1
+    assert_eq!(board.get_piece_at(Square::B1).unwrap(), Piece::Knight); // b1
+    assert_eq!(board.get_piece_at(Square::C1).unwrap(), Piece::Bishop); // c1
+    assert_eq!(board.get_piece_at(Square::D1).unwrap(), Piece::Queen);  // d1
+    assert_eq!(board.get_piece_at(Square::E1).unwrap(), Piece::King);   // e1
+    assert_eq!(board.get_piece_at(Square::F1).unwrap(), Piece::Bishop); // f1
+    assert_eq!(board.get_piece_at(Square::G1).unwrap(), Piece::Knight); // g1
+    assert_eq!(board.get_piece_at(Square::H1).unwrap(), Piece::Rook);   // h1
     
     // Test white pawns
     for i in 8..16 {
-        assert_eq!(board.get_piece_at_index(i).unwrap(), Piece::Pawn);
+        assert_eq!(board.get_piece_at(Square::new(i).unwrap()).unwrap(), Piece::Pawn);
     }
     
     // Test black pieces on starting squares
-    assert_eq!(board.get_piece_at_index(56).unwrap(), Piece::Rook);   // a8
-    assert_eq!(board.get_piece_at_index(57).unwrap(), Piece::Knight); // b8
-    assert_eq!(board.get_piece_at_index(58).unwrap(), Piece::Bishop); // c8
-    assert_eq!(board.get_piece_at_index(59).unwrap(), Piece::Queen);  // d8
-    assert_eq!(board.get_piece_at_index(60).unwrap(), Piece::King);   // e8
-    assert_eq!(board.get_piece_at_index(61).unwrap(), Piece::Bishop); // f8
-    assert_eq!(board.get_piece_at_index(62).unwrap(), Piece::Knight); // g8
-    assert_eq!(board.get_piece_at_index(63).unwrap(), Piece::Rook);   // h8
+    assert_eq!(board.get_piece_at(Square::A8).unwrap(), Piece::Rook);   // a8
+    assert_eq!(board.get_piece_at(Square::B8).unwrap(), Piece::Knight); // b8
+    assert_eq!(board.get_piece_at(Square::C8).unwrap(), Piece::Bishop); // c8
+    assert_eq!(board.get_piece_at(Square::D8).unwrap(), Piece::Queen);  // d8
+    assert_eq!(board.get_piece_at(Square::E8).unwrap(), Piece::King);   // e8
+    assert_eq!(board.get_piece_at(Square::F8).unwrap(), Piece::Bishop); // f8
+    assert_eq!(board.get_piece_at(Square::G8).unwrap(), Piece::Knight); // g8
+    assert_eq!(board.get_piece_at(Square::H8).unwrap(), Piece::Rook);   // h8
     
     // Test black pawns
     for i in 48..56 {
-        assert_eq!(board.get_piece_at_index(i).unwrap(), Piece::Pawn);
+        assert_eq!(board.get_piece_at(Square::new(i).unwrap()).unwrap(), Piece::Pawn);
     }
 }
 
@@ -45,9 +46,9 @@ fn test_empty_square_lookup() {
     
     // Test empty squares in the middle of the board
     for i in 16..48 {
-        match board.get_piece_at_index(i) {
+        match board.get_piece_at(Square::new(i).unwrap()) {
             Err(ChessError::PieceNotFound { square }) => {
-                assert_eq!(square, i);
+                assert_eq!(square.index(), i);
             },
             _ => panic!("Expected PieceNotFound error for empty square {}", i),
         }
@@ -56,22 +57,21 @@ fn test_empty_square_lookup() {
 
 #[test]
 fn test_invalid_square_lookup() {
-    let board = Board::init();
-    
-    // Test invalid square indices
-    match board.get_piece_at_index(64) {
+    // Squares outside the board cannot be constructed, so cannot be looked up
+    match Square::new(64) {
         Err(ChessError::InvalidSquare { index }) => {
             assert_eq!(index, 64);
         },
         _ => panic!("Expected InvalidSquare error for index 64"),
     }
     
-    match board.get_piece_at_index(255) {
+    match Square::new(255) {
         Err(ChessError::InvalidSquare { index }) => {
             assert_eq!(index, 255);
         },
         _ => panic!("Expected InvalidSquare error for index 255"),
     }
+    assert!("e9".parse::<Square>().is_err());
 }
 
 #[test]
@@ -83,15 +83,15 @@ fn test_lookup_after_simple_move() {
     board.apply_move(&move_e4);
     
     // e2 should now be empty
-    match board.get_piece_at_index(12) { // e2
+    match board.get_piece_at(Square::E2) { // e2
         Err(ChessError::PieceNotFound { square }) => {
-            assert_eq!(square, 12);
+            assert_eq!(square, Square::E2);
         },
         _ => panic!("Expected e2 to be empty after e4"),
     }
     
     // e4 should now have a pawn
-    assert_eq!(board.get_piece_at_index(28).unwrap(), Piece::Pawn); // e4
+    assert_eq!(board.get_piece_at(Square::E4).unwrap(), Piece::Pawn); // e4
 }
 
 #[test]
@@ -110,20 +110,20 @@ fn test_lookup_after_multiple_moves() {
     }
     
     // Check final positions
-    assert_eq!(board.get_piece_at_index(28).unwrap(), Piece::Pawn);   // e4 - white pawn
-    assert_eq!(board.get_piece_at_index(43).unwrap(), Piece::Pawn);   // d6 - black pawn
-    assert_eq!(board.get_piece_at_index(21).unwrap(), Piece::Knight); // f3 - white knight
+    assert_eq!(board.get_piece_at(Square::E4).unwrap(), Piece::Pawn);   // e4 - white pawn
+    assert_eq!(board.get_piece_at(Square::D6).unwrap(), Piece::Pawn);   // d6 - black pawn
+    assert_eq!(board.get_piece_at(Square::F3).unwrap(), Piece::Knight); // f3 - white knight
     
     // Check vacated squares
-    match board.get_piece_at_index(12) { // e2
+    match board.get_piece_at(Square::E2) { // e2
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("e2 should be empty"),
     }
-    match board.get_piece_at_index(51) { // d7
+    match board.get_piece_at(Square::D7) { // d7
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("d7 should be empty"),
     }
-    match board.get_piece_at_index(6) { // g1
+    match board.get_piece_at(Square::G1) { // g1
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("g1 should be empty"),
     }
@@ -146,10 +146,10 @@ fn test_lookup_after_capture() {
     }
     
     // d5 should have white pawn (captured black pawn)
-    assert_eq!(board.get_piece_at_index(35).unwrap(), Piece::Pawn); // d5
+    assert_eq!(board.get_piece_at(Square::D5).unwrap(), Piece::Pawn); // d5
     
     // e4 should be empty (pawn moved from there)
-    match board.get_piece_at_index(28) { // e4
+    match board.get_piece_at(Square::E4) { // e4
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("e4 should be empty after pawn moved"),
     }
@@ -178,17 +178,17 @@ fn test_lookup_after_castling() {
     board.apply_move(&castle);
     
     // King should be on g1
-    assert_eq!(board.get_piece_at_index(6).unwrap(), Piece::King); // g1
+    assert_eq!(board.get_piece_at(Square::G1).unwrap(), Piece::King); // g1
     
     // Rook should be on f1
-    assert_eq!(board.get_piece_at_index(5).unwrap(), Piece::Rook); // f1
+    assert_eq!(board.get_piece_at(Square::F1).unwrap(), Piece::Rook); // f1
     
     // e1 and h1 should be empty
-    match board.get_piece_at_index(4) { // e1
+    match board.get_piece_at(Square::E1) { // e1
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("e1 should be empty after castling"),
     }
-    match board.get_piece_at_index(7) { // h1
+    match board.get_piece_at(Square::H1) { // h1
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("h1 should be empty after castling"),
     }
@@ -213,7 +213,7 @@ fn test_lookup_consistency_with_bitboards() {
     
     // Verify lookup table matches bitboard state for all squares
     for square in 0..64 {
-        let lookup_result = board.get_piece_at_index(square);
+        let lookup_result = board.get_piece_at(Square::new(square).unwrap());
         
         // Check if square is occupied according to bitboards
         let is_occupied = board.occupied.get_bit(square);
@@ -263,7 +263,7 @@ fn test_lookup_table_rebuilds_correctly() {
         
         // Verify lookup table consistency after each move
         for square in 0..64 {
-            let lookup_result = board.get_piece_at_index(square);
+            let lookup_result = board.get_piece_at(Square::new(square).unwrap());
             let is_occupied = board.occupied.get_bit(square);
             
             match (is_occupied, lookup_result) {
@@ -275,12 +275,12 @@ fn test_lookup_table_rebuilds_correctly() {
     }
     
     // Verify specific piece positions after all moves
-    assert_eq!(board.get_piece_at_index(28).unwrap(), Piece::Pawn);   // e4 - white pawn
-    assert_eq!(board.get_piece_at_index(36).unwrap(), Piece::Pawn);   // e5 - black pawn  
-    assert_eq!(board.get_piece_at_index(21).unwrap(), Piece::Knight); // f3 - white knight
-    assert_eq!(board.get_piece_at_index(42).unwrap(), Piece::Knight); // c6 - black knight
-    assert_eq!(board.get_piece_at_index(26).unwrap(), Piece::Bishop); // c4 - white bishop
-    assert_eq!(board.get_piece_at_index(45).unwrap(), Piece::Pawn);   // f6 - black pawn
+    assert_eq!(board.get_piece_at(Square::E4).unwrap(), Piece::Pawn);   // e4 - white pawn
+    assert_eq!(board.get_piece_at(Square::E5).unwrap(), Piece::Pawn);   // e5 - black pawn  
+    assert_eq!(board.get_piece_at(Square::F3).unwrap(), Piece::Knight); // f3 - white knight
+    assert_eq!(board.get_piece_at(Square::C6).unwrap(), Piece::Knight); // c6 - black knight
+    assert_eq!(board.get_piece_at(Square::C4).unwrap(), Piece::Bishop); // c4 - white bishop
+    assert_eq!(board.get_piece_at(Square::F6).unwrap(), Piece::Pawn);   // f6 - black pawn
 }
 
 #[test]
@@ -293,7 +293,7 @@ fn test_lookup_performance_vs_linear_search() {
     let start = Instant::now();
     for _ in 0..10000 {
         for square in 0..64 {
-            let _ = board.get_piece_at_index(square);
+            let _ = board.get_piece_at(Square::new(square).unwrap());
         }
     }
     let optimized_duration = start.elapsed();
@@ -319,11 +319,11 @@ fn test_apply_move_pawn_advance() {
     let source = board.apply_move(&move_e4);
     
     // Verify source square returned
-    assert_eq!(source, Some(12)); // e2 square index
+    assert_eq!(source, Some(Square::E2));
     
     // Verify piece moved correctly
-    assert_eq!(board.get_piece_at_index(28).unwrap(), Piece::Pawn); // e4
-    match board.get_piece_at_index(12) { // e2 should be empty
+    assert_eq!(board.get_piece_at(Square::E4).unwrap(), Piece::Pawn); // e4
+    match board.get_piece_at(Square::E2) { // e2 should be empty
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("e2 should be empty after pawn move"),
     }
@@ -356,10 +356,10 @@ fn test_apply_move_capture() {
     
     // Verify capture completed
     assert!(source.is_some());
-    assert_eq!(board.get_piece_at_index(35).unwrap(), Piece::Pawn); // d5 has white pawn
+    assert_eq!(board.get_piece_at(Square::D5).unwrap(), Piece::Pawn); // d5 has white pawn
     
     // Verify source square is empty
-    match board.get_piece_at_index(28) { // e4 should be empty
+    match board.get_piece_at(Square::E4) { // e4 should be empty
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("e4 should be empty after pawn capture"),
     }
@@ -378,9 +378,9 @@ fn test_apply_move_knight() {
     let source = board.apply_move(&knight_move);
     
     // Verify knight moved
-    assert_eq!(source, Some(6)); // g1 source
-    assert_eq!(board.get_piece_at_index(21).unwrap(), Piece::Knight); // f3
-    match board.get_piece_at_index(6) { // g1 should be empty
+    assert_eq!(source, Some(Square::G1));
+    assert_eq!(board.get_piece_at(Square::F3).unwrap(), Piece::Knight); // f3
+    match board.get_piece_at(Square::G1) { // g1 should be empty
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("g1 should be empty after knight move"),
     }
@@ -408,15 +408,15 @@ fn test_apply_move_castling_kingside() {
     let source = board.apply_move(&castle_move);
     
     // Verify king and rook positions
-    assert_eq!(board.get_piece_at_index(6).unwrap(), Piece::King); // g1
-    assert_eq!(board.get_piece_at_index(5).unwrap(), Piece::Rook); // f1
+    assert_eq!(board.get_piece_at(Square::G1).unwrap(), Piece::King); // g1
+    assert_eq!(board.get_piece_at(Square::F1).unwrap(), Piece::Rook); // f1
     
     // Verify old squares are empty
-    match board.get_piece_at_index(4) { // e1
+    match board.get_piece_at(Square::E1) { // e1
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("e1 should be empty after castling"),
     }
-    match board.get_piece_at_index(7) { // h1
+    match board.get_piece_at(Square::H1) { // h1
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("h1 should be empty after castling"),
     }
@@ -439,7 +439,7 @@ fn test_apply_move_basic_functionality() {
     assert!(source.is_some());
     
     // Should update piece positions
-    assert_eq!(board.get_piece_at_index(28).unwrap(), Piece::Pawn); // e4
+    assert_eq!(board.get_piece_at(Square::E4).unwrap(), Piece::Pawn); // e4
     
     // Should maintain move counters properly
     assert_eq!(board.half_move_count, 0); // Pawn move resets
@@ -449,8 +449,8 @@ fn test_apply_move_basic_functionality() {
     board.apply_move(&Move::new("exd5".to_string(), 2));
     
     // Capture should work correctly
-    assert_eq!(board.get_piece_at_index(35).unwrap(), Piece::Pawn); // d5 white pawn
-    match board.get_piece_at_index(28) { // e4 should be empty
+    assert_eq!(board.get_piece_at(Square::D5).unwrap(), Piece::Pawn); // d5 white pawn
+    match board.get_piece_at(Square::E4) { // e4 should be empty
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("e4 should be empty after capture"),
     }
@@ -467,8 +467,8 @@ fn test_apply_move_en_passant() {
     board.apply_move(&Move::new("d5".to_string(), 3)); // Black pawn double move, enabling en passant
     
     // Verify setup
-    assert_eq!(board.get_piece_at_index(36).unwrap(), Piece::Pawn); // e5 white pawn
-    assert_eq!(board.get_piece_at_index(35).unwrap(), Piece::Pawn); // d5 black pawn
+    assert_eq!(board.get_piece_at(Square::E5).unwrap(), Piece::Pawn); // e5 white pawn
+    assert_eq!(board.get_piece_at(Square::D5).unwrap(), Piece::Pawn); // d5 black pawn
     
     // En passant capture
     let en_passant_move = Move::new("exd6".to_string(), 4);
@@ -476,16 +476,16 @@ fn test_apply_move_en_passant() {
     
     // Verify en passant worked
     assert!(source.is_some());
-    assert_eq!(board.get_piece_at_index(43).unwrap(), Piece::Pawn); // d6 has white pawn
+    assert_eq!(board.get_piece_at(Square::D6).unwrap(), Piece::Pawn); // d6 has white pawn
     
     // Verify captured pawn is gone
-    match board.get_piece_at_index(35) { // d5 should be empty
+    match board.get_piece_at(Square::D5) { // d5 should be empty
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("d5 should be empty after en passant capture"),
     }
     
     // Verify source square is empty
-    match board.get_piece_at_index(36) { // e5 should be empty
+    match board.get_piece_at(Square::E5) { // e5 should be empty
         Err(ChessError::PieceNotFound { .. }) => {},
         _ => panic!("e5 should be empty after en passant capture"),
     }
@@ -541,4 +541,41 @@ fn test_apply_move_full_move_counter() {
     // Another black move should increment again
     board.apply_move(&Move::new("Nc6".to_string(), 3));
     assert_eq!(board.full_move_count, initial_full_moves + 2);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_piece_at_reports_color_and_role() {
+    let board = Board::init();
+    assert_eq!(board.piece_at(Square::E1), Some(ColoredPiece::new(Color::White, Piece::King)));
+    assert_eq!(board.piece_at(Square::G8), Some(ColoredPiece::new(Color::Black, Piece::Knight)));
+    assert_eq!(board.piece_at(Square::E4), None);
+
+    let black_rook = board.piece_at(Square::A8).unwrap();
+    assert_eq!(black_rook.to_fen_char(), 'r');
+    assert_eq!(black_rook.to_unicode(), '♜');
+    assert_eq!(board.piece_at(Square::A1).unwrap().to_unicode(), '♖');
+    for ch in "PNBRQKpnbrqk".chars() {
+        assert_eq!(ColoredPiece::from_fen_char(ch).unwrap().to_fen_char(), ch);
+    }
+    assert_eq!(ColoredPiece::from_fen_char('x'), None);
+}
+
+#[test]
+fn test_put_and_remove_piece() {
+    let mut board = Board::init();
+    let white_queen = ColoredPiece::new(Color::White, Piece::Queen);
+
+    assert_eq!(board.remove_piece(Square::E4), None);
+    assert_eq!(board.remove_piece(Square::D1), Some(white_queen));
+    assert_eq!(board.put_piece(Square::D4, white_queen), None);
+    // replacing a piece clears it from its own bitboards
+    assert_eq!(board.put_piece(Square::D7, white_queen), Some(ColoredPiece::new(Color::Black, Piece::Pawn)));
+
+    assert_eq!(board.piece_at(Square::D1), None);
+    assert_eq!(board.piece_at(Square::D7), Some(white_queen));
+    assert!(!board.by_piece.pawn.contains(Square::D7.index()));
+    assert!(!board.by_color.black.contains(Square::D7.index()));
+    assert_eq!(board.by_piece.queen.popcount(), 3);
+    assert_eq!(board.occupied, board.by_color.white | board.by_color.black);
+    assert_eq!(board.get_piece_at(Square::D4), Ok(Piece::Queen));
+}
@@ -0,0 +1,140 @@
+use analyzer::board::Board;
+use analyzer::book_builder::{BookBuilder, BookFilter, MoveStats};
+use analyzer::color::Color;
+use analyzer::pgn::Pgn;
+use analyzer::polyglot::{zobrist_key, BookEntry, PolyglotBook};
+use analyzer::r#move::Move;
+use analyzer::setup::Setup;
+
+const GAMES: &str = r#"[White "Anna"]
+[Black "Ben"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6 4. O-O 1-0
+
+[White "Ben"]
+[Black "Anna"]
+[Result "0-1"]
+
+1. d4 d5 2. c4 e6 0-1
+
+[White "Anna"]
+[Black "Carl"]
+[Result "1/2-1/2"]
+
+1. e4 c5 2. Nf3 1/2-1/2
+
+[White "Carl"]
+[Black "Ben"]
+[Result "0-1"]
+
+1. e4 e5 2. Bc4 0-1
+"#;
+
+fn build(filter: BookFilter) -> (BookBuilder, PolyglotBook) {
+    let mut builder = BookBuilder::new(filter);
+    for game in Pgn::parse_all(GAMES.to_string()) {
+        builder.add_game(&game);
+    }
+    let book = builder.build();
+    (builder, book)
+}
+
+/// Book moves after `moves` as SAN and weight.
+fn book_moves(book: &PolyglotBook, moves: &[&str]) -> Vec<(String, u16)> {
+    let mut board = Board::init();
+    for (index, uci) in moves.iter().enumerate() {
+        let mov = Move::from_uci(&board, uci, index as u16).unwrap();
+        board.apply_move(&mov);
+    }
+    book_moves_at(book, &board, moves.len() as u16)
+}
+
+/// Book moves in `board` at ply `index` as SAN and weight.
+fn book_moves_at(book: &PolyglotBook, board: &Board, index: u16) -> Vec<(String, u16)> {
+    book.moves(board, index)
+        .into_iter()
+        .map(|book| (book.mov.san, book.weight))
+        .collect()
+}
+
+fn moves(expected: &[(&str, u16)]) -> Vec<(String, u16)> {
+    expected.iter().map(|&(san, weight)| (san.to_string(), weight)).collect()
+}
+
+#[test]
+fn test_weights_are_half_points_of_the_mover() {
+    let (builder, book) = build(BookFilter::default());
+    assert_eq!(builder.games(), 4);
+    // 1. e4: a win, a draw and a loss; 1. d4: a loss
+    assert_eq!(book_moves(&book, &[]), moves(&[("e4", 3), ("d4", 0)]));
+    // Black scored a loss and a win with 1...e5 and a draw with 1...c5
+    assert_eq!(book_moves(&book, &["e2e4"]), moves(&[("e5", 2), ("c5", 1)]));
+
+    let key = zobrist_key(&Board::init(), Color::White);
+    let stats = builder.stats(key, BookEntry::encode_move("e2e4").unwrap()).unwrap();
+    assert_eq!(stats, MoveStats { games: 3, wins: 1, draws: 1, losses: 1 });
+}
+
+#[test]
+fn test_castling_is_stored_as_king_takes_rook() {
+    let (_, book) = build(BookFilter::default());
+    let before = ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"];
+    assert_eq!(book_moves(&book, &before), moves(&[("O-O", 2)]));
+    let mut board = Board::init();
+    for (index, uci) in before.iter().enumerate() {
+        board.apply_move(&Move::from_uci(&board, uci, index as u16).unwrap());
+    }
+    let entries = book.entries(zobrist_key(&board, Color::White));
+    assert_eq!(entries[0].uci(), "e1h1");
+}
+
+#[test]
+fn test_depth_and_minimum_games() {
+    let (_, book) = build(BookFilter { max_plies: 1, ..Default::default() });
+    assert_eq!(book.len(), 2);
+    assert!(book_moves(&book, &["e2e4"]).is_empty());
+
+    let (_, book) = build(BookFilter { min_games: 2, ..Default::default() });
+    assert_eq!(book_moves(&book, &[]), moves(&[("e4", 3)]));
+    assert_eq!(book_moves(&book, &["e2e4"]), moves(&[("e5", 2)]));
+    assert!(book_moves(&book, &["e2e4", "e7e5"]).is_empty());
+}
+
+#[test]
+fn test_player_and_color_filters() {
+    let anna = BookFilter { player: Some("Anna".to_string()), ..Default::default() };
+    let (builder, book) = build(anna.clone());
+    assert_eq!(builder.games(), 3);
+    // only Anna's moves: none by her opponents
+    assert_eq!(book_moves(&book, &[]), moves(&[("e4", 3)]));
+    assert!(book_moves(&book, &["e2e4"]).is_empty());
+    assert_eq!(book_moves(&book, &["d2d4"]), moves(&[("d5", 2)]));
+
+    let (builder, book) = build(BookFilter { color: Some(Color::Black), ..anna });
+    assert_eq!(builder.games(), 1);
+    assert!(book_moves(&book, &[]).is_empty());
+    assert_eq!(book_moves(&book, &["d2d4"]), moves(&[("d5", 2)]));
+
+    let (builder, book) = build(BookFilter { color: Some(Color::Black), ..Default::default() });
+    assert_eq!(builder.games(), 4);
+    assert!(book_moves(&book, &[]).is_empty());
+    assert_eq!(book_moves(&book, &["e2e4"]), moves(&[("e5", 2), ("c5", 1)]));
+}
+
+#[test]
+fn test_games_from_a_fen_tag_are_keyed_from_there() {
+    let mut builder = BookBuilder::new(BookFilter::default());
+    let endgame = "[Result \"1-0\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]\n\n1... Kd7 2. e4 1-0";
+    assert!(builder.add_game(&Pgn::new(endgame.to_string())));
+    assert!(!builder.add_game(&Pgn::new("[FEN \"8/8/8/8 w - - 0 1\"]\n\n1. e4 *".to_string())));
+    assert_eq!(builder.games(), 1);
+    let book = builder.build();
+
+    let start = Setup::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap();
+    let mut board = start.board().clone();
+    assert_eq!(book_moves_at(&book, &board, 1), moves(&[("Kd7", 0)]));
+    board.apply_move(&Move::from_uci(&board, "e8d7", 1).unwrap());
+    assert_eq!(book_moves_at(&book, &board, 2), moves(&[("e4", 2)]));
+    assert!(book_moves(&book, &[]).is_empty());
+}
@@ -0,0 +1,117 @@
+use analyzer::board::Board;
+use analyzer::chess960::{back_rank_pieces, position_number, start_position, STANDARD_POSITION};
+use analyzer::color::Color;
+use analyzer::components::{ColoredPiece, Piece};
+use analyzer::engine::analysis::Analysis;
+use analyzer::engine::backend::SearchLimits;
+use analyzer::engine::mock::MockBackend;
+use analyzer::engine::search::NativeEngine;
+use analyzer::fen::{extract_castling_rights, extract_shredder_castling_rights};
+use analyzer::game_analysis::analyse_game;
+use analyzer::pgn::Pgn;
+use analyzer::r#move::{Castling, Move};
+use analyzer::setup::Setup;
+use analyzer::square::Square;
+
+#[test]
+fn test_start_position_numbers() {
+    use Piece::*;
+    assert_eq!(back_rank_pieces(STANDARD_POSITION).unwrap(), [Rook, Knight, Bishop, Queen, King, Bishop, Knight, Rook]);
+    assert_eq!(back_rank_pieces(0).unwrap(), [Bishop, Bishop, Queen, Knight, Knight, Rook, King, Rook]);
+    assert_eq!(back_rank_pieces(959).unwrap(), [Rook, King, Rook, Knight, Knight, Queen, Bishop, Bishop]);
+    assert!(back_rank_pieces(960).is_err());
+    assert!(start_position(960).is_err());
+
+    for number in [0, 1, 99, 518, 959] {
+        assert_eq!(position_number(&start_position(number).unwrap()), Some(number));
+    }
+    assert_eq!(position_number(&Board::init()), Some(STANDARD_POSITION));
+}
+
+#[test]
+fn test_shredder_and_x_fen_castling() {
+    let setup = Setup::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9").unwrap();
+    assert!(setup.board().chess960);
+    assert_eq!(extract_shredder_castling_rights(setup.board()), "HFhf");
+    assert_eq!(extract_castling_rights(setup.board()), "KQkq");
+
+    // a second rook beyond the castling one needs its file in X-FEN
+    let inner = Setup::from_fen("4k3/8/8/8/8/8/8/R1R1K3 w C - 0 1").unwrap();
+    assert_eq!(extract_castling_rights(inner.board()), "C");
+    assert_eq!(extract_shredder_castling_rights(inner.board()), "C");
+
+    let standard = Setup::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+    assert!(!standard.board().chess960);
+    assert_eq!(extract_shredder_castling_rights(standard.board()), "HAha");
+}
+
+#[test]
+fn test_castling_with_king_next_to_rook() {
+    // king b1 and rook a1: queenside castling swaps them to c1 and d1
+    let setup = Setup::from_fen("rk5r/8/8/8/8/8/8/RK5R w KQkq - 0 1").unwrap();
+    let board = setup.clone().build().unwrap();
+    assert!(board.chess960);
+
+    let moves = board.legal_moves(0);
+    assert!(moves.iter().any(|mov| mov.san == "O-O-O"));
+    assert!(moves.iter().any(|mov| mov.san == "O-O"));
+
+    let queenside = Move::from_uci(&board, "b1a1", 0).unwrap();
+    assert_eq!(queenside.castling, Some(Castling::Queen));
+    assert_eq!(queenside.to_uci_on(&board).unwrap(), "b1a1");
+    // the standard king move is not castling in Chess960
+    assert!(Move::from_uci(&board, "b1d1", 0).is_err());
+
+    let mut after = board.clone();
+    after.apply_move(&queenside);
+    assert_eq!(after.piece_at(Square::C1), Some(ColoredPiece::new(Color::White, Piece::King)));
+    assert_eq!(after.piece_at(Square::D1), Some(ColoredPiece::new(Color::White, Piece::Rook)));
+    assert_eq!(after.piece_at(Square::A1), None);
+    assert_eq!(after.piece_at(Square::B1), None);
+    assert_eq!(extract_castling_rights(&after), "kq");
+
+    // kingside the king crosses c1 to g1, which an attacked square blocks
+    let attacked = setup.put(Square::E8, ColoredPiece::new(Color::Black, Piece::Rook)).build().unwrap();
+    let moves = attacked.legal_moves(0);
+    assert!(!moves.iter().any(|mov| mov.san == "O-O"));
+    assert!(moves.iter().any(|mov| mov.san == "O-O-O"));
+}
+
+#[test]
+fn test_pgn_variant_replays_castling() {
+    let mut pgn = Pgn::new(String::from(
+        "[Variant \"Chess960\"]\n[FEN \"rk5r/pppppppp/8/8/8/8/PPPPPPPP/RK5R w KQkq - 0 5\"]\n\n5. O-O-O O-O-O 6. Kb1 *",
+    ));
+    assert!(pgn.is_chess960());
+    assert_eq!(pgn.moves[0].index, 8);
+
+    let positions = pgn.uci_positions();
+    assert_eq!(positions.len(), 3);
+    let command = positions[2].to_command();
+    assert!(command.starts_with("position fen rk5r/"), "{}", command);
+    assert!(command.ends_with("moves b1a1 b8a8 c1b1"), "{}", command);
+
+    let fens = pgn.parse_moves();
+    assert!(fens[1].starts_with("2kr3r/pppppppp/8/8/8/8/PPPPPPPP/2KR3R w - -"), "{}", fens[1]);
+    assert!(fens[2].starts_with("2kr3r/pppppppp/8/8/8/8/PPPPPPPP/1K1R3R b - -"), "{}", fens[2]);
+}
+
+#[test]
+fn test_chess960_games_switch_the_engine_over() {
+    let game = "[Variant \"Chess960\"]\n[FEN \"rk5r/pppppppp/8/8/8/8/PPPPPPPP/RK5R w KQkq - 0 5\"]\n\n5. O-O-O *";
+    let mut analysis = Analysis::default();
+    analysis.record_info("info depth 10 score cp 0 pv b1a1");
+    analysis.record_best_move("bestmove b1a1");
+
+    let mut backend = MockBackend::new(vec![analysis]);
+    analyse_game(&mut backend, &mut Pgn::new(String::from(game)), &SearchLimits::depth(10)).unwrap();
+    assert!(backend.chess960);
+    analyse_game(&mut backend, &mut Pgn::new(String::from("1. e4 *")), &SearchLimits::depth(10)).unwrap();
+    assert!(!backend.chess960);
+
+    // the native search plays Chess960 too, castling as the king taking its rook
+    let mut engine = NativeEngine::new();
+    let plies = analyse_game(&mut engine, &mut Pgn::new(String::from(game)), &SearchLimits::depth(1)).unwrap();
+    assert_eq!(plies[0].uci, "b1a1");
+    assert!(!plies[0].before.best_move.is_empty());
+}
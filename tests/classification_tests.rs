@@ -0,0 +1,172 @@
+use analyzer::classification::{review_game, ClassificationThresholds, MoveClass, MoveReview};
+use analyzer::engine::analysis::{Analysis, Score};
+use analyzer::engine::backend::SearchLimits;
+use analyzer::engine::mock::MockBackend;
+use analyzer::engine::position::UciPosition;
+use analyzer::game_analysis::{analyse_game, PlyAnalysis};
+use analyzer::pgn::Pgn;
+
+fn analysis(best_move: &str, score: &str) -> Analysis {
+    Analysis::from_uci_lines([
+        format!("info depth 12 score {} pv {}", score, best_move),
+        format!("bestmove {}", best_move),
+    ])
+}
+
+/// A ply where `uci` was played; `before` and `after` are engine scores from
+/// the point of view of the side to move in each position.
+fn ply(uci: &str, best_move: &str, before: &str, after: &str) -> PlyAnalysis {
+    PlyAnalysis {
+        ply: 0,
+        san: uci.to_string(),
+        uci: uci.to_string(),
+        clock: None,
+        position: UciPosition::startpos(),
+        before: analysis(best_move, before),
+        analysis: analysis("a7a6", after),
+        book: false,
+        tablebase: None,
+    }
+}
+
+fn judge(ply: &PlyAnalysis) -> MoveReview {
+    MoveReview::from_ply(ply, &ClassificationThresholds::default()).unwrap()
+}
+
+#[test]
+fn test_thresholds() {
+    let thresholds = ClassificationThresholds::default();
+    let classes: Vec<MoveClass> = [0, 5, 20, 49, 50, 100, 299, 300, 900]
+        .iter()
+        .map(|&loss| thresholds.classify(loss))
+        .collect();
+    assert_eq!(
+        classes,
+        [
+            MoveClass::Best,
+            MoveClass::Excellent,
+            MoveClass::Good,
+            MoveClass::Good,
+            MoveClass::Inaccuracy,
+            MoveClass::Mistake,
+            MoveClass::Mistake,
+            MoveClass::Blunder,
+            MoveClass::Blunder,
+        ]
+    );
+
+    let strict = ClassificationThresholds { blunder: 150, ..Default::default() };
+    assert_eq!(strict.classify(200), MoveClass::Blunder);
+}
+
+#[test]
+fn test_evaluations_are_from_the_movers_point_of_view() {
+    let review = judge(&ply("e2e4", "d2d4", "cp 30", "cp 400"));
+    assert_eq!(review.eval_before, Score::Centipawns(30));
+    assert_eq!(review.eval_after, Score::Centipawns(-400));
+    assert_eq!(review.cp_loss, 430);
+    assert_eq!(review.class, MoveClass::Blunder);
+    assert_eq!(review.best_move, "d2d4");
+}
+
+#[test]
+fn test_engine_move_is_best() {
+    // a shallower search after the move may disagree slightly
+    let review = judge(&ply("e2e4", "e2e4", "cp 30", "cp -10"));
+    assert_eq!(review.cp_loss, 0);
+    assert_eq!(review.class, MoveClass::Best);
+
+    // another move that turns out at least as good
+    let review = judge(&ply("g1f3", "e2e4", "cp 30", "cp -50"));
+    assert_eq!(review.cp_loss, 0);
+    assert_eq!(review.class, MoveClass::Best);
+}
+
+#[test]
+fn test_mate_scores() {
+    // mate in 3 becomes mate in 5: still winning
+    let review_slower = judge(&ply("d1h5", "d1f7", "mate 3", "mate -5"));
+    assert_eq!(review_slower.eval_after, Score::Mate(5));
+    assert_eq!(review_slower.class, MoveClass::Excellent);
+
+    // letting a forced mate slip is a blunder, however large the advantage left
+    assert_eq!(judge(&ply("d1h5", "d1f7", "mate 2", "cp -800")).class, MoveClass::Blunder);
+
+    // allowing a forced mate is a blunder
+    let allowed = judge(&ply("f2f3", "e2e4", "cp 10", "mate 2"));
+    assert_eq!(allowed.eval_after, Score::Mate(-2));
+    assert_eq!(allowed.class, MoveClass::Blunder);
+
+    // engines report a position without moves with a score but no pv
+    let no_moves = |score: &str| {
+        Analysis::from_uci_lines([format!("info depth 0 score {}", score), String::from("bestmove (none)")])
+    };
+
+    // delivering mate
+    let mate = judge(&PlyAnalysis { analysis: no_moves("mate 0"), ..ply("d8h4", "d8h4", "mate 1", "cp 0") });
+    assert_eq!(mate.eval_after, Score::Mate(0));
+    assert_eq!(mate.class, MoveClass::Best);
+
+    // stalemating a won position throws the win away
+    let stalemate = judge(&PlyAnalysis { analysis: no_moves("cp 0"), ..ply("f6f7", "f6g6", "cp 900", "cp 0") });
+    assert_eq!(stalemate.eval_after, Score::Centipawns(0));
+    assert_eq!(stalemate.class, MoveClass::Blunder);
+}
+
+#[test]
+fn test_won_positions_are_clamped() {
+    assert_eq!(judge(&ply("a2a3", "d1d8", "cp 1500", "cp -1100")).class, MoveClass::Best);
+    assert_eq!(judge(&ply("a2a3", "d1d8", "cp 1500", "cp -600")).class, MoveClass::Blunder);
+}
+
+#[test]
+fn test_brilliant_sacrifice() {
+    // Bxh7+ gives the bishop for a pawn unless a knight on g5 guards it
+    let sacrifice = |fen: &str, uci: &str, before: &str| {
+        let mut position = UciPosition::from_fen(fen);
+        position.push(uci);
+        PlyAnalysis { position, ..ply(uci, uci, before, "cp -40") }
+    };
+    let open = "6k1/5ppp/8/8/8/3B4/5PPP/6K1 w - - 0 1";
+    let guarded = "6k1/5ppp/8/6N1/8/3B4/5PPP/6K1 w - - 0 1";
+    assert_eq!(judge(&sacrifice(open, "d3h7", "cp 40")).class, MoveClass::Brilliant);
+    assert_eq!(judge(&sacrifice(open, "d3h7", "cp 40")).class.nag(), Some(3));
+    // with a won game the sacrifice is only best
+    assert_eq!(judge(&sacrifice(open, "d3h7", "cp 800")).class, MoveClass::Best);
+    // a safe retreat is not a sacrifice
+    assert_eq!(judge(&sacrifice(open, "d3e2", "cp 40")).class, MoveClass::Best);
+    // nor is a pawn push, or giving a piece the king cannot take
+    assert_eq!(judge(&sacrifice(open, "h2h3", "cp 40")).class, MoveClass::Best);
+    assert_eq!(judge(&sacrifice(guarded, "d3h7", "cp 40")).class, MoveClass::Best);
+}
+
+#[test]
+fn test_review_game() {
+    // the engine likes White's position after e4 and hates it after f3
+    let mut backend = MockBackend::with_responder(|position, _| {
+        Ok(match position.moves.len() {
+            0 => analysis("e2e4", "cp 30"),
+            1 => analysis("e7e5", "cp -25"),
+            2 => analysis("g1f3", "cp 40"),
+            _ => analysis("d8h4", "mate 1"),
+        })
+    });
+    let mut pgn = Pgn::new("1. e4 e5 2. f3".to_string());
+    let plies = analyse_game(&mut backend, &mut pgn, &SearchLimits::depth(12)).unwrap();
+
+    let reviews = review_game(&plies, &ClassificationThresholds::default());
+    let classes: Vec<(&str, MoveClass)> = reviews.iter().map(|r| (r.san.as_str(), r.class)).collect();
+    assert_eq!(
+        classes,
+        [("e4", MoveClass::Best), ("e5", MoveClass::Best), ("f3", MoveClass::Blunder)]
+    );
+    assert_eq!(reviews[2].eval_after, Score::Mate(-1));
+}
+
+#[test]
+fn test_moves_without_scores_are_skipped() {
+    let mut unscored = ply("e2e4", "e2e4", "cp 0", "cp 0");
+    unscored.analysis = Analysis::from_uci_lines(["bestmove e7e5"]);
+    assert!(MoveReview::from_ply(&unscored, &ClassificationThresholds::default()).is_none());
+    assert!(review_game(&[unscored], &ClassificationThresholds::default()).is_empty());
+}
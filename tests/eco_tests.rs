@@ -0,0 +1,76 @@
+use analyzer::eco::EcoTable;
+use analyzer::error::ChessError;
+use analyzer::pgn::Pgn;
+
+fn game(movetext: &str) -> Pgn {
+    Pgn::new(movetext.to_string())
+}
+
+#[test]
+fn test_builtin_lines_classify_as_themselves() {
+    let table = EcoTable::builtin();
+    assert!(table.len() > 100);
+    for opening in table.openings() {
+        let matched = table.classify(&game(&opening.moves)).unwrap();
+        assert_eq!(matched.plies, Pgn::new(opening.moves.clone()).moves.len(), "{}", opening.name);
+        assert_eq!(matched.out_of_book, None, "{}", opening.name);
+    }
+}
+
+#[test]
+fn test_deepest_named_position_wins() {
+    let table = EcoTable::builtin();
+    let matched = table
+        .classify(&game("1. e4 c5 2. Nf3 d6 3. d4 cxd4 4. Nxd4 Nf6 5. Nc3 a6 6. Be3 e5 *"))
+        .unwrap();
+    assert_eq!(matched.opening.eco, "B90");
+    assert_eq!(matched.opening.family(), "Sicilian Defense");
+    assert_eq!(matched.opening.variation(), Some("Najdorf Variation"));
+    assert_eq!(matched.plies, 10);
+    assert_eq!(matched.out_of_book, Some(10));
+    assert_eq!(matched.out_of_book_move().as_deref(), Some("6."));
+}
+
+#[test]
+fn test_transpositions_are_recognised() {
+    let table = EcoTable::builtin();
+    let london = table.classify(&game("1. Nf3 d5 2. d4 Nf6 3. Bf4 e6 *")).unwrap();
+    assert_eq!(london.opening.name, "Queen's Pawn Game: London System");
+    assert_eq!(london.out_of_book_move().as_deref(), Some("3..."));
+
+    let kings_indian = table.classify(&game("1. c4 g6 2. d4 Nf6 3. Nc3 Bg7 4. e4 d6 5. Nf3 c5 *")).unwrap();
+    assert_eq!(kings_indian.opening.eco, "E90");
+    assert_eq!(kings_indian.out_of_book_move().as_deref(), Some("5..."));
+}
+
+#[test]
+fn test_unknown_openings() {
+    let table = EcoTable::builtin();
+    assert!(table.classify(&game("1. a3 e5 *")).is_none());
+    assert!(table.classify(&game("*")).is_none());
+}
+
+#[test]
+fn test_tags() {
+    let table = EcoTable::builtin();
+    let mut pgn = game("[Event \"Club\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 *");
+    table.classify(&pgn).unwrap().set_tags(&mut pgn.headers);
+    assert_eq!(pgn.headers.tag("ECO"), Some("C60"));
+    assert_eq!(pgn.headers.tag("Opening"), Some("Ruy Lopez"));
+    assert_eq!(pgn.headers.tag("Variation"), Some("Morphy Defense"));
+}
+
+#[test]
+fn test_custom_tables() {
+    let table = EcoTable::parse("# comment\n\nX01\tMy Gambit\t1. e4 e5 2. d4\n").unwrap();
+    assert_eq!(table.len(), 1);
+    let matched = table.classify(&game("1. e4 e5 2. d4 exd4 *")).unwrap();
+    assert_eq!(matched.opening.name, "My Gambit");
+
+    for bad in ["X01\tMissing moves\n", "X01\tIllegal\t1. e4 e5 2. Ke3\n"] {
+        match EcoTable::parse(bad) {
+            Err(ChessError::InvalidMove { move_str }) => assert!(move_str.contains("line 1"), "{}", move_str),
+            other => panic!("{:?}", other.map(|table| table.len())),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use analyzer::engine::options::{EngineInfo, EngineOption, OptionKind};
+use analyzer::error::ChessError;
+
+const HANDSHAKE: [&str; 9] = [
+    "id name Stockfish 16",
+    "id author the Stockfish developers (see AUTHORS file)",
+    "option name Debug Log File type string default",
+    "option name Threads type spin default 1 min 1 max 1024",
+    "option name Ponder type check default false",
+    "option name Clear Hash type button",
+    "option name Analysis Contempt type combo default Both var Off var White var Black var Both",
+    "option name SyzygyPath type string default <empty>",
+    "uciok",
+];
+
+#[test]
+fn test_handshake_identity() {
+    let info = EngineInfo::from_handshake(HANDSHAKE);
+    assert_eq!(info.name.as_deref(), Some("Stockfish 16"));
+    assert_eq!(info.author.as_deref(), Some("the Stockfish developers (see AUTHORS file)"));
+    assert_eq!(info.options.len(), 6);
+}
+
+#[test]
+fn test_option_types() {
+    let info = EngineInfo::from_handshake(HANDSHAKE);
+
+    assert_eq!(
+        info.option("Threads").unwrap().kind,
+        OptionKind::Spin { default: 1, min: 1, max: 1024 }
+    );
+    assert_eq!(info.option("Ponder").unwrap().kind, OptionKind::Check { default: false });
+    assert_eq!(info.option("Clear Hash").unwrap().kind, OptionKind::Button);
+    assert_eq!(
+        info.option("Analysis Contempt").unwrap().kind,
+        OptionKind::Combo {
+            default: "Both".to_string(),
+            vars: vec!["Off".to_string(), "White".to_string(), "Black".to_string(), "Both".to_string()],
+        }
+    );
+    assert_eq!(
+        info.option("SyzygyPath").unwrap().kind,
+        OptionKind::String { default: String::new() }
+    );
+    assert_eq!(
+        info.option("Debug Log File").unwrap().kind,
+        OptionKind::String { default: String::new() }
+    );
+}
+
+#[test]
+fn test_option_lookup_is_case_insensitive() {
+    let info = EngineInfo::from_handshake(HANDSHAKE);
+    assert!(info.option("threads").is_some());
+    assert!(info.option("CLEAR HASH").is_some());
+    assert!(info.option("Hash").is_none());
+}
+
+#[test]
+fn test_validate_setoption() {
+    let info = EngineInfo::from_handshake(HANDSHAKE);
+
+    assert!(info.validate("Threads", Some("8")).is_ok());
+    assert!(info.validate("Threads", Some("0")).is_err());
+    assert!(info.validate("Threads", Some("many")).is_err());
+    assert!(info.validate("Threads", None).is_err());
+    assert!(info.validate("Ponder", Some("true")).is_ok());
+    assert!(info.validate("Ponder", Some("yes")).is_err());
+    assert!(info.validate("Clear Hash", None).is_ok());
+    assert!(info.validate("Clear Hash", Some("1")).is_err());
+    assert!(info.validate("Analysis Contempt", Some("white")).is_ok());
+    assert!(info.validate("Analysis Contempt", Some("Green")).is_err());
+    assert!(info.validate("SyzygyPath", Some("/tb")).is_ok());
+
+    match info.validate("Hash", Some("64")) {
+        Err(ChessError::InvalidEngineOption { name, .. }) => assert_eq!(name, "Hash"),
+        other => panic!("Expected InvalidEngineOption, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_invalid_option_lines() {
+    assert!(EngineOption::parse("option type spin default 1 min 1 max 2").is_none());
+    assert!(EngineOption::parse("option name Foo type dial default 1").is_none());
+    assert!(EngineOption::parse("option name Foo type spin default x min 1 max 2").is_none());
+}
@@ -0,0 +1,200 @@
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use analyzer::config::EngineConfig;
+use analyzer::engine::analysis::Analysis;
+use analyzer::engine::backend::{EngineBackend, SearchLimits};
+use analyzer::engine::mock::MockBackend;
+use analyzer::engine::pool::EnginePool;
+use analyzer::engine::position::UciPosition;
+use analyzer::error::ChessError;
+use analyzer::pgn::Pgn;
+
+/// Answers with the last move of the position, sleeping longer for earlier
+/// positions so workers finish out of order.
+fn echo_backend() -> Box<dyn EngineBackend + Send> {
+    Box::new(MockBackend::with_responder(|position, _| {
+        thread::sleep(Duration::from_millis(40u64.saturating_sub(position.moves.len() as u64 * 10)));
+        let mut analysis = Analysis::default();
+        analysis.record_best_move(&format!("bestmove {}", position.moves.last().map_or("0000", |m| m)));
+        Ok(analysis)
+    }))
+}
+
+fn positions(count: usize) -> Vec<UciPosition> {
+    let moves = ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"];
+    (1..=count)
+        .map(|n| {
+            let mut position = UciPosition::startpos();
+            for uci in &moves[..n] {
+                position.push(uci);
+            }
+            position
+        })
+        .collect()
+}
+
+#[test]
+fn test_positions_keep_input_order() {
+    let pool = EnginePool::new(|| Ok(echo_backend()), 3);
+    let results = pool.analyse_positions(positions(5), &SearchLimits::depth(1));
+
+    let best_moves: Vec<String> = results.into_iter().map(|r| r.unwrap().best_move).collect();
+    assert_eq!(best_moves, ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"]);
+}
+
+#[test]
+fn test_games_keep_input_order() {
+    let games = vec![
+        Pgn::new("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6".to_string()),
+        Pgn::new("1. d4".to_string()),
+        Pgn::new("1. c4 e5".to_string()),
+    ];
+    let pool = EnginePool::new(|| Ok(echo_backend()), 2);
+
+    let mut emitted = Vec::new();
+    pool.analyse_games_with(games, &SearchLimits::depth(1), |index, result| {
+        emitted.push((index, result.unwrap().len()));
+    });
+    assert_eq!(emitted, [(0, 6), (1, 1), (2, 2)]);
+}
+
+#[test]
+fn test_engines_started_lazily_per_worker() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&started);
+    let pool = EnginePool::new(
+        move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(echo_backend())
+        },
+        4,
+    );
+
+    pool.analyse_positions(positions(2), &SearchLimits::depth(1));
+    // only as many engines as there is work for
+    assert!(started.load(Ordering::SeqCst) <= 2);
+}
+
+#[test]
+fn test_crashed_engine_is_restarted() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&started);
+    let pool = EnginePool::new(
+        move || {
+            // the first engine crashes on its first search, the replacement works
+            let crashes = counter.fetch_add(1, Ordering::SeqCst) == 0;
+            let echo = echo_backend();
+            let mut echo = Some(echo);
+            Ok(Box::new(MockBackend::with_responder(move |position, limits| {
+                if crashes {
+                    return Err(ChessError::EngineTerminated { waiting_for: "bestmove".to_string() });
+                }
+                echo.as_mut().unwrap().analyse(position, limits)
+            })) as Box<dyn EngineBackend + Send>)
+        },
+        1,
+    );
+
+    let results = pool.analyse_positions(positions(3), &SearchLimits::depth(1));
+    assert!(results.iter().all(|r| r.is_ok()));
+    assert_eq!(results[0].as_ref().unwrap().best_move, "e2e4");
+    assert_eq!(started.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_retries_are_limited() {
+    let pool = EnginePool::new(
+        || {
+            Ok(Box::new(MockBackend::with_responder(|_, _| {
+                Err(ChessError::EngineTimeout { waiting_for: "bestmove".to_string(), timeout_ms: 10 })
+            })) as Box<dyn EngineBackend + Send>)
+        },
+        2,
+    )
+    .with_max_retries(1);
+
+    let results = pool.analyse_positions(positions(2), &SearchLimits::depth(1));
+    assert!(results
+        .iter()
+        .all(|r| matches!(r, Err(ChessError::EngineTimeout { .. }))));
+}
+
+#[test]
+fn test_other_errors_are_not_retried() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&started);
+    let pool = EnginePool::new(
+        move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(MockBackend::with_responder(|_, _| {
+                Err(ChessError::InvalidEngineOption { name: "Hash".to_string(), reason: "test".to_string() })
+            })) as Box<dyn EngineBackend + Send>)
+        },
+        1,
+    );
+
+    let results = pool.analyse_positions(positions(1), &SearchLimits::depth(1));
+    assert!(matches!(results[0], Err(ChessError::InvalidEngineOption { .. })));
+    assert_eq!(started.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_uci_pool_sets_threads_and_hash() {
+    let dir = std::env::temp_dir().join(format!("analyzer-pool-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("pool.uci");
+    fs::write(
+        &script,
+        "id name PoolMock\n\
+         option name Threads type spin default 1 min 1 max 64\n\
+         option name Hash type spin default 16 min 1 max 4096\n\
+         [go]\n\
+         info depth 3 score cp 12 pv d2d4\n\
+         bestmove d2d4\n",
+    )
+    .unwrap();
+    let logs: Vec<_> = (0..2).map(|i| dir.join(format!("pool-{}.log", i))).collect();
+    for log in &logs {
+        let _ = fs::remove_file(log);
+    }
+
+    // each engine of the pool logs to its own file
+    let next_log = Arc::new(AtomicUsize::new(0));
+    let log_paths = logs.clone();
+    let pool = EnginePool::new(
+        move || {
+            let log = &log_paths[next_log.fetch_add(1, Ordering::SeqCst) % 2];
+            let config = EngineConfig {
+                depth: 3,
+                timeout_ms: Some(2000),
+                engine_path: Some(env!("CARGO_BIN_EXE_mock_uci").to_string()),
+                engine_args: vec![
+                    script.to_string_lossy().into_owned(),
+                    log.to_string_lossy().into_owned(),
+                ],
+                threads: Some(2),
+                hash_mb: Some(128),
+            };
+            let engine = analyzer::engine::engine::Engine::with_config(&config)?;
+            Ok(Box::new(engine) as Box<dyn EngineBackend + Send>)
+        },
+        2,
+    );
+
+    let games = vec![Pgn::new("1. e4 e5".to_string()), Pgn::new("1. d4 d5".to_string())];
+    let results = pool.analyse_games(games, &SearchLimits::depth(3));
+    assert!(results.iter().all(|r| r.as_ref().unwrap().len() == 2));
+
+    let mut commands = String::new();
+    for log in &logs {
+        commands.push_str(&fs::read_to_string(log).unwrap_or_default());
+    }
+    assert!(commands.contains("setoption name Threads value 2"));
+    assert!(commands.contains("setoption name Hash value 128"));
+    assert!(commands.contains("ucinewgame"));
+    assert!(commands.contains("position startpos moves d2d4 d7d5"));
+}
@@ -0,0 +1,64 @@
+use analyzer::board::Board;
+use analyzer::color::Color;
+use analyzer::evaluation::{evaluate, EvalTerm, MAX_PHASE};
+use analyzer::r#move::Move;
+
+/// Plays UCI moves from the starting position and returns the board.
+fn play(moves: &[&str]) -> Board {
+    let mut board = Board::init();
+    for (index, uci) in moves.iter().enumerate() {
+        let mov = Move::from_uci(&board, uci, index as u16).unwrap();
+        board.apply_move(&mov);
+    }
+    board
+}
+
+#[test]
+fn test_symmetric_positions_are_level() {
+    for moves in [&[][..], &["e2e4", "e7e5"], &["g1f3", "g8f6", "d2d4", "d7d5"]] {
+        let evaluation = evaluate(&play(moves));
+        assert_eq!(evaluation.phase, MAX_PHASE);
+        assert_eq!(evaluation.total(), 0, "{:?}", moves);
+        for score in &evaluation.terms {
+            assert_eq!(score.white, score.black, "{} after {:?}", score.term.name(), moves);
+        }
+    }
+}
+
+#[test]
+fn test_breakdown_adds_up() {
+    let evaluation = evaluate(&play(&["e2e4", "d7d5", "e4d5"]));
+    assert_eq!(evaluation.terms.iter().map(|score| score.term).collect::<Vec<_>>(), EvalTerm::ALL);
+    assert_eq!(evaluation.total(), evaluation.terms.iter().map(|score| score.net()).sum::<i32>());
+    assert_eq!(evaluation.relative(Color::Black), -evaluation.total());
+
+    // a pawn up, with all pieces on the board the middlegame value counts
+    assert_eq!(evaluation.term(EvalTerm::Material).net(), 82);
+    assert!(evaluation.total() > 0);
+
+    let table = evaluation.to_string();
+    assert!(table.lines().next().unwrap().starts_with("term"));
+    assert!(table.contains("pawn-structure"));
+    assert!(table.contains("+0.82"));
+}
+
+#[test]
+fn test_pawn_structure_and_phase() {
+    // the exchange variation of the Ruy Lopez doubles Black's c-pawns
+    let board = play(&["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5c6", "d7c6"]);
+    let evaluation = evaluate(&board);
+    assert_eq!(evaluation.phase, MAX_PHASE - 2);
+    let pawns = evaluation.term(EvalTerm::PawnStructure);
+    assert_eq!(pawns.white, 0);
+    assert!(pawns.black < 0);
+    // bishops are worth more than knights while the board is full
+    assert!(evaluation.term(EvalTerm::Material).net() < 0);
+}
+
+#[test]
+fn test_developed_pieces_are_more_mobile() {
+    let evaluation = evaluate(&play(&["g1f3", "a7a6", "b1c3", "h7h6"]));
+    let mobility = evaluation.term(EvalTerm::Mobility);
+    assert!(mobility.white > mobility.black);
+    assert!(evaluation.term(EvalTerm::PieceSquares).net() > 0);
+}
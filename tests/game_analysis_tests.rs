@@ -0,0 +1,91 @@
+use analyzer::engine::analysis::Analysis;
+use analyzer::engine::backend::{EngineBackend, SearchLimits};
+use analyzer::engine::mock::MockBackend;
+use analyzer::error::ChessError;
+use analyzer::game_analysis::analyse_game;
+use analyzer::pgn::Pgn;
+
+fn analysis(best_move: &str, cp: i32) -> Analysis {
+    let mut analysis = Analysis::default();
+    analysis.record_info(&format!("info depth 10 score cp {} pv {}", cp, best_move));
+    analysis.record_best_move(&format!("bestmove {}", best_move));
+    analysis
+}
+
+#[test]
+fn test_analyse_game_visits_every_position() {
+    let mut backend = MockBackend::new(vec![analysis("e2e4", 20), analysis("e7e5", 30), analysis("g1f3", 25)]);
+    let mut pgn = Pgn::new("1. e4 c5 2. Nf3 *".to_string());
+
+    let plies = analyse_game(&mut backend, &mut pgn, &SearchLimits::depth(12)).unwrap();
+
+    assert_eq!(backend.games, 1);
+    // the starting position is searched too
+    assert_eq!(backend.positions.len(), 4);
+    assert!(backend.positions[0].moves.is_empty());
+    assert_eq!(backend.positions[3].moves, ["e2e4", "c7c5", "g1f3"]);
+
+    assert_eq!(plies.len(), 3);
+    assert_eq!(plies[0].san, "e4");
+    assert_eq!(plies[0].before.best_move, "e2e4");
+    assert_eq!(plies[1].before, plies[0].analysis);
+    assert_eq!(plies[0].analysis.best_move, "e7e5");
+    assert_eq!(plies[1].uci, "c7c5");
+    // the last canned response repeats
+    assert_eq!(plies[2].analysis.best_move, "g1f3");
+}
+
+#[test]
+fn test_limits_are_passed_to_backend() {
+    let mut backend = MockBackend::with_responder(|position, limits| {
+        assert_eq!(limits.movetime_ms, Some(100));
+        Ok(analysis(if position.moves.len() % 2 == 1 { "e7e5" } else { "g1f3" }, 0))
+    });
+    let limits = SearchLimits {
+        movetime_ms: Some(100),
+        ..Default::default()
+    };
+    let mut pgn = Pgn::new("1. e4 e5".to_string());
+
+    let plies = analyse_game(&mut backend, &mut pgn, &limits).unwrap();
+    assert_eq!(plies[0].analysis.best_move, "e7e5");
+    assert_eq!(plies[1].analysis.best_move, "g1f3");
+}
+
+#[test]
+fn test_backend_errors_are_propagated() {
+    let mut backend = MockBackend::new(Vec::new());
+    let mut pgn = Pgn::new("1. e4 e5".to_string());
+    assert!(matches!(
+        analyse_game(&mut backend, &mut pgn, &SearchLimits::depth(1)),
+        Err(ChessError::EngineTerminated { .. })
+    ));
+}
+
+#[test]
+fn test_backends_are_interchangeable() {
+    let mut backends: Vec<Box<dyn EngineBackend>> = vec![
+        Box::new(MockBackend::new(vec![analysis("e7e5", 10)])),
+        Box::new(MockBackend::new(vec![analysis("c7c5", 20)])),
+    ];
+    let mut best_moves = Vec::new();
+    for backend in backends.iter_mut() {
+        let mut pgn = Pgn::new("1. e4".to_string());
+        let plies = analyse_game(backend.as_mut(), &mut pgn, &SearchLimits::depth(1)).unwrap();
+        best_moves.push(plies[0].analysis.best_move.clone());
+        backend.quit();
+    }
+    assert_eq!(best_moves, ["e7e5", "c7c5"]);
+}
+
+#[test]
+fn test_go_command() {
+    assert_eq!(SearchLimits::depth(16).to_go_command(), "go depth 16");
+    assert_eq!(SearchLimits::default().to_go_command(), "go depth 16");
+    let limits = SearchLimits {
+        depth: Some(20),
+        nodes: Some(5000),
+        movetime_ms: Some(250),
+    };
+    assert_eq!(limits.to_go_command(), "go depth 20 nodes 5000 movetime 250");
+}
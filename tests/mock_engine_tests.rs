@@ -0,0 +1,295 @@
+// Engine client tests against the scriptable mock engine (src/bin/mock_uci.rs),
+// so they run without Stockfish installed.
+
+use std::fs;
+use std::path::PathBuf;
+
+use analyzer::config::EngineConfig;
+use analyzer::engine::analysis::{Analysis, PvLine, Score};
+use analyzer::engine::engine::Engine;
+use analyzer::engine::position::UciPosition;
+use analyzer::error::ChessError;
+
+const HANDSHAKE: &str = "
+id name MockEngine 1.0
+id author analyzer tests
+option name Hash type spin default 16 min 1 max 1024
+option name MultiPV type spin default 1 min 1 max 500
+";
+
+const MULTIPV_SEARCH: &str = "
+[go]
+info depth 1 multipv 1 score cp 10 pv d2d4
+info string NNUE evaluation enabled
+info depth 12 currmove e2e4 currmovenumber 1
+info depth 12 seldepth 18 multipv 1 score cp 35 nodes 120000 nps 900000 time 133 pv e2e4 e7e5 g1f3
+info depth 12 seldepth 16 multipv 2 score cp 28 lowerbound nodes 120000 pv d2d4 d7d5
+info depth 12 seldepth 15 multipv 3 score mate -4 pv f2f3 e7e5
+bestmove e2e4 ponder e7e5
+";
+
+/// Writes a mock engine script and returns a config pointing at it, plus the command log path.
+fn mock_engine(name: &str, script: &str) -> (EngineConfig, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("analyzer-mock-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let script_path = dir.join(format!("{}.uci", name));
+    let log_path = dir.join(format!("{}.log", name));
+    fs::write(&script_path, script).unwrap();
+    let _ = fs::remove_file(&log_path);
+
+    let config = EngineConfig {
+        depth: 12,
+        timeout_ms: Some(2000),
+        engine_path: Some(env!("CARGO_BIN_EXE_mock_uci").to_string()),
+        engine_args: vec![
+            script_path.to_string_lossy().into_owned(),
+            log_path.to_string_lossy().into_owned(),
+        ],
+        ..Default::default()
+    };
+    (config, log_path)
+}
+
+fn sent_commands(log_path: &PathBuf) -> Vec<String> {
+    fs::read_to_string(log_path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_handshake() {
+    let (config, log) = mock_engine("handshake", &format!("{}{}", HANDSHAKE, MULTIPV_SEARCH));
+    let mut engine = Engine::with_config(&config).unwrap();
+
+    assert_eq!(engine.info().name.as_deref(), Some("MockEngine 1.0"));
+    assert_eq!(engine.info().author.as_deref(), Some("analyzer tests"));
+    assert_eq!(engine.info().options.len(), 2);
+
+    engine.quit();
+    assert_eq!(
+        sent_commands(&log),
+        ["uci", "setoption name MultiPV value 3", "isready", "quit"]
+    );
+}
+
+#[test]
+fn test_set_option_is_validated() {
+    let (config, log) = mock_engine("set_option", &format!("{}{}", HANDSHAKE, MULTIPV_SEARCH));
+    let mut engine = Engine::with_config(&config).unwrap();
+
+    assert!(engine.set_option("Hash", Some("256")).is_ok());
+    assert!(matches!(
+        engine.set_option("Hash", Some("4096")),
+        Err(ChessError::InvalidEngineOption { .. })
+    ));
+    assert!(matches!(
+        engine.set_option("Threads", Some("2")),
+        Err(ChessError::InvalidEngineOption { .. })
+    ));
+
+    engine.quit();
+    let commands = sent_commands(&log);
+    assert!(commands.contains(&"setoption name Hash value 256".to_string()));
+    assert!(!commands.iter().any(|c| c.contains("4096") || c.contains("Threads")));
+}
+
+#[test]
+fn test_position_protocol() {
+    let (config, log) = mock_engine("protocol", &format!("{}{}", HANDSHAKE, MULTIPV_SEARCH));
+    let mut engine = Engine::with_config(&config).unwrap();
+
+    engine.new_game().unwrap();
+    let mut position = UciPosition::startpos();
+    position.push("e2e4");
+    engine.process_position(&position).unwrap();
+    engine.quit();
+
+    let commands = sent_commands(&log);
+    assert_eq!(
+        commands[3..],
+        [
+            "ucinewgame",
+            "isready",
+            "position startpos moves e2e4",
+            "go depth 12",
+            "quit",
+        ]
+    );
+}
+
+#[test]
+fn test_multipv_parsing() {
+    let (config, _) = mock_engine("multipv", &format!("{}{}", HANDSHAKE, MULTIPV_SEARCH));
+    let mut engine = Engine::with_config(&config).unwrap();
+
+    let analysis = engine.process_position(&UciPosition::startpos()).unwrap();
+    engine.quit();
+
+    assert_eq!(analysis.best_move, "e2e4");
+    assert_eq!(analysis.ponder.as_deref(), Some("e7e5"));
+    assert_eq!(analysis.lines.len(), 3);
+    assert_eq!(
+        analysis.lines[0],
+        PvLine {
+            multipv: 1,
+            depth: 12,
+            score: Score::Centipawns(35),
+            pv: vec!["e2e4".to_string(), "e7e5".to_string(), "g1f3".to_string()],
+        }
+    );
+    assert_eq!(analysis.lines[1].score, Score::Centipawns(28));
+    assert_eq!(analysis.lines[2].score, Score::Mate(-4));
+    assert_eq!(analysis.score(), Some(Score::Centipawns(35)));
+}
+
+#[test]
+fn test_searches_replay_in_order() {
+    let script = format!(
+        "{}\n[go]\nbestmove e2e4\n[go]\ninfo depth 5 score mate 2 pv d8h4\nbestmove d8h4\n",
+        HANDSHAKE
+    );
+    let (config, _) = mock_engine("ordered", &script);
+    let mut engine = Engine::with_config(&config).unwrap();
+
+    assert_eq!(engine.process_fen("8/8/8/8/8/8/4k3/4K3 w - - 0 1").unwrap().best_move, "e2e4");
+    let second = engine.process_position(&UciPosition::startpos()).unwrap();
+    assert_eq!(second.best_move, "d8h4");
+    assert_eq!(second.score(), Some(Score::Mate(2)));
+    // the last block repeats once the script runs out
+    assert_eq!(engine.process_position(&UciPosition::startpos()).unwrap().best_move, "d8h4");
+    engine.quit();
+}
+
+#[test]
+fn test_search_timeout() {
+    let script = format!("{}\n[go]\ninfo depth 1 score cp 5 pv e2e4\n@hang\n", HANDSHAKE);
+    let (mut config, _) = mock_engine("timeout", &script);
+    config.timeout_ms = Some(200);
+    let mut engine = Engine::with_config(&config).unwrap();
+
+    match engine.process_position(&UciPosition::startpos()) {
+        Err(ChessError::EngineTimeout { waiting_for, timeout_ms }) => {
+            assert_eq!(waiting_for, "bestmove");
+            assert_eq!(timeout_ms, 200);
+        }
+        other => panic!("Expected EngineTimeout, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_handshake_timeout() {
+    let script = "id name Slow\n@hang\n";
+    let (mut config, _) = mock_engine("handshake_timeout", script);
+    config.timeout_ms = Some(200);
+
+    match Engine::with_config(&config) {
+        Err(ChessError::EngineTimeout { waiting_for, .. }) => assert_eq!(waiting_for, "uciok"),
+        Err(other) => panic!("Expected EngineTimeout, got {:?}", other),
+        Ok(_) => panic!("Expected EngineTimeout, engine started"),
+    }
+}
+
+#[test]
+fn test_crash_during_search() {
+    let script = format!("{}\n[go]\ninfo depth 1 score cp 5 pv e2e4\n@exit 3\n", HANDSHAKE);
+    let (config, _) = mock_engine("crash", &script);
+    let mut engine = Engine::with_config(&config).unwrap();
+
+    match engine.process_position(&UciPosition::startpos()) {
+        Err(ChessError::EngineTerminated { waiting_for }) => assert_eq!(waiting_for, "bestmove"),
+        other => panic!("Expected EngineTerminated, got {:?}", other),
+    }
+    // further commands fail cleanly instead of panicking
+    assert!(engine.new_game().is_err());
+}
+
+#[test]
+fn test_crash_during_handshake() {
+    let (config, _) = mock_engine("crash_handshake", "id name Crashy\n@exit 1\n");
+    assert!(matches!(
+        Engine::with_config(&config),
+        Err(ChessError::EngineTerminated { .. })
+    ));
+}
+
+#[test]
+fn test_missing_engine_binary() {
+    let config = EngineConfig {
+        engine_path: Some("/nonexistent/analyzer-test-engine".to_string()),
+        ..Default::default()
+    };
+    match Engine::with_config(&config) {
+        Err(ChessError::EngineStart { path, .. }) => assert_eq!(path, "/nonexistent/analyzer-test-engine"),
+        Err(other) => panic!("Expected EngineStart, got {:?}", other),
+        Ok(_) => panic!("Expected EngineStart, engine started"),
+    }
+}
+
+#[test]
+fn test_analysis_keeps_latest_line_per_slot() {
+    let mut analysis = Analysis::default();
+    analysis.record_info("info depth 8 multipv 2 score cp 10 pv d2d4");
+    analysis.record_info("info depth 8 multipv 1 score cp 20 pv e2e4");
+    analysis.record_info("info depth 9 multipv 1 score cp 25 pv e2e4 c7c5");
+    analysis.record_best_move("bestmove e2e4");
+
+    assert_eq!(analysis.lines.len(), 2);
+    assert_eq!(analysis.lines[0].depth, 9);
+    assert_eq!(analysis.lines[0].score, Score::Centipawns(25));
+    assert_eq!(analysis.lines[1].multipv, 2);
+    assert_eq!(analysis.ponder, None);
+}
+
+#[test]
+fn test_score_display() {
+    assert_eq!(Score::Centipawns(35).to_string(), "+0.35");
+    assert_eq!(Score::Centipawns(-120).to_string(), "-1.20");
+    assert_eq!(Score::Mate(3).to_string(), "#3");
+    assert_eq!(Score::Mate(-2).to_string(), "#-2");
+}
+
+#[test]
+fn test_engine_as_backend() {
+    use analyzer::engine::backend::{EngineBackend, SearchLimits};
+
+    let (config, log) = mock_engine("backend", &format!("{}{}", HANDSHAKE, MULTIPV_SEARCH));
+    let mut backend: Box<dyn EngineBackend> = Box::new(Engine::with_config(&config).unwrap());
+
+    assert_eq!(backend.name(), "MockEngine 1.0");
+    let limits = SearchLimits { nodes: Some(1000), ..Default::default() };
+    let analysis = backend.analyse(&UciPosition::startpos(), &limits).unwrap();
+    assert_eq!(analysis.best_move, "e2e4");
+    backend.quit();
+
+    assert!(sent_commands(&log).contains(&"go nodes 1000".to_string()));
+}
+
+#[test]
+fn test_chess960_needs_the_engine_option() {
+    use analyzer::engine::backend::EngineBackend;
+
+    let handshake = format!("{}option name UCI_Chess960 type check default false\n", HANDSHAKE);
+    let (config, log) = mock_engine("chess960", &format!("{}{}", handshake, MULTIPV_SEARCH));
+    let mut engine = Engine::with_config(&config).unwrap();
+    engine.set_chess960(true).unwrap();
+    engine.new_game().unwrap();
+    engine.quit();
+
+    let commands = sent_commands(&log);
+    let option = commands.iter().position(|c| c == "setoption name UCI_Chess960 value true");
+    let new_game = commands.iter().position(|c| c == "ucinewgame");
+    assert!(option.is_some() && option < new_game, "{:?}", commands);
+
+    // without the option only standard games can be analysed
+    let (config, log) = mock_engine("standard_only", &format!("{}{}", HANDSHAKE, MULTIPV_SEARCH));
+    let mut engine = Engine::with_config(&config).unwrap();
+    assert!(engine.set_chess960(false).is_ok());
+    assert!(matches!(
+        engine.set_chess960(true),
+        Err(ChessError::InvalidEngineOption { .. })
+    ));
+    engine.quit();
+    assert!(!sent_commands(&log).iter().any(|c| c.contains("UCI_Chess960")));
+}
@@ -0,0 +1,100 @@
+use analyzer::engine::position::UciPosition;
+use analyzer::pgn::Pgn;
+use std::fs;
+
+#[test]
+fn test_moves_split_across_lines() {
+    let contents = fs::read_to_string("tests/pgn/1.pgn").unwrap();
+    let pgn = Pgn::new(contents);
+
+    // 30. Qxb3 1-0 is the last move, so 59 plies in total
+    assert_eq!(pgn.moves.len(), 59);
+    assert_eq!(pgn.moves[14].san, "exf6");
+    assert_eq!(pgn.moves[15].san, "Bxf6");
+    assert_eq!(pgn.moves[40].san, "Rab1");
+}
+
+#[test]
+fn test_nested_variations_and_comments_are_skipped() {
+    let contents = fs::read_to_string("tests/pgn/2.pgn").unwrap();
+    let pgn = Pgn::new(contents);
+    let sans: Vec<&str> = pgn.moves.iter().map(|m| m.san.as_str()).collect();
+    assert_eq!(sans, ["e4", "e5", "d4", "f6", "d5", "c5", "dxc6", "Ne7", "cxd7+", "Qxd7"]);
+
+    let pgn = Pgn::new("1. e4 {best by test} e5 ; a line comment\n2.Nf3 $1 Nc6 1/2-1/2".to_string());
+    let sans: Vec<&str> = pgn.moves.iter().map(|m| m.san.as_str()).collect();
+    assert_eq!(sans, ["e4", "e5", "Nf3", "Nc6"]);
+}
+
+#[test]
+fn test_split_games() {
+    let first = fs::read_to_string("tests/pgn/1.pgn").unwrap();
+    let second = fs::read_to_string("tests/pgn/3.pgn").unwrap();
+    let contents = format!("{}\n\n{}\n", first, second);
+
+    let games = Pgn::split_games(&contents);
+    assert_eq!(games.len(), 2);
+    assert!(games[0].contains("Josefg02"));
+    assert!(games[1].contains("gxh8=Q"));
+
+    let pgns = Pgn::parse_all(contents);
+    assert_eq!(pgns[0].moves.len(), 59);
+    assert_eq!(pgns[1].moves.len(), 10);
+}
+
+#[test]
+fn test_uci_positions() {
+    let mut pgn = Pgn::new("1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6 4. O-O Nxe4".to_string());
+    let positions = pgn.uci_positions();
+
+    assert_eq!(positions.len(), 8);
+    assert_eq!(positions[0].to_command(), "position startpos moves e2e4");
+    assert_eq!(
+        positions[7].moves,
+        ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1", "f6e4"]
+    );
+}
+
+#[test]
+fn test_uci_positions_promotion() {
+    let contents = fs::read_to_string("tests/pgn/3.pgn").unwrap();
+    let mut pgn = Pgn::new(contents);
+    let positions = pgn.uci_positions();
+    assert_eq!(positions.last().unwrap().moves[8], "g7h8q");
+}
+
+#[test]
+fn test_uci_position_command() {
+    assert_eq!(UciPosition::startpos().to_command(), "position startpos");
+
+    let fen = "8/8/8/8/8/8/4k3/4K3 w - - 0 1";
+    let mut position = UciPosition::from_fen(fen);
+    assert_eq!(position.to_command(), format!("position fen {}", fen));
+    position.push("e1d1");
+    assert_eq!(position.to_command(), format!("position fen {} moves e1d1", fen));
+}
+
+#[test]
+fn test_uci_positions_full_game() {
+    let contents = fs::read_to_string("tests/pgn/1.pgn").unwrap();
+    let mut pgn = Pgn::new(contents);
+    let positions = pgn.uci_positions();
+
+    assert_eq!(positions.len(), 59);
+    // 19. O-O
+    assert_eq!(positions[36].moves[36], "e1g1");
+}
+
+#[test]
+fn test_clock_comments() {
+    let pgn = Pgn::new(
+        "[Event \"Blitz\"]\n\n1. e4 {[%clk 0:02:59.9]} 1... e5 { [%clk 0:02:58] [%eval 0.2] } 2. Nf3 ( 2. Bc4 {[%clk 0:00:01]} ) 2... Nc6 *"
+            .to_string(),
+    );
+    assert_eq!(pgn.moves.len(), 4);
+    assert_eq!(
+        pgn.clocks,
+        [Some("0:02:59.9".to_string()), Some("0:02:58".to_string()), None, None]
+    );
+    assert_eq!(pgn.headers.event(), Some("Blitz"));
+}
@@ -0,0 +1,139 @@
+use analyzer::board::Board;
+use analyzer::classification::{review_game, ClassificationThresholds};
+use analyzer::color::Color;
+use analyzer::engine::analysis::Analysis;
+use analyzer::engine::backend::SearchLimits;
+use analyzer::engine::mock::MockBackend;
+use analyzer::error::ChessError;
+use analyzer::game_analysis::{analyse_game_with_lookups, Lookups};
+use analyzer::pgn::Pgn;
+use analyzer::polyglot::{zobrist_key, BookEntry, PolyglotBook, ENTRY_SIZE};
+use analyzer::r#move::Move;
+use analyzer::report::PlyRecord;
+
+/// Plays UCI moves from the starting position and returns the board.
+fn play(moves: &[&str]) -> Board {
+    let mut board = Board::init();
+    for (index, uci) in moves.iter().enumerate() {
+        let mov = Move::from_uci(&board, uci, index as u16).unwrap();
+        board.apply_move(&mov);
+    }
+    board
+}
+
+fn key_after(moves: &[&str]) -> u64 {
+    let side_to_move = if moves.len().is_multiple_of(2) { Color::White } else { Color::Black };
+    zobrist_key(&play(moves), side_to_move)
+}
+
+fn entry(moves: &[&str], uci: &str, weight: u16) -> BookEntry {
+    BookEntry {
+        key: key_after(moves),
+        raw_move: BookEntry::encode_move(uci).unwrap(),
+        weight,
+        learn: 0,
+    }
+}
+
+#[test]
+fn test_keys_match_the_polyglot_reference() {
+    // test positions from the Polyglot book format specification
+    let cases: [(&[&str], u64); 9] = [
+        (&[], 0x463b96181691fc9c),
+        (&["e2e4"], 0x823c9b50fd114196),
+        (&["e2e4", "d7d5"], 0x0756b94461c50fb0),
+        (&["e2e4", "d7d5", "e4e5"], 0x662fafb965db29d4),
+        // en passant is possible
+        (&["e2e4", "d7d5", "e4e5", "f7f5"], 0x22a48b5a8e47ff78),
+        (&["e2e4", "d7d5", "e4e5", "f7f5", "e1e2"], 0x652a607ca3f242c1),
+        (&["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"], 0x00fdd303c946bdd9),
+        (&["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"], 0x3c8123ea7b067637),
+        (&["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"], 0x5c3f9b829b279560),
+    ];
+    for (moves, key) in cases {
+        assert_eq!(key_after(moves), key, "{:?}", moves);
+    }
+}
+
+#[test]
+fn test_entries_round_trip_through_bytes() {
+    let entry = BookEntry { key: 0x0123456789abcdef, raw_move: 0x031c, weight: 7, learn: 42 };
+    let bytes = entry.to_bytes();
+    assert_eq!(bytes.len(), ENTRY_SIZE);
+    assert_eq!(&bytes[..8], &[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+    assert_eq!(BookEntry::from_bytes(bytes), entry);
+
+    for uci in ["e2e4", "g1f3", "e1h1", "a7a8q", "b2a1n"] {
+        let raw_move = BookEntry::encode_move(uci).unwrap();
+        assert_eq!(BookEntry { key: 0, raw_move, weight: 0, learn: 0 }.uci(), uci);
+    }
+    assert!(BookEntry::encode_move("e2e9").is_err());
+}
+
+#[test]
+fn test_lookup_and_move_selection() {
+    let book = PolyglotBook::from_entries(vec![
+        entry(&["e2e4"], "e7e5", 5),
+        entry(&[], "d2d4", 1),
+        entry(&[], "e2e4", 3),
+        // not legal in the position, so never offered
+        entry(&[], "e2e5", 9),
+    ]);
+    let reread = PolyglotBook::from_bytes(&book.to_bytes());
+    assert_eq!(reread.len(), 4);
+
+    let start = Board::init();
+    assert_eq!(reread.entries(key_after(&[])).len(), 3);
+    let moves: Vec<(String, u16)> = reread.moves(&start, 0).into_iter().map(|book| (book.mov.san, book.weight)).collect();
+    assert_eq!(moves, [("e4".to_string(), 3), ("d4".to_string(), 1)]);
+
+    // weights 3 and 1 split the range of random numbers 3:1
+    let pick = |random| reread.weighted_move(&start, 0, random).unwrap().mov.san;
+    assert_eq!([pick(0), pick(2), pick(3), pick(4), pick(7)], ["e4", "e4", "d4", "e4", "d4"]);
+
+    assert!(reread.contains(&play(&["e2e4"]), 1, "e7e5"));
+    assert!(!reread.contains(&play(&["e2e4"]), 1, "c7c5"));
+    assert!(reread.moves(&play(&["d2d4"]), 1).is_empty());
+    assert!(reread.weighted_move(&play(&["d2d4"]), 1, 0).is_none());
+}
+
+#[test]
+fn test_castling_entries() {
+    let opening = ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"];
+    let book = PolyglotBook::from_entries(vec![entry(&opening, "e1h1", 1)]);
+    let moves = book.moves(&play(&opening), 6);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].mov.san, "O-O");
+    assert!(book.contains(&play(&opening), 6, "e1g1"));
+}
+
+#[test]
+fn test_open_rejects_truncated_files() {
+    let path = std::env::temp_dir().join(format!("analyzer-truncated-{}.bin", std::process::id()));
+    std::fs::write(&path, [0u8; ENTRY_SIZE + 3]).unwrap();
+    assert!(matches!(PolyglotBook::open(&path), Err(ChessError::FileError { .. })));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_book_moves_are_not_analysed() {
+    let book = PolyglotBook::from_entries(vec![entry(&[], "e2e4", 1), entry(&["e2e4"], "e7e5", 1)]);
+    let mut backend = MockBackend::with_responder(|_, _| {
+        Ok(Analysis::from_uci_lines(["info depth 10 score cp 20 pv g1f3", "bestmove g1f3"]))
+    });
+    let mut pgn = Pgn::new("1. e4 e5 2. Nf3 Nc6 *".to_string());
+
+    let plies = analyse_game_with_lookups(&mut backend, &mut pgn, &SearchLimits::depth(10), Lookups { book: Some(&book), ..Default::default() }).unwrap();
+
+    // the start position and the one after 1. e4 lie between book moves
+    let searched: Vec<usize> = backend.positions.iter().map(|position| position.moves.len()).collect();
+    assert_eq!(searched, [2, 3, 4]);
+    assert_eq!(plies.iter().map(|ply| ply.book).collect::<Vec<_>>(), [true, true, false, false]);
+
+    let thresholds = ClassificationThresholds::default();
+    let reviews = review_game(&plies, &thresholds);
+    assert_eq!(reviews.iter().map(|review| review.ply).collect::<Vec<_>>(), [2, 3]);
+    let record = PlyRecord::new(1, &plies[1], &thresholds);
+    assert_eq!(record.classification, Some("book"));
+    assert_eq!(record.cp_loss, None);
+}
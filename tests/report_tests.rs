@@ -0,0 +1,153 @@
+use analyzer::classification::{review_game, ClassificationThresholds};
+use analyzer::eco::EcoTable;
+use analyzer::engine::analysis::Analysis;
+use analyzer::engine::backend::SearchLimits;
+use analyzer::engine::mock::MockBackend;
+use analyzer::game_analysis::{analyse_game, PlyAnalysis};
+use analyzer::pgn::Pgn;
+use analyzer::report::{ReportFormat, ReportWriter, SCHEMA_VERSION};
+use analyzer::summary::{GameSummary, PlayerTotals};
+use serde_json::Value;
+
+const GAME: &str = "[White \"Alice\"]\n[Black \"Bob, Jr\"]\n\n\
+    1. e4 { [%clk 0:05:00] } e5 { [%clk 0:04:58] } 2. Qh5 *";
+
+fn analysis(score: &str, pv: &str) -> Analysis {
+    Analysis::from_uci_lines([
+        format!("info depth 12 score {} pv {}", score, pv),
+        format!("bestmove {}", pv.split_whitespace().next().unwrap()),
+    ])
+}
+
+fn analysed_game() -> (Pgn, Vec<PlyAnalysis>) {
+    let mut backend = MockBackend::new(vec![
+        analysis("cp 30", "e2e4 e7e5"),
+        analysis("cp -30", "e7e5 g1f3"),
+        analysis("cp 30", "g1f3 b8c6"),
+        analysis("mate 2", "d8h4"),
+    ]);
+    let mut pgn = Pgn::new(GAME.to_string());
+    let plies = analyse_game(&mut backend, &mut pgn, &SearchLimits::depth(12)).unwrap();
+    (pgn, plies)
+}
+
+/// Writes the analysed game followed by a failed one in `format`.
+fn report(format: ReportFormat) -> String {
+    let (pgn, plies) = analysed_game();
+    let thresholds = ClassificationThresholds::default();
+    let mut totals = PlayerTotals::default();
+    totals.add_game(
+        pgn.headers.white(),
+        pgn.headers.black(),
+        &GameSummary::from_reviews(&review_game(&plies, &thresholds)),
+    );
+
+    let table = EcoTable::builtin();
+    let mut output = Vec::new();
+    let mut writer = ReportWriter::new(&mut output, format, thresholds);
+    writer.write_game(&pgn.headers, table.classify(&pgn).as_ref(), Ok(&plies)).unwrap();
+    writer.write_game(&pgn.headers, None, Err("engine crashed".to_string())).unwrap();
+    writer.finish(&totals).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_json_document() {
+    let document: Value = serde_json::from_str(&report(ReportFormat::Json)).unwrap();
+    assert_eq!(document["schema_version"], SCHEMA_VERSION);
+
+    let games = document["games"].as_array().unwrap();
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0]["white"], "Alice");
+    assert_eq!(games[0]["error"], Value::Null);
+    assert_eq!(games[0]["eco"], "C20");
+    assert_eq!(games[0]["opening"], "King's Pawn Game");
+    assert_eq!(games[0]["out_of_book"], 3);
+    assert_eq!(games[1]["eco"], Value::Null);
+    assert_eq!(games[0]["white_stats"]["moves"], 2);
+    assert_eq!(games[1]["error"], "engine crashed");
+    assert_eq!(games[1]["plies"].as_array().unwrap().len(), 0);
+
+    let plies = games[0]["plies"].as_array().unwrap();
+    assert_eq!(plies.len(), 3);
+    assert_eq!(plies[0]["ply"], 1);
+    assert_eq!(plies[0]["san"], "e4");
+    assert_eq!(plies[0]["uci"], "e2e4");
+    assert_eq!(plies[0]["clock"], "0:05:00");
+    assert_eq!(plies[0]["eval_before"]["cp"], 30);
+    assert_eq!(plies[0]["classification"], "best");
+    assert_eq!(plies[1]["side"], "black");
+    assert_eq!(plies[1]["pv"], serde_json::json!(["e7e5", "g1f3"]));
+    assert_eq!(plies[2]["move_number"], 2);
+    assert_eq!(plies[2]["eval_after"]["mate"], -2);
+    assert_eq!(plies[2]["classification"], "blunder");
+    assert!(plies[2]["fen"].as_str().unwrap().starts_with("rnbqkbnr/pppp1ppp/8/4p2Q/4P3/8/PPPP1PPP/RNB1KBNR b"));
+
+    let players = document["players"].as_array().unwrap();
+    assert_eq!(players[0]["name"], "Alice");
+    assert_eq!(players[0]["blunder"], 1);
+}
+
+#[test]
+fn test_ndjson_records_are_tagged() {
+    let output = report(ReportFormat::Ndjson);
+    let records: Vec<Value> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let types: Vec<&str> = records.iter().map(|record| record["type"].as_str().unwrap()).collect();
+    assert_eq!(types, ["ply", "ply", "ply", "game", "game", "player", "player"]);
+    assert_eq!(records[2]["game"], 1);
+    assert_eq!(records[4]["game"], 2);
+    assert_eq!(records[6]["name"], "Bob, Jr");
+}
+
+#[test]
+fn test_csv_table() {
+    let output = report(ReportFormat::Csv);
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].starts_with("type,game,ply,move_number,side,san,uci,fen,eval_before,eval_after,"));
+    let columns = lines[0].split(',').count();
+
+    assert!(lines[1].starts_with("ply,1,1,1,white,e4,e2e4,"));
+    assert!(lines[1].contains(",30,30,0,e2e4,e2e4 e7e5,best,,,0:05:00,"));
+    assert!(lines[3].contains(",#-2,"));
+    // one row per side for analysed games, one for failed ones
+    assert!(lines[4].starts_with("game,1,,,white,"));
+    assert!(lines[5].starts_with("game,1,,,black,"));
+    assert!(lines[6].starts_with("game,2,") && lines[6].contains("engine crashed"));
+    // fields with commas are quoted
+    assert!(lines[7..].iter().any(|line| line.starts_with("player,") && line.contains("\"Bob, Jr\"")));
+    assert!(lines.iter().filter(|line| !line.contains('"')).all(|line| line.split(',').count() == columns));
+}
+
+#[test]
+fn test_empty_csv_has_header() {
+    let mut output = Vec::new();
+    let writer = ReportWriter::new(&mut output, ReportFormat::Csv, ClassificationThresholds::default());
+    writer.finish(&PlayerTotals::default()).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.lines().count(), 1);
+    assert!(output.starts_with("type,game,"));
+}
+
+#[test]
+fn test_games_from_a_fen_start_with_blacks_move() {
+    let mut backend = MockBackend::new(vec![
+        analysis("cp -90", "e8d7 e1d2"),
+        analysis("cp 80", "e2e4 d7c6"),
+        analysis("cp -100", "d7c6 e4e5"),
+    ]);
+    let mut pgn = Pgn::new("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]\n\n1... Kd7 2. e4 *".to_string());
+    let plies = analyse_game(&mut backend, &mut pgn, &SearchLimits::depth(12)).unwrap();
+    let thresholds = ClassificationThresholds::default();
+
+    let mut output = Vec::new();
+    let mut writer = ReportWriter::new(&mut output, ReportFormat::Json, thresholds);
+    writer.write_game(&pgn.headers, None, Ok(&plies)).unwrap();
+    writer.finish(&PlayerTotals::default()).unwrap();
+    let document: Value = serde_json::from_slice(&output).unwrap();
+
+    let plies = document["games"][0]["plies"].as_array().unwrap();
+    assert_eq!((&plies[0]["move_number"], &plies[0]["side"]), (&Value::from(1), &Value::from("black")));
+    assert_eq!((&plies[1]["move_number"], &plies[1]["side"]), (&Value::from(2), &Value::from("white")));
+    assert_eq!(document["games"][0]["black_stats"]["moves"], 1);
+    assert_eq!(document["games"][0]["white_stats"]["moves"], 1);
+}
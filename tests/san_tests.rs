@@ -0,0 +1,75 @@
+use analyzer::board::Board;
+use analyzer::color::Color;
+use analyzer::r#move::Move;
+
+/// Plays UCI moves from the starting position and returns the board.
+fn play(moves: &[&str]) -> Board {
+    let mut board = Board::init();
+    for (index, uci) in moves.iter().enumerate() {
+        let mov = Move::from_uci(&board, uci, index as u16).unwrap();
+        board.apply_move(&mov);
+    }
+    board
+}
+
+/// SAN of `uci` played after `moves`.
+fn san_after(moves: &[&str], uci: &str) -> String {
+    let board = play(moves);
+    Move::from_uci(&board, uci, moves.len() as u16).unwrap().to_san(&board)
+}
+
+#[test]
+fn test_plain_moves() {
+    assert_eq!(san_after(&[], "e2e4"), "e4");
+    assert_eq!(san_after(&[], "g1f3"), "Nf3");
+    assert_eq!(san_after(&["e2e4", "d7d5"], "e4d5"), "exd5");
+    assert_eq!(san_after(&["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"], "e1g1"), "O-O");
+}
+
+#[test]
+fn test_disambiguation() {
+    // by file
+    assert_eq!(san_after(&["d2d4", "d7d5", "g1f3", "g8f6"], "b1d2"), "Nbd2");
+    // by rank when both pieces share the file
+    assert_eq!(san_after(&["b1c3", "a7a6", "c3e4", "a6a5", "e4g5", "b7b6"], "g1f3"), "N1f3");
+    assert_eq!(san_after(&["b1c3", "a7a6", "c3e4", "a6a5", "e4g5", "b7b6"], "g5f3"), "N5f3");
+    // a pinned knight is no rival
+    assert_eq!(san_after(&["e2e4", "e7e5", "d2d4", "f8b4", "b1c3", "a7a6"], "g1e2"), "Ne2");
+}
+
+#[test]
+fn test_check_and_mate_suffixes() {
+    assert_eq!(san_after(&["e2e4", "d7d5"], "f1b5"), "Bb5+");
+    assert_eq!(san_after(&["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6"], "h5f7"), "Qxf7#");
+    assert_eq!(san_after(&["f2f3", "e7e5", "g2g4"], "d8h4"), "Qh4#");
+    // the king can escape
+    assert_eq!(san_after(&["e2e4", "e7e6", "d2d4"], "f8b4"), "Bb4+");
+}
+
+#[test]
+fn test_promotion() {
+    let moves = ["h2h4", "g7g5", "h4g5", "g8f6", "g5g6", "f6e4", "g6g7", "e4d6"];
+    assert_eq!(san_after(&moves, "g7h8q"), "gxh8=Q");
+    assert_eq!(san_after(&moves, "g7g8n"), "g8=N");
+}
+
+#[test]
+fn test_legal_moves() {
+    let board = Board::init();
+    let moves = board.legal_moves(0);
+    assert_eq!(moves.len(), 20);
+    let total: usize = moves
+        .iter()
+        .map(|mov| {
+            let mut after = board.clone();
+            after.apply_move(mov);
+            after.legal_moves(1).len()
+        })
+        .sum();
+    assert_eq!(total, 400);
+
+    // checkmated: no moves at all
+    let board = play(&["f2f3", "e7e5", "g2g4", "d8h4"]);
+    assert!(!board.has_legal_moves(4));
+    assert!(board.is_in_check(Color::White));
+}
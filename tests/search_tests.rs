@@ -0,0 +1,115 @@
+use analyzer::engine::analysis::Score;
+use analyzer::engine::backend::{EngineBackend, SearchLimits};
+use analyzer::engine::position::UciPosition;
+use analyzer::engine::search::NativeEngine;
+use analyzer::error::ChessError;
+use analyzer::game_analysis::analyse_game;
+use analyzer::pgn::Pgn;
+
+fn position(moves: &[&str]) -> UciPosition {
+    let mut position = UciPosition::startpos();
+    for uci in moves {
+        position.push(uci);
+    }
+    position
+}
+
+#[test]
+fn test_finds_mate_in_one() {
+    let mut engine = NativeEngine::new();
+    let scholars = position(&["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6"]);
+    let analysis = engine.analyse(&scholars, &SearchLimits::depth(3)).unwrap();
+    assert_eq!(analysis.best_move, "h5f7");
+    assert_eq!(analysis.score(), Some(Score::Mate(1)));
+    assert_eq!(analysis.best_line().unwrap().pv, ["h5f7"]);
+}
+
+#[test]
+fn test_takes_a_hanging_queen() {
+    let mut engine = NativeEngine::new();
+    let analysis = engine.analyse(&position(&["e2e4", "d7d5", "d1g4"]), &SearchLimits::depth(2)).unwrap();
+    assert_eq!(analysis.best_move, "c8g4");
+    let Some(Score::Centipawns(cp)) = analysis.score() else {
+        panic!("expected a centipawn score, got {:?}", analysis.score());
+    };
+    assert!(cp > 500, "{}", cp);
+}
+
+#[test]
+fn test_limits() {
+    let mut engine = NativeEngine::new();
+    let start = UciPosition::startpos();
+    let analysis = engine.analyse(&start, &SearchLimits::depth(1)).unwrap();
+    assert_eq!(analysis.best_line().unwrap().depth, 1);
+
+    // the first iteration always completes, so there is a move to report
+    let limits = SearchLimits { nodes: Some(1), ..Default::default() };
+    let analysis = engine.analyse(&start, &limits).unwrap();
+    assert_eq!(analysis.best_line().unwrap().depth, 1);
+    assert!(!analysis.best_move.is_empty());
+
+    let limits = SearchLimits { movetime_ms: Some(50), ..Default::default() };
+    assert!(engine.analyse(&start, &limits).unwrap().best_line().is_some());
+}
+
+#[test]
+fn test_positions_without_moves_and_invalid_ones() {
+    let mut engine = NativeEngine::new();
+    let mated = position(&["f2f3", "e7e5", "g2g4", "d8h4"]);
+    let analysis = engine.analyse(&mated, &SearchLimits::depth(2)).unwrap();
+    assert_eq!(analysis.best_move, "(none)");
+    assert_eq!(analysis.score(), Some(Score::Mate(0)));
+    let stalemated = UciPosition::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 60");
+    let analysis = engine.analyse(&stalemated, &SearchLimits::depth(2)).unwrap();
+    assert_eq!((analysis.best_move.as_str(), analysis.score()), ("(none)", Some(Score::Centipawns(0))));
+
+    // Black to move mates from a FEN, with moves played after it
+    let mut from_fen = UciPosition::from_fen("6k1/8/8/8/8/1r6/r7/7K w - - 0 40");
+    from_fen.push("h1g1");
+    let analysis = engine.analyse(&from_fen, &SearchLimits::depth(2)).unwrap();
+    assert_eq!(analysis.best_move, "b3b1");
+    assert_eq!(analysis.score().unwrap().to_uci(), "mate 1");
+
+    assert!(matches!(
+        engine.analyse(&UciPosition::from_fen("8/8/8/8 w - - 0 1"), &SearchLimits::depth(1)),
+        Err(ChessError::InvalidFen { .. })
+    ));
+    assert!(matches!(
+        engine.analyse(&position(&["e2e5"]), &SearchLimits::depth(1)),
+        Err(ChessError::IllegalMove { .. })
+    ));
+}
+
+#[test]
+fn test_chess960_castling() {
+    // O-O-O mates: the king goes to c1 and the rook to d1, written as the king taking its rook
+    let mut engine = NativeEngine::new();
+    let position = UciPosition::from_fen("2rkr3/2p1p3/8/8/8/8/8/RK6 w Q - 0 1");
+    let analysis = engine.analyse(&position, &SearchLimits::depth(2)).unwrap();
+    assert_eq!(analysis.best_move, "b1a1");
+    assert_eq!(analysis.score(), Some(Score::Mate(1)));
+
+    // with UCI_Chess960 set, standard castling is also written that way
+    engine.set_chess960(true).unwrap();
+    let castled = |uci: &str| {
+        let mut position = UciPosition::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+        position.push(uci);
+        position
+    };
+    assert!(engine.analyse(&castled("e1h1"), &SearchLimits::depth(1)).is_ok());
+    assert!(matches!(
+        engine.analyse(&castled("e1g1"), &SearchLimits::depth(1)),
+        Err(ChessError::IllegalMove { .. })
+    ));
+}
+
+#[test]
+fn test_analyses_games() {
+    let mut engine = NativeEngine::new();
+    let mut pgn = Pgn::new("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0".to_string());
+    let plies = analyse_game(&mut engine, &mut pgn, &SearchLimits::depth(2)).unwrap();
+    assert_eq!(plies.len(), 7);
+    // 3... Nf6 walks into mate
+    assert_eq!(plies[5].analysis.score(), Some(Score::Mate(1)));
+    assert_eq!(plies[6].before.best_move, "h5f7");
+}
@@ -0,0 +1,112 @@
+use analyzer::board::Board;
+use analyzer::color::Color;
+use analyzer::components::{ColoredPiece, Piece};
+use analyzer::error::ChessError;
+use analyzer::r#move::{Castling, Move};
+use analyzer::setup::Setup;
+use analyzer::square::Square;
+
+fn white(role: Piece) -> ColoredPiece {
+    ColoredPiece::new(Color::White, role)
+}
+
+fn black(role: Piece) -> ColoredPiece {
+    ColoredPiece::new(Color::Black, role)
+}
+
+fn kings() -> Setup {
+    Setup::empty()
+        .put(Square::E1, white(Piece::King))
+        .put(Square::E8, black(Piece::King))
+}
+
+fn problems(setup: &Setup) -> Vec<String> {
+    match setup.validate() {
+        Ok(()) => Vec::new(),
+        Err(ChessError::InvalidPosition { problems }) => problems,
+        Err(error) => panic!("unexpected error {}", error),
+    }
+}
+
+#[test]
+fn test_start_position_is_valid() {
+    let setup = Setup::from_board(Board::init(), Color::White);
+    assert!(setup.validate().is_ok());
+    assert_eq!(setup.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+}
+
+#[test]
+fn test_built_board_plays_moves() {
+    let setup = kings()
+        .put(Square::A7, white(Piece::Pawn))
+        .with_side_to_move(Color::White)
+        .with_move_counters(3, 40);
+    assert_eq!(setup.ply_index(), 78);
+    let mut board = setup.build().unwrap();
+    board.apply_move(&Move::new("a8=Q+".to_string(), 78));
+    assert_eq!(board.piece_at(Square::A8), Some(white(Piece::Queen)));
+}
+
+#[test]
+fn test_reports_every_problem() {
+    let setup = Setup::empty()
+        .put(Square::E1, white(Piece::King))
+        .put(Square::D1, white(Piece::King))
+        .put(Square::C8, white(Piece::Pawn))
+        .with_castling(Color::Black, Castling::Queen);
+    let found = problems(&setup);
+    assert_eq!(found.len(), 4, "{:?}", found);
+    assert!(found.contains(&"White has 2 kings".to_string()));
+    assert!(found.contains(&"Black has 0 kings".to_string()));
+    assert!(found.contains(&"pawn on c8".to_string()));
+    assert!(found.contains(&"Black may castle queenside without king on e8 and rook on a8".to_string()));
+}
+
+#[test]
+fn test_side_not_to_move_in_check() {
+    let setup = kings().put(Square::E4, black(Piece::Rook));
+    assert!(setup.clone().with_side_to_move(Color::White).validate().is_ok());
+    assert_eq!(
+        problems(&setup.with_side_to_move(Color::Black)),
+        ["White is in check with Black to move"]
+    );
+}
+
+#[test]
+fn test_too_many_pieces() {
+    let mut setup = kings();
+    for square in Square::all().skip(8).take(16) {
+        setup = setup.put(square, white(Piece::Knight));
+    }
+    let found = problems(&setup);
+    assert!(found.contains(&"White has 17 pieces".to_string()), "{:?}", found);
+    assert!(found.contains(&"White has 14 promoted pieces but only 8 missing pawns".to_string()));
+
+    let mut pawns = kings();
+    for square in Square::all().skip(8).take(9) {
+        pawns = pawns.put(square, black(Piece::Pawn));
+    }
+    assert_eq!(problems(&pawns), ["Black has 9 pawns"]);
+}
+
+#[test]
+fn test_en_passant_square() {
+    let setup = kings()
+        .put(Square::D5, black(Piece::Pawn))
+        .put(Square::E5, white(Piece::Pawn))
+        .with_side_to_move(Color::White);
+    assert!(setup.clone().with_en_passant(Some(Square::D6)).validate().is_ok());
+
+    assert_eq!(
+        problems(&setup.clone().with_en_passant(Some(Square::D3))),
+        ["en passant square d3 is not on the sixth rank"]
+    );
+    assert_eq!(
+        problems(&setup.clone().with_en_passant(Some(Square::C6))),
+        ["en passant square c6 without a Black pawn on c5"]
+    );
+    assert_eq!(
+        problems(&setup.put(Square::D7, black(Piece::Bishop)).with_en_passant(Some(Square::D6))),
+        ["en passant square d6 with d6 or d7 occupied"]
+    );
+}
@@ -0,0 +1,100 @@
+use analyzer::classification::{MoveClass, MoveReview};
+use analyzer::color::Color;
+use analyzer::engine::analysis::Score;
+use analyzer::pgn::Pgn;
+use analyzer::summary::{move_accuracy, win_probability, GameSummary, PlayerTotals};
+
+fn review(ply: usize, before: i32, after: i32, class: MoveClass) -> MoveReview {
+    MoveReview {
+        ply,
+        san: String::from("e4"),
+        uci: String::from("e2e4"),
+        best_move: String::from("d2d4"),
+        eval_before: Score::Centipawns(before),
+        eval_after: Score::Centipawns(after),
+        cp_loss: (before - after).max(0),
+        class,
+    }
+}
+
+#[test]
+fn test_win_probability() {
+    assert!((win_probability(0) - 50.0).abs() < 1e-9);
+    assert!((win_probability(300) + win_probability(-300) - 100.0).abs() < 1e-9);
+    assert!(win_probability(1000) > 97.0);
+    assert!(win_probability(-1000) < 3.0);
+}
+
+#[test]
+fn test_move_accuracy() {
+    assert!((move_accuracy(60.0, 60.0) - 100.0).abs() < 0.01);
+    // gaining winning chances is not rewarded beyond 100
+    assert!((move_accuracy(40.0, 70.0) - 100.0).abs() < 0.01);
+    assert!(move_accuracy(80.0, 20.0) < 10.0);
+    assert!(move_accuracy(55.0, 50.0) > move_accuracy(55.0, 40.0));
+}
+
+#[test]
+fn test_game_summary_per_side() {
+    let reviews = [
+        review(0, 30, 30, MoveClass::Best),
+        review(1, -30, -80, MoveClass::Inaccuracy),
+        review(2, 80, 70, MoveClass::Excellent),
+        review(3, -70, -470, MoveClass::Blunder),
+    ];
+    let summary = GameSummary::from_reviews(&reviews);
+
+    let white = summary.player(Color::White);
+    assert_eq!((white.games, white.moves), (1, 2));
+    assert!((white.acpl() - 5.0).abs() < 1e-9);
+    assert_eq!(white.count(MoveClass::Best), 1);
+    assert_eq!(white.count(MoveClass::Excellent), 1);
+
+    let black = summary.player(Color::Black);
+    assert!((black.acpl() - 225.0).abs() < 1e-9);
+    assert_eq!(black.count(MoveClass::Blunder), 1);
+    assert!(white.accuracy() > black.accuracy());
+    assert!(black.accuracy() > 0.0 && white.accuracy() <= 100.0);
+}
+
+#[test]
+fn test_missed_mates_are_capped() {
+    let mut missed = review(0, 0, 0, MoveClass::Blunder);
+    missed.eval_before = Score::Mate(1);
+    missed.cp_loss = 1999;
+    let summary = GameSummary::from_reviews(&[missed]);
+    assert!((summary.white.acpl() - 1000.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_totals_per_player() {
+    let pgns = Pgn::parse_all(
+        "[White \"Alice\"]\n[Black \"Bob\"]\n\n1. e4 e5 1-0\n\n\
+         [White \"Bob\"]\n[Black \"Alice\"]\n\n1. d4 d5 0-1\n\n\
+         [Event \"Casual\"]\n\n1. c4 *\n"
+            .to_string(),
+    );
+    let summaries = [
+        GameSummary::from_reviews(&[review(0, 20, 20, MoveClass::Best), review(1, -20, -120, MoveClass::Mistake)]),
+        GameSummary::from_reviews(&[review(0, 20, -30, MoveClass::Inaccuracy), review(1, -20, -20, MoveClass::Best)]),
+        GameSummary::from_reviews(&[review(0, 20, 20, MoveClass::Best)]),
+    ];
+
+    let mut totals = PlayerTotals::default();
+    for (pgn, summary) in pgns.iter().zip(&summaries) {
+        totals.add_game(pgn.headers.white(), pgn.headers.black(), summary);
+    }
+
+    let names: Vec<&str> = totals.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["?", "Alice", "Bob"]);
+
+    let alice = totals.get("Alice").unwrap();
+    assert_eq!((alice.games, alice.moves), (2, 2));
+    assert_eq!(alice.count(MoveClass::Best), 2);
+    assert!((alice.acpl() - 0.0).abs() < 1e-9);
+
+    let bob = totals.get("Bob").unwrap();
+    assert_eq!(bob.count(MoveClass::Mistake), 1);
+    assert_eq!(bob.count(MoveClass::Inaccuracy), 1);
+    assert!((bob.acpl() - 75.0).abs() < 1e-9);
+}
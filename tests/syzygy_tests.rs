@@ -0,0 +1,98 @@
+use analyzer::board::Board;
+use analyzer::color::Color;
+use analyzer::engine::analysis::Analysis;
+use analyzer::engine::backend::SearchLimits;
+use analyzer::engine::mock::MockBackend;
+use analyzer::error::ChessError;
+use analyzer::game_analysis::{analyse_game_with_lookups, Lookups};
+use analyzer::pgn::Pgn;
+use analyzer::syzygy::{Tablebase, TablebaseProbe, Wdl};
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("analyzer-syzygy-tests-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_open_indexes_table_files() {
+    let dir = temp_dir("index");
+    for name in ["KQvK.rtbw", "KRPvKR.rtbz", "KRPvKR.rtbw", "notes.txt", "KXvK.rtbw"] {
+        std::fs::write(dir.join(name), []).unwrap();
+    }
+    let tablebase = Tablebase::open(&dir).unwrap();
+    assert_eq!(tablebase.len(), 3);
+    assert_eq!(tablebase.max_pieces(), 5);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(matches!(Tablebase::open(&dir), Err(ChessError::FileError { .. })));
+}
+
+#[test]
+fn test_verdicts_seen_from_either_side() {
+    assert_eq!(-Wdl::Win, Wdl::Loss);
+    assert_eq!(-Wdl::CursedWin, Wdl::BlessedLoss);
+    assert_eq!(-Wdl::Draw, Wdl::Draw);
+    let probe = TablebaseProbe { wdl: Wdl::BlessedLoss, dtz: Some(-105) };
+    assert_eq!(-probe, TablebaseProbe { wdl: Wdl::CursedWin, dtz: Some(105) });
+    assert_eq!(probe.wdl.name(), "blessed-loss");
+    assert_eq!((-probe).to_string(), "drawing, the win takes too long for the 50-move rule (DTZ 105)");
+}
+
+#[test]
+fn test_positions_outside_the_tables_fall_back_to_the_engine() {
+    let dir = temp_dir("fallback");
+    let tablebase = Tablebase::open(&dir).unwrap();
+    assert!(tablebase.is_empty());
+    assert_eq!(tablebase.probe(&Board::init(), Color::White).unwrap(), None);
+
+    let mut backend = MockBackend::with_responder(|_, _| {
+        Ok(Analysis::from_uci_lines(["info depth 10 score cp 20 pv g1f3", "bestmove g1f3"]))
+    });
+    let mut pgn = Pgn::new("1. e4 e5 2. Nf3 *".to_string());
+    let lookups = Lookups { tablebase: Some(&tablebase), ..Default::default() };
+    let plies = analyse_game_with_lookups(&mut backend, &mut pgn, &SearchLimits::depth(10), lookups).unwrap();
+    assert_eq!(plies.len(), 3);
+    assert!(plies.iter().all(|ply| ply.tablebase.is_none()));
+    assert_eq!(backend.positions.len(), 4);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_games_from_a_fen_tag_are_probed_from_there() {
+    let dir = temp_dir("fen");
+    // a KQvK table where White to move wins and Black to move loses
+    let table = [0x71, 0xe8, 0x23, 0x5d, 0x01, 0x00, 0x66, 0x55, 0xee, 0x00, 0x80, 4, 0x80, 0];
+    let mut bytes = table.to_vec();
+    bytes.resize(64, 0);
+    std::fs::write(dir.join("KQvK.rtbw"), bytes).unwrap();
+    let tablebase = Tablebase::open(&dir).unwrap();
+
+    let mut backend = MockBackend::with_responder(|_, _| Ok(Analysis::from_uci_lines(["bestmove a1a1"])));
+    let mut pgn = Pgn::new("[FEN \"8/8/8/8/8/2K5/1Q6/7k b - - 0 60\"]\n\n60... Kg1 61. Kd3 *".to_string());
+    let lookups = Lookups { tablebase: Some(&tablebase), ..Default::default() };
+    let plies = analyse_game_with_lookups(&mut backend, &mut pgn, &SearchLimits::depth(10), lookups).unwrap();
+
+    let verdicts: Vec<Option<Wdl>> = plies.iter().map(|ply| ply.tablebase.map(|probe| probe.wdl)).collect();
+    assert_eq!(verdicts, [Some(Wdl::Loss), Some(Wdl::Win)]);
+    assert_eq!(backend.positions[0].to_command(), "position fen 8/8/8/8/8/2K5/1Q6/7k b - - 0 60");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_unreadable_tables_leave_the_verdict_to_the_engine() {
+    let dir = temp_dir("corrupt");
+    std::fs::write(dir.join("KQvK.rtbw"), b"not a table").unwrap();
+    let tablebase = Tablebase::open(&dir).unwrap();
+
+    let mut backend = MockBackend::with_responder(|_, _| {
+        Ok(Analysis::from_uci_lines(["info depth 10 score mate 5 pv c3d3", "bestmove c3d3"]))
+    });
+    let mut pgn = Pgn::new("[FEN \"8/8/8/8/8/2K5/1Q6/7k b - - 0 60\"]\n\n60... Kg1 61. Kd3 *".to_string());
+    let lookups = Lookups { tablebase: Some(&tablebase), ..Default::default() };
+    let plies = analyse_game_with_lookups(&mut backend, &mut pgn, &SearchLimits::depth(10), lookups).unwrap();
+    assert_eq!(plies.len(), 2);
+    assert!(plies.iter().all(|ply| ply.tablebase.is_none()));
+    assert_eq!(backend.positions.len(), 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,144 @@
+use analyzer::board::Board;
+use analyzer::components::Piece;
+use analyzer::square::Square;
+use analyzer::error::ChessError;
+use analyzer::pgn::Pgn;
+use analyzer::r#move::{Castling, Move};
+
+/// Plays UCI moves from the starting position and returns the board.
+fn play(moves: &[&str]) -> Board {
+    let mut board = Board::init();
+    for (index, uci) in moves.iter().enumerate() {
+        let mov = Move::from_uci(&board, uci, index as u16).unwrap_or_else(|e| panic!("{}: {}", uci, e));
+        board.apply_move(&mov);
+    }
+    board
+}
+
+fn is_illegal(board: &Board, uci: &str, index: u16) -> bool {
+    matches!(Move::from_uci(board, uci, index), Err(ChessError::IllegalMove { .. }))
+}
+
+#[test]
+fn test_simple_moves_round_trip() {
+    let board = Board::init();
+    for (uci, san) in [("e2e4", "e4"), ("e2e3", "e3"), ("g1f3", "Nf3"), ("b1c3", "Nc3")] {
+        let mov = Move::from_uci(&board, uci, 0).unwrap();
+        assert_eq!(mov.san, san);
+        assert_eq!(mov.to_uci().as_deref(), Some(uci));
+    }
+    let reply = Move::from_uci(&play(&["e2e4"]), "e7e5", 1).unwrap();
+    assert_eq!(reply.san, "e5");
+}
+
+#[test]
+fn test_malformed_moves() {
+    let board = Board::init();
+    for uci in ["", "e2", "e2e9", "i2i4", "e2e4x", "e2e4k", "e7e8p", "e2e4qq"] {
+        assert!(
+            matches!(Move::from_uci(&board, uci, 0), Err(ChessError::InvalidMove { .. })),
+            "{} should be rejected",
+            uci
+        );
+    }
+}
+
+#[test]
+fn test_moves_are_checked_against_the_board() {
+    let board = Board::init();
+    // empty square, opponent's piece, blocked and impossible moves
+    assert!(is_illegal(&board, "e4e5", 0));
+    assert!(is_illegal(&board, "e7e5", 0));
+    assert!(is_illegal(&board, "f1c4", 0));
+    assert!(is_illegal(&board, "e2e5", 0));
+    assert!(is_illegal(&board, "g1g3", 0));
+    assert!(is_illegal(&board, "d1d2", 0));
+
+    // a pinned knight may not move
+    let board = play(&["e2e4", "e7e5", "d2d4", "f8b4", "b1c3", "d7d6"]);
+    assert!(is_illegal(&board, "c3d5", 6));
+    // nor may the king step into check
+    let board = play(&["e2e4", "e7e5", "d1h5", "b8c6", "h5f7"]);
+    assert!(is_illegal(&board, "e8e7", 5));
+    assert_eq!(Move::from_uci(&board, "e8f7", 5).unwrap().san, "Kxf7");
+}
+
+#[test]
+fn test_captures_and_en_passant() {
+    let board = play(&["e2e4", "d7d5"]);
+    let capture = Move::from_uci(&board, "e4d5", 2).unwrap();
+    assert!(capture.is_capture);
+    assert_eq!(capture.san, "exd5");
+
+    let mut board = play(&["e2e4", "a7a6", "e4e5", "d7d5"]);
+    let en_passant = Move::from_uci(&board, "e5d6", 4).unwrap();
+    assert!(en_passant.is_capture);
+    assert_eq!(en_passant.san, "exd6");
+    board.apply_move(&en_passant);
+    assert!(board.get_piece_at(Square::D5).is_err(), "captured pawn stays on d5");
+
+    // en passant is only possible right after the double step
+    let board = play(&["e2e4", "a7a6", "e4e5", "d7d5", "g1f3", "a6a5"]);
+    assert!(is_illegal(&board, "e5d6", 6));
+}
+
+#[test]
+fn test_promotion() {
+    let board = play(&["h2h4", "g7g5", "h4g5", "g8f6", "g5g6", "f6e4", "g6g7", "e4d6"]);
+    let promotion = Move::from_uci(&board, "g7h8q", 8).unwrap();
+    assert_eq!(promotion.promotion, Some(Piece::Queen));
+    assert_eq!(promotion.san, "gxh8=Q");
+    assert_eq!(promotion.to_uci().as_deref(), Some("g7h8q"));
+    assert_eq!(Move::from_uci(&board, "g7g8n", 8).unwrap().san, "g8=N");
+    // promotion is mandatory on the last rank and impossible elsewhere
+    assert!(is_illegal(&board, "g7g8", 8));
+    assert!(is_illegal(&board, "a2a3q", 8));
+}
+
+#[test]
+fn test_castling_notations() {
+    let board = play(&["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"]);
+    for uci in ["e1g1", "e1h1"] {
+        let castle = Move::from_uci(&board, uci, 6).unwrap();
+        assert_eq!(castle.castling, Some(Castling::King));
+        assert_eq!(castle.san, "O-O");
+        assert_eq!(castle.to_uci().as_deref(), Some("e1g1"));
+    }
+    // queenside is still blocked
+    assert!(is_illegal(&board, "e1c1", 6));
+    assert!(is_illegal(&board, "e1a1", 6));
+
+    let board = play(&["d2d4", "d7d5", "b1c3", "b8c6", "c1f4", "c8f5", "d1d2", "d8d7"]);
+    let castle = Move::from_uci(&board, "e1a1", 8).unwrap();
+    assert_eq!((castle.castling, castle.san.as_str()), (Some(Castling::Queen), "O-O-O"));
+}
+
+#[test]
+fn test_castling_rules() {
+    // the king moved and came back
+    let board = play(&["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1f1", "a7a6", "f1e1", "a6a5"]);
+    assert!(is_illegal(&board, "e1g1", 10));
+
+    // the bishop on e2 covers f1
+    let board = play(&["e2e4", "e7e5", "g1f3", "f8c5", "f1e2", "d7d6", "f3g5", "c8g4", "g5h3", "g4e2"]);
+    assert!(is_illegal(&board, "e1g1", 10));
+    // attacks on f2 and h2 do not matter
+    let board = play(&["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "b2b3", "f8c5", "c1a3", "f6g4"]);
+    assert!(Move::from_uci(&board, "e1g1", 10).is_ok());
+    let board = play(&["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5", "b2b3", "d8h4", "c1a3", "h4f2"]);
+    assert!(is_illegal(&board, "e1g1", 10), "the king is in check");
+}
+
+#[test]
+fn test_to_uci_on_resolves_san_moves() {
+    let board = Board::init();
+    assert_eq!(Move::new("Nf3".to_string(), 0).to_uci_on(&board).unwrap(), "g1f3");
+    assert_eq!(Move::new("O-O".to_string(), 0).to_uci_on(&board).unwrap(), "e1g1");
+    assert!(Move::new("Nd4".to_string(), 0).to_uci_on(&board).is_err());
+
+    // moves replayed from a PGN compare equal to the engine's notation
+    let mut pgn = Pgn::new("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. O-O *".to_string());
+    pgn.parse_moves();
+    let played: Vec<String> = pgn.moves.iter().map(|m| m.to_uci().unwrap()).collect();
+    assert_eq!(played, ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "e1g1"]);
+}
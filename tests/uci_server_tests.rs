@@ -0,0 +1,124 @@
+use analyzer::config::EngineConfig;
+use analyzer::engine::analysis::Score;
+use analyzer::engine::backend::{EngineBackend, SearchLimits};
+use analyzer::engine::engine::Engine;
+use analyzer::engine::position::UciPosition;
+use analyzer::engine::uci_server::UciServer;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Output shared between the server and the test.
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Output {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_handshake() {
+    let output = Output::default();
+    let mut server = UciServer::new(output.clone());
+    server.run("uci\nsetoption name Hash value 8\nsetoption name Clear Hash\nisready\n".as_bytes());
+
+    let lines = output.lines();
+    assert!(lines[0].starts_with("id name analyzer native"));
+    assert!(lines.contains(&"option name Hash type spin default 16 min 1 max 4096".to_string()));
+    assert!(lines.contains(&"option name Ponder type check default false".to_string()));
+    assert_eq!(lines[lines.len() - 2..], ["uciok", "readyok"]);
+}
+
+#[test]
+fn test_malformed_setoption_is_ignored() {
+    let output = Output::default();
+    let mut server = UciServer::new(output.clone());
+    server.run("setoption value 5\nsetoption\nsetoption name\nsetoption Hash value 8\nisready\n".as_bytes());
+    assert_eq!(output.lines(), ["info string Unknown option ''", "readyok"]);
+}
+
+#[test]
+fn test_searches_from_a_fen() {
+    let output = Output::default();
+    let mut server = UciServer::new(output.clone());
+    server.run("position fen 6k1/8/8/8/8/1r6/r7/7K w - - 0 40 moves h1g1\ngo depth 2\n".as_bytes());
+    assert_eq!(output.lines().last().unwrap(), "bestmove b3b1");
+}
+
+#[test]
+fn test_chess960_castling() {
+    let castle = |options: &str| {
+        let output = Output::default();
+        let mut server = UciServer::new(output.clone());
+        let position = "position fen 4k3/8/8/8/8/8/8/4K2R w K - 0 1 moves e1g1";
+        server.run(format!("{}{}\ngo depth 1\n", options, position).as_bytes());
+        output.lines().last().unwrap().clone()
+    };
+    assert!(castle("").starts_with("bestmove e8"));
+    // in Chess960 castling is the king taking its rook, so e1g1 is a plain king move
+    let chess960 = "setoption name UCI_Chess960 value true\nsetoption name Hash value 8\n";
+    assert_eq!(castle(chess960), "bestmove 0000");
+}
+
+#[test]
+fn test_go_reports_the_best_move() {
+    let output = Output::default();
+    let mut server = UciServer::new(output.clone());
+    server.run("position startpos moves f2f3 e7e5 g2g4\ngo depth 2\n".as_bytes());
+
+    let lines = output.lines();
+    assert!(lines.iter().any(|line| line.starts_with("info depth 1 score mate 1")), "{:?}", lines);
+    assert_eq!(lines.last().unwrap(), "bestmove d8h4");
+}
+
+#[test]
+fn test_infinite_search_waits_for_stop() {
+    let output = Output::default();
+    let mut server = UciServer::new(output.clone());
+    server.handle("position startpos moves f2f3 e7e5 g2g4");
+    server.handle("go infinite");
+    thread::sleep(Duration::from_millis(100));
+    assert!(!output.lines().iter().any(|line| line.starts_with("bestmove")));
+
+    server.handle("stop");
+    assert_eq!(output.lines().last().unwrap(), "bestmove d8h4");
+    assert!(!server.handle("quit"));
+}
+
+#[test]
+fn test_engine_client_drives_the_server() {
+    let config = EngineConfig {
+        engine_path: Some(env!("CARGO_BIN_EXE_analyzer").to_string()),
+        engine_args: vec![String::from("uci")],
+        hash_mb: Some(8),
+        ..Default::default()
+    };
+    let mut engine = Engine::with_config(&config).unwrap();
+    assert!(engine.info().name.as_deref().unwrap().starts_with("analyzer native"));
+
+    let mut position = UciPosition::startpos();
+    for uci in ["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6"] {
+        position.push(uci);
+    }
+    let analysis = engine.analyse(&position, &SearchLimits::depth(3)).unwrap();
+    assert_eq!(analysis.best_move, "h5f7");
+    assert_eq!(analysis.score(), Some(Score::Mate(1)));
+    engine.quit();
+}